@@ -0,0 +1,168 @@
+#ifndef MLUVA_H
+#define MLUVA_H
+
+/* This file is generated by cbindgen from capi/src/lib.rs, do not edit it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum MluvaStatus {
+  MLUVA_STATUS_OK = 0,
+  MLUVA_STATUS_INVALID_ARGUMENT = 1,
+  MLUVA_STATUS_COMPILE_ERROR = 2,
+  MLUVA_STATUS_BYTECODE_ERROR = 3,
+  MLUVA_STATUS_RUNTIME_ERROR = 4,
+} MluvaStatus;
+
+typedef enum MluvaValueTag {
+  MLUVA_VALUE_TAG_VOID = 0,
+  MLUVA_VALUE_TAG_INT = 1,
+  MLUVA_VALUE_TAG_FLOAT = 2,
+  MLUVA_VALUE_TAG_BOOL = 3,
+  MLUVA_VALUE_TAG_STRING = 4,
+  MLUVA_VALUE_TAG_INT64 = 5,
+  MLUVA_VALUE_TAG_BYTE = 6,
+} MluvaValueTag;
+
+/**
+ * Holds compiled modules, modules can import any module that was added to the engine before them.
+ */
+typedef struct MluvaEngine MluvaEngine;
+
+typedef union MluvaValueData {
+  int32_t int_value;
+  int64_t int64_value;
+  uint8_t byte_value;
+  double float_value;
+  bool bool_value;
+  /**
+   * NUL terminated UTF-8 string
+   */
+  char *string_value;
+} MluvaValueData;
+
+/**
+ * Tagged value passed to and returned from Mluva functions.
+ * `tag` is one of `MluvaValueTag`, it is stored as integer since the host can write any value.
+ */
+typedef struct MluvaValue {
+  uint32_t tag;
+  union MluvaValueData data;
+} MluvaValue;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates new engine with the standard modules, free it with `mluva_engine_free`.
+ */
+struct MluvaEngine *mluva_engine_new(void);
+
+/**
+ * # Safety
+ * `engine` must be null or created by `mluva_engine_new` and not freed yet.
+ */
+void mluva_engine_free(struct MluvaEngine *engine);
+
+/**
+ * Compiles source text into module called `name`.
+ * Module can import modules that were already added to the engine,
+ * a module with the same name as an added one fails with `MLUVA_STATUS_INVALID_ARGUMENT`.
+ *
+ * # Safety
+ * `engine` must be valid engine, `name` and `source` valid NUL terminated strings.
+ */
+enum MluvaStatus mluva_engine_compile(struct MluvaEngine *engine,
+                                      const char *name,
+                                      const char *source);
+
+/**
+ * Loads module called `name` from `.mvb` bytecode.
+ * The bytecode is verified, modules it calls must be added to the engine before it.
+ * A module with the same name as an added one fails with `MLUVA_STATUS_INVALID_ARGUMENT`.
+ *
+ * # Safety
+ * `engine` must be valid engine, `name` valid NUL terminated string
+ * and `bytes` must point to `len` readable bytes.
+ */
+enum MluvaStatus mluva_engine_load_bytecode(struct MluvaEngine *engine,
+                                            const char *name,
+                                            const uint8_t *bytes,
+                                            size_t len);
+
+/**
+ * Serializes module called `name` into `.mvb` bytecode.
+ * The bytes must be freed with `mluva_bytes_free`.
+ *
+ * # Safety
+ * `engine` must be valid engine, `name` valid NUL terminated string,
+ * `out_bytes` and `out_len` must be valid for writes.
+ */
+enum MluvaStatus mluva_engine_module_bytecode(struct MluvaEngine *engine,
+                                              const char *name,
+                                              uint8_t **out_bytes,
+                                              size_t *out_len);
+
+/**
+ * Calls `function` from module `module` with `arg_count` arguments.
+ * On success the returned value is written into `out_result`,
+ * free it with `mluva_value_free`.
+ *
+ * # Safety
+ * `engine` must be valid engine, `module` and `function` valid NUL terminated strings,
+ * `args` must point to `arg_count` values (may be null if `arg_count` is 0)
+ * and `out_result` must be valid for writes.
+ */
+enum MluvaStatus mluva_engine_call(struct MluvaEngine *engine,
+                                   const char *module,
+                                   const char *function,
+                                   const struct MluvaValue *args,
+                                   size_t arg_count,
+                                   struct MluvaValue *out_result);
+
+/**
+ * Returns message of the last error or null if there was none.
+ * The string is owned by the engine and valid until the next call on the engine.
+ *
+ * # Safety
+ * `engine` must be null or valid engine.
+ */
+const char *mluva_engine_last_error(const struct MluvaEngine *engine);
+
+/**
+ * Formats value the same way as `print` does, free the result with `mluva_string_free`.
+ *
+ * # Safety
+ * `value` must be null or point to valid value.
+ */
+char *mluva_value_to_string(const struct MluvaValue *value);
+
+/**
+ * Frees string owned by the value and sets the value to void.
+ *
+ * # Safety
+ * `value` must be null or value returned by the library.
+ */
+void mluva_value_free(struct MluvaValue *value);
+
+/**
+ * # Safety
+ * `string` must be null or string returned by the library.
+ */
+void mluva_string_free(char *string);
+
+/**
+ * # Safety
+ * `bytes` and `len` must be null / 0 or returned by `mluva_engine_module_bytecode`.
+ */
+void mluva_bytes_free(uint8_t *bytes, size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MLUVA_H */
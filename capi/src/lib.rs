@@ -0,0 +1,513 @@
+//! C ABI for embedding the Mluva interpreter into non-Rust hosts.
+//!
+//! The header `include/mluva.h` is generated from this file by cbindgen,
+//! run `cargo test -p capi` after changing the API to check that it is up to date.
+//!
+//! Every function that can fail returns `MluvaStatus`, the message of the last error
+//! can be read with `mluva_engine_last_error`.
+//! Strings and values returned by the library are owned by the caller and must be freed
+//! with `mluva_string_free` / `mluva_value_free` / `mluva_bytes_free`.
+
+use std::{
+    collections::HashMap,
+    ffi::{CStr, CString, c_char},
+    panic::{AssertUnwindSafe, catch_unwind},
+    ptr,
+};
+
+use codespan_reporting::{
+    files::SimpleFiles,
+    term::{Config as CodespanConfig, emit_into_string},
+};
+use mluva::{
+    ast::Ast, errors::CompileError, module::Module, runtime::Runtime, stdlib::standard_modules,
+    value::Value,
+};
+
+/// Holds compiled modules, modules can import any module that was added to the engine before them.
+pub struct MluvaEngine {
+    modules: HashMap<String, Module>,
+    files: SimpleFiles<String, String>,
+    last_error: Option<CString>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MluvaStatus {
+    Ok = 0,
+    InvalidArgument = 1,
+    CompileError = 2,
+    BytecodeError = 3,
+    RuntimeError = 4,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MluvaValueTag {
+    Void = 0,
+    Int = 1,
+    Float = 2,
+    Bool = 3,
+    String = 4,
+    Int64 = 5,
+    Byte = 6,
+}
+
+impl MluvaValueTag {
+    fn from_u32(tag: u32) -> Option<Self> {
+        let tag = match tag {
+            0 => Self::Void,
+            1 => Self::Int,
+            2 => Self::Float,
+            3 => Self::Bool,
+            4 => Self::String,
+            5 => Self::Int64,
+            6 => Self::Byte,
+            _ => return None,
+        };
+
+        Some(tag)
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union MluvaValueData {
+    pub int_value: i32,
+    pub int64_value: i64,
+    pub byte_value: u8,
+    pub float_value: f64,
+    pub bool_value: bool,
+    /// NUL terminated UTF-8 string
+    pub string_value: *mut c_char,
+}
+
+/// Tagged value passed to and returned from Mluva functions.
+/// `tag` is one of `MluvaValueTag`, it is stored as integer since the host can write any value.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct MluvaValue {
+    pub tag: u32,
+    pub data: MluvaValueData,
+}
+
+impl MluvaValue {
+    fn void() -> Self {
+        Self {
+            tag: MluvaValueTag::Void as u32,
+            data: MluvaValueData { int_value: 0 },
+        }
+    }
+
+    /// Converts the value into an owned C value, strings are allocated by the library.
+    fn from_value(value: Value) -> Self {
+        match value {
+            Value::Void => Self::void(),
+            Value::Int(int_value) => Self {
+                tag: MluvaValueTag::Int as u32,
+                data: MluvaValueData { int_value },
+            },
+            Value::Int64(int64_value) => Self {
+                tag: MluvaValueTag::Int64 as u32,
+                data: MluvaValueData { int64_value },
+            },
+            Value::Byte(byte_value) => Self {
+                tag: MluvaValueTag::Byte as u32,
+                data: MluvaValueData { byte_value },
+            },
+            Value::Float(float_value) => Self {
+                tag: MluvaValueTag::Float as u32,
+                data: MluvaValueData { float_value },
+            },
+            Value::Bool(bool_value) => Self {
+                tag: MluvaValueTag::Bool as u32,
+                data: MluvaValueData { bool_value },
+            },
+            Value::String(s) => Self {
+                tag: MluvaValueTag::String as u32,
+                data: MluvaValueData {
+                    string_value: into_c_string(s),
+                },
+            },
+        }
+    }
+
+    /// # Safety
+    /// String values must point to valid NUL terminated strings.
+    unsafe fn to_value(self) -> Result<Value, String> {
+        let Some(tag) = MluvaValueTag::from_u32(self.tag) else {
+            return Err(format!("Unknown value tag: {}", self.tag));
+        };
+
+        let value = match tag {
+            MluvaValueTag::Void => Value::Void,
+            MluvaValueTag::Int => Value::Int(unsafe { self.data.int_value }),
+            MluvaValueTag::Int64 => Value::Int64(unsafe { self.data.int64_value }),
+            MluvaValueTag::Byte => Value::Byte(unsafe { self.data.byte_value }),
+            MluvaValueTag::Float => Value::Float(unsafe { self.data.float_value }),
+            MluvaValueTag::Bool => {
+                // the host can write any byte, read it as `u8` since only 0 and 1 are valid `bool`s
+                match unsafe { self.data.byte_value } {
+                    0 => Value::Bool(false),
+                    1 => Value::Bool(true),
+                    byte => return Err(format!("Invalid bool value: {}", byte)),
+                }
+            }
+            MluvaValueTag::String => {
+                let string_value = unsafe { self.data.string_value };
+                Value::String(unsafe { str_from_ptr(string_value) }?.to_string())
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+impl MluvaEngine {
+    fn fail(&mut self, status: MluvaStatus, message: impl Into<String>) -> MluvaStatus {
+        self.last_error = CString::new(message.into().replace('\0', "")).ok();
+        status
+    }
+
+    fn render_compile_error(&self, error: &CompileError) -> String {
+        emit_into_string(
+            &CodespanConfig::default(),
+            &self.files,
+            &error.to_diagnostic(),
+        )
+        .unwrap_or_else(|_| error.to_string())
+    }
+
+    /// Modules compiled against a module keep its slots and signitures,
+    /// so a module can't be replaced once it is added.
+    fn check_new_module(&mut self, name: &str) -> Result<(), MluvaStatus> {
+        if self.modules.contains_key(name) {
+            let message = format!("Module '{}' is already added to the engine", name);
+            return Err(self.fail(MluvaStatus::InvalidArgument, message));
+        }
+
+        Ok(())
+    }
+
+    fn compile(&mut self, name: &str, source: &str) -> MluvaStatus {
+        if let Err(status) = self.check_new_module(name) {
+            return status;
+        }
+
+        let file_id = self.files.add(name.to_string(), source.to_string());
+        let result = match Ast::from_string(source, file_id) {
+            Ok(ast) => Module::from_ast_and_dependencies(ast, &self.modules),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(module) => {
+                self.modules.insert(name.to_string(), module);
+                MluvaStatus::Ok
+            }
+            Err(e) => {
+                let message = self.render_compile_error(&e);
+                self.fail(MluvaStatus::CompileError, message)
+            }
+        }
+    }
+
+    fn call(
+        &mut self,
+        module_name: &str,
+        function_name: &str,
+        args: Vec<Value>,
+    ) -> Result<Value, MluvaStatus> {
+        let Some(module) = self.modules.get(module_name) else {
+            let message = format!("Module '{}' not found", module_name);
+            return Err(self.fail(MluvaStatus::InvalidArgument, message));
+        };
+
+        // panics are caught only when the library is built with `panic = "unwind"`,
+        // the `release-capi` profile does that, the `release` profile aborts
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            Runtime::new(module, &self.modules).call(function_name, args)
+        }));
+
+        match result {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(e)) => {
+                Err(self.fail(MluvaStatus::RuntimeError, format!("Runtime error: {}", e)))
+            }
+            Err(_) => Err(self.fail(
+                MluvaStatus::RuntimeError,
+                "Runtime error: interpreter panicked",
+            )),
+        }
+    }
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    // interior NUL can't be represented in C string, cut the string there
+    let s = match s.find('\0') {
+        Some(index) => s[..index].to_string(),
+        None => s,
+    };
+
+    CString::new(s).unwrap().into_raw()
+}
+
+/// # Safety
+/// `ptr` must be null or point to valid NUL terminated string.
+unsafe fn str_from_ptr<'a>(ptr: *const c_char) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err("Unexpected null string".to_string());
+    }
+
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|e| format!("Invalid UTF-8 string: {}", e))
+}
+
+/// Creates new engine with the standard modules, free it with `mluva_engine_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn mluva_engine_new() -> *mut MluvaEngine {
+    Box::into_raw(Box::new(MluvaEngine {
+        modules: standard_modules(),
+        files: SimpleFiles::new(),
+        last_error: None,
+    }))
+}
+
+/// # Safety
+/// `engine` must be null or created by `mluva_engine_new` and not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mluva_engine_free(engine: *mut MluvaEngine) {
+    if !engine.is_null() {
+        drop(unsafe { Box::from_raw(engine) });
+    }
+}
+
+/// Compiles source text into module called `name`.
+/// Module can import modules that were already added to the engine,
+/// a module with the same name as an added one fails with `MLUVA_STATUS_INVALID_ARGUMENT`.
+///
+/// # Safety
+/// `engine` must be valid engine, `name` and `source` valid NUL terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mluva_engine_compile(
+    engine: *mut MluvaEngine,
+    name: *const c_char,
+    source: *const c_char,
+) -> MluvaStatus {
+    let Some(engine) = (unsafe { engine.as_mut() }) else {
+        return MluvaStatus::InvalidArgument;
+    };
+
+    let (name, source) = match unsafe { (str_from_ptr(name), str_from_ptr(source)) } {
+        (Ok(name), Ok(source)) => (name, source),
+        (Err(e), _) | (_, Err(e)) => return engine.fail(MluvaStatus::InvalidArgument, e),
+    };
+
+    engine.compile(name, source)
+}
+
+/// Loads module called `name` from `.mvb` bytecode.
+/// The bytecode is verified, modules it calls must be added to the engine before it.
+/// A module with the same name as an added one fails with `MLUVA_STATUS_INVALID_ARGUMENT`.
+///
+/// # Safety
+/// `engine` must be valid engine, `name` valid NUL terminated string
+/// and `bytes` must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mluva_engine_load_bytecode(
+    engine: *mut MluvaEngine,
+    name: *const c_char,
+    bytes: *const u8,
+    len: usize,
+) -> MluvaStatus {
+    let Some(engine) = (unsafe { engine.as_mut() }) else {
+        return MluvaStatus::InvalidArgument;
+    };
+
+    let name = match unsafe { str_from_ptr(name) } {
+        Ok(name) => name,
+        Err(e) => return engine.fail(MluvaStatus::InvalidArgument, e),
+    };
+
+    if bytes.is_null() {
+        return engine.fail(MluvaStatus::InvalidArgument, "Unexpected null bytecode");
+    }
+
+    if let Err(status) = engine.check_new_module(name) {
+        return status;
+    }
+
+    let bytes = unsafe { std::slice::from_raw_parts(bytes, len) };
+    match Module::from_verified_bytecode(bytes, &engine.modules) {
+        Ok(module) => {
+            engine.modules.insert(name.to_string(), module);
+            MluvaStatus::Ok
+        }
+        Err(e) => engine.fail(
+            MluvaStatus::BytecodeError,
+            format!("Failed to load module {} from bytecode: {}", name, e),
+        ),
+    }
+}
+
+/// Serializes module called `name` into `.mvb` bytecode.
+/// The bytes must be freed with `mluva_bytes_free`.
+///
+/// # Safety
+/// `engine` must be valid engine, `name` valid NUL terminated string,
+/// `out_bytes` and `out_len` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mluva_engine_module_bytecode(
+    engine: *mut MluvaEngine,
+    name: *const c_char,
+    out_bytes: *mut *mut u8,
+    out_len: *mut usize,
+) -> MluvaStatus {
+    let Some(engine) = (unsafe { engine.as_mut() }) else {
+        return MluvaStatus::InvalidArgument;
+    };
+
+    if out_bytes.is_null() || out_len.is_null() {
+        return engine.fail(
+            MluvaStatus::InvalidArgument,
+            "Unexpected null output pointer",
+        );
+    }
+
+    let name = match unsafe { str_from_ptr(name) } {
+        Ok(name) => name,
+        Err(e) => return engine.fail(MluvaStatus::InvalidArgument, e),
+    };
+
+    let Some(module) = engine.modules.get(name) else {
+        let message = format!("Module '{}' not found", name);
+        return engine.fail(MluvaStatus::InvalidArgument, message);
+    };
+
+    let bytecode = module.to_bytecode().into_boxed_slice();
+    unsafe {
+        *out_len = bytecode.len();
+        *out_bytes = Box::into_raw(bytecode) as *mut u8;
+    }
+
+    MluvaStatus::Ok
+}
+
+/// Calls `function` from module `module` with `arg_count` arguments.
+/// On success the returned value is written into `out_result`,
+/// free it with `mluva_value_free`.
+///
+/// # Safety
+/// `engine` must be valid engine, `module` and `function` valid NUL terminated strings,
+/// `args` must point to `arg_count` values (may be null if `arg_count` is 0)
+/// and `out_result` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mluva_engine_call(
+    engine: *mut MluvaEngine,
+    module: *const c_char,
+    function: *const c_char,
+    args: *const MluvaValue,
+    arg_count: usize,
+    out_result: *mut MluvaValue,
+) -> MluvaStatus {
+    let Some(engine) = (unsafe { engine.as_mut() }) else {
+        return MluvaStatus::InvalidArgument;
+    };
+
+    if out_result.is_null() || (args.is_null() && arg_count > 0) {
+        return engine.fail(MluvaStatus::InvalidArgument, "Unexpected null pointer");
+    }
+
+    let (module, function) = match unsafe { (str_from_ptr(module), str_from_ptr(function)) } {
+        (Ok(module), Ok(function)) => (module, function),
+        (Err(e), _) | (_, Err(e)) => return engine.fail(MluvaStatus::InvalidArgument, e),
+    };
+
+    let c_args: &[MluvaValue] = if arg_count == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(args, arg_count) }
+    };
+
+    let args = match c_args
+        .iter()
+        .map(|arg| unsafe { arg.to_value() })
+        .collect::<Result<Vec<Value>, String>>()
+    {
+        Ok(args) => args,
+        Err(e) => return engine.fail(MluvaStatus::InvalidArgument, e),
+    };
+
+    match engine.call(module, function, args) {
+        Ok(value) => {
+            unsafe { *out_result = MluvaValue::from_value(value) };
+            MluvaStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Returns message of the last error or null if there was none.
+/// The string is owned by the engine and valid until the next call on the engine.
+///
+/// # Safety
+/// `engine` must be null or valid engine.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mluva_engine_last_error(engine: *const MluvaEngine) -> *const c_char {
+    match unsafe { engine.as_ref() }.and_then(|e| e.last_error.as_ref()) {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Formats value the same way as `print` does, free the result with `mluva_string_free`.
+///
+/// # Safety
+/// `value` must be null or point to valid value.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mluva_value_to_string(value: *const MluvaValue) -> *mut c_char {
+    let Some(value) = (unsafe { value.as_ref() }) else {
+        return ptr::null_mut();
+    };
+
+    match unsafe { value.to_value() } {
+        Ok(value) => into_c_string(value.to_string()),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Frees string owned by the value and sets the value to void.
+///
+/// # Safety
+/// `value` must be null or value returned by the library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mluva_value_free(value: *mut MluvaValue) {
+    let Some(value) = (unsafe { value.as_mut() }) else {
+        return;
+    };
+
+    if value.tag == MluvaValueTag::String as u32 {
+        unsafe { mluva_string_free(value.data.string_value) };
+    }
+
+    *value = MluvaValue::void();
+}
+
+/// # Safety
+/// `string` must be null or string returned by the library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mluva_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+/// # Safety
+/// `bytes` and `len` must be null / 0 or returned by `mluva_engine_module_bytecode`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mluva_bytes_free(bytes: *mut u8, len: usize) {
+    if !bytes.is_null() {
+        drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(bytes, len)) });
+    }
+}
@@ -0,0 +1,153 @@
+/* Exercises the C API, run by tests/c_api.rs. Exits with non-zero status on failure. */
+#include <stdio.h>
+#include <string.h>
+
+#include "mluva.h"
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+            return 1;                                                      \
+        }                                                                  \
+    } while (0)
+
+#define CHECK_OK(engine, call)                                             \
+    do {                                                                   \
+        MluvaStatus status_ = (call);                                      \
+        if (status_ != MLUVA_STATUS_OK) {                                  \
+            fprintf(stderr, "%s:%d: %s failed (%d): %s\n", __FILE__, __LINE__, #call, \
+                    (int)status_, mluva_engine_last_error(engine));        \
+            return 1;                                                      \
+        }                                                                  \
+    } while (0)
+
+static const char *NUMBERS_SOURCE =
+    "pub Int sub(Int a, Int b) {\n"
+    "    return a - b\n"
+    "}\n"
+    "String greet(String name, Bool loud) {\n"
+    "    if loud {\n"
+    "        return format('HELLO ', name)\n"
+    "    }\n"
+    "    return format('hello ', name)\n"
+    "}\n";
+
+static const char *MAIN_SOURCE =
+    "import numbers\n"
+    "Float half(Float x) {\n"
+    "    return x / 2.0\n"
+    "}\n"
+    "Int twice_sub(Int a, Int b) {\n"
+    "    return numbers:sub(a, b) * 2\n"
+    "}\n"
+    "Int fail() {\n"
+    "    return 1 / 0\n"
+    "}\n";
+
+int main(void) {
+    MluvaEngine *engine = mluva_engine_new();
+    CHECK(engine != NULL);
+    CHECK(mluva_engine_last_error(engine) == NULL);
+
+    CHECK_OK(engine, mluva_engine_compile(engine, "numbers", NUMBERS_SOURCE));
+    CHECK_OK(engine, mluva_engine_compile(engine, "main", MAIN_SOURCE));
+
+    /* integer arguments keep their order */
+    MluvaValue args[2];
+    args[0].tag = MLUVA_VALUE_TAG_INT;
+    args[0].data.int_value = 10;
+    args[1].tag = MLUVA_VALUE_TAG_INT;
+    args[1].data.int_value = 3;
+
+    MluvaValue result;
+    CHECK_OK(engine, mluva_engine_call(engine, "main", "twice_sub", args, 2, &result));
+    CHECK(result.tag == MLUVA_VALUE_TAG_INT);
+    CHECK(result.data.int_value == 14);
+    mluva_value_free(&result);
+
+    /* floats */
+    args[0].tag = MLUVA_VALUE_TAG_FLOAT;
+    args[0].data.float_value = 5.0;
+    CHECK_OK(engine, mluva_engine_call(engine, "main", "half", args, 1, &result));
+    CHECK(result.tag == MLUVA_VALUE_TAG_FLOAT);
+    CHECK(result.data.float_value == 2.5);
+
+    char *text = mluva_value_to_string(&result);
+    CHECK(strcmp(text, "2.5") == 0);
+    mluva_string_free(text);
+    mluva_value_free(&result);
+
+    /* strings and bools */
+    char name[] = "world";
+    args[0].tag = MLUVA_VALUE_TAG_STRING;
+    args[0].data.string_value = name;
+    args[1].tag = MLUVA_VALUE_TAG_BOOL;
+    args[1].data.bool_value = true;
+    CHECK_OK(engine, mluva_engine_call(engine, "numbers", "greet", args, 2, &result));
+    CHECK(result.tag == MLUVA_VALUE_TAG_STRING);
+    CHECK(strcmp(result.data.string_value, "HELLO world") == 0);
+    mluva_value_free(&result);
+    CHECK(result.tag == MLUVA_VALUE_TAG_VOID);
+
+    /* runtime errors */
+    CHECK(mluva_engine_call(engine, "main", "fail", NULL, 0, &result) == MLUVA_STATUS_RUNTIME_ERROR);
+    CHECK(strstr(mluva_engine_last_error(engine), "Division by zero") != NULL);
+
+    /* wrong arguments */
+    CHECK(mluva_engine_call(engine, "main", "half", args, 2, &result) == MLUVA_STATUS_RUNTIME_ERROR);
+    CHECK(mluva_engine_call(engine, "nope", "half", args, 1, &result) == MLUVA_STATUS_INVALID_ARGUMENT);
+    args[0].tag = 42;
+    CHECK(mluva_engine_call(engine, "main", "half", args, 1, &result) == MLUVA_STATUS_INVALID_ARGUMENT);
+    CHECK(strstr(mluva_engine_last_error(engine), "Unknown value tag: 42") != NULL);
+    args[0].tag = MLUVA_VALUE_TAG_BOOL;
+    args[0].data.byte_value = 2;
+    CHECK(mluva_engine_call(engine, "numbers", "greet", args, 2, &result) == MLUVA_STATUS_INVALID_ARGUMENT);
+    CHECK(strstr(mluva_engine_last_error(engine), "Invalid bool value: 2") != NULL);
+
+    /* added modules can't be replaced, including the standard ones */
+    CHECK(mluva_engine_compile(engine, "numbers", "pub Int sub(Int a) {\n    return a\n}\n") ==
+          MLUVA_STATUS_INVALID_ARGUMENT);
+    CHECK(strstr(mluva_engine_last_error(engine), "Module 'numbers' is already added") != NULL);
+    CHECK(mluva_engine_compile(engine, "math", NUMBERS_SOURCE) == MLUVA_STATUS_INVALID_ARGUMENT);
+    args[0].tag = MLUVA_VALUE_TAG_INT;
+    args[0].data.int_value = 10;
+    args[1].tag = MLUVA_VALUE_TAG_INT;
+    args[1].data.int_value = 3;
+    CHECK_OK(engine, mluva_engine_call(engine, "main", "twice_sub", args, 2, &result));
+    CHECK(result.data.int_value == 14);
+
+    /* compile errors are rendered with source location */
+    CHECK(mluva_engine_compile(engine, "broken.mv", "Int main() {\n    return true\n}\n") ==
+          MLUVA_STATUS_COMPILE_ERROR);
+    CHECK(strstr(mluva_engine_last_error(engine), "wrong type") != NULL);
+    CHECK(strstr(mluva_engine_last_error(engine), "broken.mv") != NULL);
+
+    /* bytecode round trip into a second engine */
+    uint8_t *bytes = NULL;
+    size_t len = 0;
+    CHECK_OK(engine, mluva_engine_module_bytecode(engine, "numbers", &bytes, &len));
+    CHECK(len > 0);
+
+    MluvaEngine *loaded = mluva_engine_new();
+    CHECK_OK(loaded, mluva_engine_load_bytecode(loaded, "numbers", bytes, len));
+    CHECK(mluva_engine_load_bytecode(loaded, "numbers", bytes, len) == MLUVA_STATUS_INVALID_ARGUMENT);
+    mluva_bytes_free(bytes, len);
+
+    args[0].tag = MLUVA_VALUE_TAG_INT;
+    args[0].data.int_value = 1;
+    args[1].tag = MLUVA_VALUE_TAG_INT;
+    args[1].data.int_value = 2;
+    CHECK_OK(loaded, mluva_engine_call(loaded, "numbers", "sub", args, 2, &result));
+    CHECK(result.data.int_value == -1);
+
+    uint8_t garbage[] = {1, 2, 3};
+    CHECK(mluva_engine_load_bytecode(loaded, "bad", garbage, sizeof(garbage)) ==
+          MLUVA_STATUS_BYTECODE_ERROR);
+
+    mluva_engine_free(loaded);
+    mluva_engine_free(engine);
+
+    printf("ok\n");
+    return 0;
+}
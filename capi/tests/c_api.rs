@@ -0,0 +1,52 @@
+#![cfg(target_os = "linux")]
+
+use std::{path::Path, process::Command};
+
+/// Compiles `tests/c/test_api.c` against the cdylib and runs it.
+#[test]
+fn c_test_program() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+
+    // `cargo test` builds only the rlib, build the cdylib into separate target dir
+    // so we don't wait for the lock held by the outer cargo
+    let target_dir = out_dir.join("capi");
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "-p", "capi", "--lib", "--target-dir"])
+        .arg(&target_dir)
+        .current_dir(crate_dir)
+        .status()
+        .expect("Failed to run cargo");
+    assert!(status.success(), "Failed to build libmluva_capi.so");
+
+    let lib_dir = target_dir.join("debug");
+    let binary = out_dir.join("mluva_c_test");
+
+    let status = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(crate_dir.join("tests/c/test_api.c"))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg("-lmluva_capi")
+        .arg("-o")
+        .arg(&binary)
+        .status()
+        .expect("Failed to run C compiler");
+    assert!(status.success(), "Failed to compile C test program");
+
+    let output = Command::new(&binary)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "C test program failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
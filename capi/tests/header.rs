@@ -0,0 +1,31 @@
+use std::path::Path;
+
+/// Regenerates the C header and checks that the committed one is up to date.
+/// Run with `MLUVA_UPDATE_HEADER=1` to overwrite `include/mluva.h`.
+#[test]
+fn header_is_up_to_date() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("Failed to generate C header")
+        .write(&mut generated);
+
+    let generated = String::from_utf8(generated).unwrap();
+    let header_path = crate_dir.join("include/mluva.h");
+
+    if std::env::var_os("MLUVA_UPDATE_HEADER").is_some() {
+        std::fs::write(&header_path, &generated).unwrap();
+        return;
+    }
+
+    let committed = std::fs::read_to_string(&header_path).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "include/mluva.h is out of date, run `MLUVA_UPDATE_HEADER=1 cargo test -p capi --test header`"
+    );
+}
@@ -1,6 +1,15 @@
+pub mod asm;
 pub mod build;
+pub mod clean;
+pub mod disasm;
+pub mod fmt;
 pub mod init;
+pub mod lsp;
+pub mod repl;
 pub mod run;
+pub mod test;
+
+use std::path::PathBuf;
 
 use clap::Subcommand;
 
@@ -9,8 +18,52 @@ use crate::module_metadata::ModuleMetadataStorage;
 #[derive(Subcommand)]
 pub enum Commands {
     Init,
-    Run,
-    Build,
+    /// Run the project, or a single script without a project
+    Run {
+        /// Path to the script, e.g. `script.mv`
+        file: Option<PathBuf>,
+
+        /// Arguments passed to the program, e.g. `mluva run -- a b c`
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    Build {
+        /// Rebuild all modules, including unchanged ones
+        #[arg(long)]
+        all: bool,
+    },
+    /// Remove the build cache (the `.mluva` directory)
+    Clean,
+    /// Start an interactive session
+    Repl,
+    /// Format source files, all `.mv` files in the current directory by default
+    Fmt {
+        paths: Vec<PathBuf>,
+
+        /// Do not modify files, fail if any file is not formatted
+        #[arg(long)]
+        check: bool,
+    },
+    /// Print the bytecode listing of a compiled module
+    Disasm {
+        /// Module name, e.g. `math`, or path to a `.mvb` file
+        target: String,
+    },
+    /// Assemble textual bytecode (`.mva`) into a bytecode file (`.mvb`)
+    Asm {
+        file: PathBuf,
+
+        /// Path of the bytecode file, the source path with `.mvb` extension by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Start the language server, communicating over stdin and stdout
+    Lsp,
+    /// Run test functions (functions with `test_` prefix) of all project modules
+    Test {
+        /// Run only tests whose name (`module:test_name`) contains this string
+        filter: Option<String>,
+    },
 }
 
 const META_DIR: &str = ".mluva";
@@ -0,0 +1,32 @@
+use std::path::{Path, PathBuf};
+
+use codespan_reporting::files::SimpleFiles;
+use mluva::assembler::assemble;
+
+use crate::{
+    diagnostics::emit_compile_error, module_loader::read_source,
+    module_metadata::load_cached_modules,
+};
+
+/// Assembles `.mva` file into bytecode, written next to the source unless `output` is given.
+pub fn command(file: PathBuf, output: Option<PathBuf>) -> Result<(), ()> {
+    let source = read_source(&file).ok_or(())?;
+    let mut files = SimpleFiles::new();
+    let file_id = files.add(file.to_string_lossy().to_string(), source.clone());
+
+    let module = assemble(&source, file_id, &load_cached_modules()).map_err(|e| {
+        emit_compile_error(&files, &e);
+    })?;
+
+    let output = output.unwrap_or_else(|| file.with_extension("mvb"));
+    write_bytecode(&output, &module.to_bytecode())?;
+    println!("Assembled {}", output.display());
+
+    Ok(())
+}
+
+fn write_bytecode(path: &Path, bytecode: &[u8]) -> Result<(), ()> {
+    std::fs::write(path, bytecode).map_err(|e| {
+        eprintln!("Failed to write bytecode file {}: {}", path.display(), e);
+    })
+}
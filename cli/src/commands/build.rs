@@ -1,21 +1,17 @@
 use std::{collections::HashMap, path::Path};
 
-use codespan_reporting::{
-    files::SimpleFiles,
-    term::{
-        Config as CodespanConfig, emit_to_io_write,
-        termcolor::{ColorChoice, StandardStream},
-    },
-};
-use mluva::{ast::Ast, errors::CompileError, module::Module};
+use codespan_reporting::files::SimpleFiles;
+use mluva::{ast::Ast, errors::CompileError, module::Module, stdlib::standard_module};
 
 use crate::{
     commands::create_meta_storage,
     config::Config,
+    diagnostics::emit_compile_error,
     module_metadata::{ModuleMetadata, ModuleMetadataStorage},
 };
 
-pub fn command() -> Result<(Config, HashMap<String, Module>), ()> {
+/// `all` rebuilds every module, even when its cached bytecode is up to date.
+pub fn command(all: bool) -> Result<(Config, HashMap<String, Module>), ()> {
     println!("Building the Mluva project...");
 
     let config = Config::load_from_file()?;
@@ -27,36 +23,21 @@ pub fn command() -> Result<(Config, HashMap<String, Module>), ()> {
     create_meta_storage()?;
 
     let mut module_meta_storage = ModuleMetadataStorage::load_from_file()?;
-    let mut compiled_modules: HashMap<String, Module> = HashMap::new();
-    let mut parent_stack: Vec<String> = vec![];
-    let mut files = SimpleFiles::new();
+    if all || !module_meta_storage.is_current() {
+        module_meta_storage.clear();
+    }
 
-    let compile_result = compile_module(
-        &config.root_module,
-        &mut compiled_modules,
-        &mut module_meta_storage,
-        &mut parent_stack,
-        &mut files,
-    );
+    let mut files = SimpleFiles::new();
+    let compile_result = compile_modules(&config, &mut module_meta_storage, &mut files);
 
     match compile_result {
-        Ok(_) => {
+        Ok(compiled_modules) => {
             module_meta_storage.save_to_file()?;
             println!("Build completed!");
             Ok((config, compiled_modules))
         }
         Err(Some(e)) => {
-            let diag = e.to_diagnostic();
-            let writer = StandardStream::stderr(ColorChoice::Auto);
-            let Ok(_) = emit_to_io_write(
-                &mut writer.lock(),
-                &CodespanConfig::default(),
-                &files,
-                &diag,
-            ) else {
-                eprintln!("Failed to write diagnostics");
-                return Err(());
-            };
+            emit_compile_error(&files, &e);
             Err(())
         }
         Err(None) => {
@@ -66,32 +47,68 @@ pub fn command() -> Result<(Config, HashMap<String, Module>), ()> {
     }
 }
 
-fn compile_module(
-    source_module: &str,
-    compiled_modules: &mut HashMap<String, Module>,
+/// Source of a project module, read before any module is compiled.
+struct SourceModule {
+    name: String,
+    source_path: String,
+    content: Vec<u8>,
+    /// dotted paths of the imported modules
+    imports: Vec<String>,
+}
+
+/// Compiles the root module and all modules it imports, modules can import each other.
+/// Interfaces of all modules are collected first, every module is then compiled against them
+/// unless its cached bytecode is up to date. Imported standard modules are returned with them.
+fn compile_modules(
+    config: &Config,
     module_meta_storage: &mut ModuleMetadataStorage,
-    parent_stack: &mut Vec<String>, // TODO: change to something that is not O(n) on search but has ordering
     files: &mut SimpleFiles<String, String>,
-) -> Result<(), Option<CompileError>> {
-    let source_path = Path::new(source_module)
-        .with_extension("mv")
-        .to_string_lossy()
-        .to_string();
-
-    if compiled_modules.contains_key(source_module) {
-        return Ok(());
+) -> Result<HashMap<String, Module>, Option<CompileError>> {
+    let mut sources = vec![];
+    let mut asts = HashMap::new();
+    let mut standard = HashMap::new();
+    read_module(
+        config,
+        &config.root_module,
+        &mut sources,
+        &mut asts,
+        &mut standard,
+        files,
+    )?;
+
+    let mut interfaces = Module::interfaces(&asts, &standard)?;
+    interfaces.extend(standard.clone());
+
+    let mut compiled_modules = standard;
+    for source in sources {
+        let ast = asts.remove(&source.name).unwrap();
+        let module = compile_module(&source, ast, &interfaces, module_meta_storage)?;
+        compiled_modules.insert(source.name, module);
     }
 
-    if parent_stack.iter().any(|p| p == source_module) {
-        eprintln!(
-            "Cyclic dependency detected: {} -> {}",
-            parent_stack.join(" -> "),
-            source_module
-        );
-        return Err(None);
+    Ok(compiled_modules)
+}
+
+/// Reads and parses the module and all modules it imports, `sources` lists them
+/// with imports before the importing modules. `source_module` is a dotted module path,
+/// modules are keyed by it. Imports without a source file are resolved to standard modules,
+/// which are collected in `standard`.
+fn read_module(
+    config: &Config,
+    source_module: &str,
+    sources: &mut Vec<SourceModule>,
+    asts: &mut HashMap<String, Ast>,
+    standard: &mut HashMap<String, Module>,
+    files: &mut SimpleFiles<String, String>,
+) -> Result<(), Option<CompileError>> {
+    if asts.contains_key(source_module) {
+        return Ok(());
     }
 
-    parent_stack.push(source_module.to_string());
+    let source_path = config
+        .module_file_path(source_module)
+        .to_string_lossy()
+        .to_string();
 
     let Ok(content) = std::fs::read(&source_path) else {
         eprintln!("Failed to read module file: {}", source_path);
@@ -106,12 +123,25 @@ fn compile_module(
     let file_id = files.add(source_path.clone(), content_str.clone());
     let ast = Ast::from_string(&content_str, file_id)?;
 
-    for import in ast.get_imports() {
-        // TODO: resolve full path
-        let import_path_str = import.get_tail().unwrap();
-        let import_path = Path::new(import_path_str).with_extension("mv");
+    let imports: Vec<String> = ast
+        .get_imports()
+        .iter()
+        .map(|import| import.path.to_string())
+        .collect();
+    asts.insert(source_module.to_string(), ast);
 
+    for import_name in &imports {
+        let import_path = config.module_file_path(import_name);
         if !import_path.exists() {
+            if standard.contains_key(import_name) {
+                continue;
+            }
+
+            if let Some(module) = standard_module(import_name) {
+                standard.insert(import_name.clone(), module);
+                continue;
+            }
+
             eprintln!(
                 "Dependecy module {} of module {} not found",
                 import_path.display(),
@@ -120,46 +150,82 @@ fn compile_module(
             return Err(None);
         }
 
-        compile_module(
-            import_path_str,
-            compiled_modules,
-            module_meta_storage,
-            parent_stack,
-            files,
-        )?;
+        read_module(config, import_name, sources, asts, standard, files)?;
     }
 
-    let bytecode_path_str = ModuleMetadata::source_to_bytecode_path(&source_path);
-    let bytecode_path = Path::new(&bytecode_path_str);
-    let needs_compilation = module_meta_storage.needs_recompilation(&source_path, &content);
+    sources.push(SourceModule {
+        name: source_module.to_string(),
+        source_path,
+        content,
+        imports,
+    });
 
-    if needs_compilation || !bytecode_path.exists() {
-        let module = Module::from_ast_and_dependencies(ast, compiled_modules)?;
-        let bytecode = module.to_bytecode();
+    Ok(())
+}
 
-        let Ok(_) = std::fs::write(&bytecode_path, bytecode) else {
-            eprintln!("Failed to write bytecode file for module {}", source_path);
-            return Err(None);
-        };
+/// Compiles the module against interfaces of the project modules,
+/// or loads its cached bytecode when neither it nor the interfaces of its imports changed.
+fn compile_module(
+    source: &SourceModule,
+    ast: Ast,
+    interfaces: &HashMap<String, Module>,
+    module_meta_storage: &mut ModuleMetadataStorage,
+) -> Result<Module, Option<CompileError>> {
+    let dependency_hashes: HashMap<String, String> = source
+        .imports
+        .iter()
+        .map(|import_name| {
+            let hash = ModuleMetadata::calculate_interface_hash(&interfaces[import_name]);
+            (import_name.clone(), hash)
+        })
+        .collect();
+
+    let source_path = &source.source_path;
+    let bytecode_path_str = ModuleMetadata::source_to_bytecode_path(source_path);
+    let bytecode_path = Path::new(&bytecode_path_str);
+    let needs_compilation =
+        module_meta_storage.needs_recompilation(source_path, &source.content, &dependency_hashes);
 
-        module_meta_storage.update_hash(&source_path, &content);
-        compiled_modules.insert(source_module.to_string(), module);
+    let cached = if needs_compilation || !bytecode_path.exists() {
+        None
     } else {
-        // load from cached bytecode
-        let Ok(bytecode) = std::fs::read(&bytecode_path) else {
-            eprintln!("Failed to read bytecode file for module {}", source_path);
-            return Err(None);
-        };
-
-        let module = Module::from_bytecode_bytes(&bytecode).map_err(|e| {
-            eprintln!("Failed to load module {} from bytecode: {}", source_path, e);
-            None
-        })?;
+        load_cached_module(bytecode_path, source_path, interfaces)
+    };
 
-        compiled_modules.insert(source_module.to_string(), module);
+    if let Some(module) = cached {
+        println!("{} is up to date", source.name);
+        return Ok(module);
     }
 
-    parent_stack.pop();
+    println!("Compiling {}", source.name);
+    let module = Module::from_ast_and_dependencies(ast, interfaces)?;
+    let bytecode = module.to_bytecode();
 
-    Ok(())
+    let Ok(_) = std::fs::write(bytecode_path, bytecode) else {
+        eprintln!("Failed to write bytecode file for module {}", source_path);
+        return Err(None);
+    };
+
+    module_meta_storage.update_hash(source_path, &source.content, &module, dependency_hashes);
+    Ok(module)
+}
+
+/// Loads and verifies the cached bytecode, `None` means the module has to be recompiled.
+fn load_cached_module(
+    bytecode_path: &Path,
+    source_path: &str,
+    dependencies: &HashMap<String, Module>,
+) -> Option<Module> {
+    let bytecode = std::fs::read(bytecode_path)
+        .inspect_err(|_| eprintln!("Failed to read bytecode file for module {}", source_path))
+        .ok()?;
+
+    Module::from_verified_bytecode(&bytecode, dependencies)
+        .inspect_err(|e| {
+            eprintln!(
+                "Invalid bytecode of module {}, recompiling: {}",
+                source_path, e
+            )
+        })
+        .ok()
 }
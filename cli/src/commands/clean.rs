@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use super::META_DIR;
+use crate::config::Config;
+
+/// Removes the `.mluva` directory of the project in the current directory.
+pub fn command() -> Result<(), ()> {
+    // do not remove directories outside of a project
+    if !Config::file_exists() {
+        eprintln!(
+            "Configuration file '{}' not found, not a Mluva project",
+            Config::FILE_PATH
+        );
+        return Err(());
+    }
+
+    let path = Path::new(META_DIR);
+    let Ok(metadata) = path.symlink_metadata() else {
+        println!("Nothing to clean");
+        return Ok(());
+    };
+
+    if !metadata.is_dir() {
+        eprintln!("'{}' is not a directory, not removing it", META_DIR);
+        return Err(());
+    }
+
+    std::fs::remove_dir_all(path).map_err(|e| {
+        eprintln!("Failed to remove {}: {}", META_DIR, e);
+    })?;
+
+    println!("Removed {}", META_DIR);
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::path::{Path, PathBuf};
+
+use mluva::{disassembler::disassemble, module::Module, verifier::verify};
+
+use crate::{
+    config::Config,
+    module_metadata::{ModuleMetadata, load_cached_modules},
+};
+
+/// Prints the listing of a compiled module, `target` is a module path (e.g. `utils.strings`)
+/// or a path to a `.mvb` file.
+pub fn command(target: String) -> Result<(), ()> {
+    let path = if target.ends_with(".mvb") {
+        PathBuf::from(&target)
+    } else {
+        let config = Config::read_from(Path::new(Config::FILE_PATH)).unwrap_or_default();
+        let source_path = config.module_file_path(&target);
+        let path = ModuleMetadata::source_to_bytecode_path(&source_path.to_string_lossy());
+        if !Path::new(&path).exists() {
+            eprintln!(
+                "Bytecode of module '{}' not found, run 'mluva build' first",
+                target
+            );
+            return Err(());
+        }
+
+        PathBuf::from(path)
+    };
+
+    // the listing of invalid bytecode is still useful, problems are reported after it
+    let module = load_module(&path)?;
+    let dependencies = load_cached_modules();
+    print!("{}", disassemble(&module, &dependencies));
+
+    verify(&module, &dependencies).map_err(|e| {
+        eprintln!("Bytecode verification failed: {}", e);
+    })
+}
+
+fn load_module(path: &Path) -> Result<Module, ()> {
+    let Ok(bytecode) = std::fs::read(path) else {
+        eprintln!("Failed to read bytecode file {}", path.display());
+        return Err(());
+    };
+
+    Module::from_bytecode_bytes(&bytecode).map_err(|e| {
+        eprintln!("Failed to load module from {}: {}", path.display(), e);
+    })
+}
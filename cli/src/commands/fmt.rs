@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use codespan_reporting::files::SimpleFiles;
+use mluva::formatter::format_source;
+
+use crate::{diagnostics::emit_compile_error, module_loader::read_source};
+
+/// Formats given files, or all `.mv` files in the current directory when no path is given.
+/// With `check` the files are not modified, the command fails if any file is not formatted.
+pub fn command(paths: Vec<PathBuf>, check: bool) -> Result<(), ()> {
+    let paths = if paths.is_empty() {
+        let mut paths = vec![];
+        collect_source_files(Path::new("."), &mut paths)?;
+        paths.sort();
+        paths
+    } else {
+        paths
+    };
+
+    let mut files = SimpleFiles::new();
+    let mut failed = false;
+    let mut unformatted = 0;
+
+    for path in paths {
+        let Some(source) = read_source(&path) else {
+            failed = true;
+            continue;
+        };
+
+        let file_id = files.add(path.to_string_lossy().to_string(), source.clone());
+        let formatted = match format_source(&source, file_id) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                emit_compile_error(&files, &e);
+                failed = true;
+                continue;
+            }
+        };
+
+        if formatted == source {
+            continue;
+        }
+
+        if check {
+            println!("File {} is not formatted", path.display());
+            unformatted += 1;
+            continue;
+        }
+
+        if let Err(e) = std::fs::write(&path, formatted) {
+            eprintln!("Failed to write file {}: {}", path.display(), e);
+            failed = true;
+            continue;
+        }
+
+        println!("Formatted {}", path.display());
+    }
+
+    if unformatted > 0 {
+        eprintln!("{} files are not formatted, run 'mluva fmt'", unformatted);
+        return Err(());
+    }
+
+    if failed { Err(()) } else { Ok(()) }
+}
+
+/// Collects `.mv` files recursively, hidden directories like `.mluva` are skipped.
+fn collect_source_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), ()> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        eprintln!("Failed to read directory {}", dir.display());
+        return Err(());
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+
+        if path.is_dir() && !hidden {
+            collect_source_files(&path, paths)?;
+        } else if path.extension().is_some_and(|e| e == "mv") {
+            paths.push(path.strip_prefix(".").unwrap_or(&path).to_path_buf());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,382 @@
+mod analysis;
+mod position;
+
+use std::{collections::HashMap, path::PathBuf, str::FromStr};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, Location, MarkupContent, MarkupKind, OneOf, PublishDiagnosticsParams,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Uri,
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, Request as LspRequest},
+};
+use mluva::{
+    ast::{Ast, BuiltinFunction},
+    compiler::SymbolKind,
+};
+
+use self::{
+    analysis::Analysis,
+    position::{position_to_offset, span_to_range},
+};
+use crate::module_loader::read_source;
+
+/// Runs the language server over stdin and stdout until the client sends `exit`.
+pub fn command() -> Result<(), ()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".to_string(), ".".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let result = connection
+        .initialize(serde_json::to_value(capabilities).unwrap())
+        .map_err(|e| e.to_string())
+        .and_then(|_| Server::new().run(&connection));
+
+    drop(connection);
+    if let Err(e) = result {
+        eprintln!("Language server error: {}", e);
+        return Err(());
+    }
+
+    io_threads.join().map_err(|e| {
+        eprintln!("Language server error: {}", e);
+    })
+}
+
+struct Document {
+    text: String,
+    /// The last successful analysis, kept while the document can not be parsed
+    analysis: Option<Analysis>,
+}
+
+struct Server {
+    documents: HashMap<Uri, Document>,
+}
+
+impl Server {
+    fn new() -> Self {
+        Self {
+            documents: HashMap::new(),
+        }
+    }
+
+    fn run(mut self, connection: &Connection) -> Result<(), String> {
+        for message in connection.receiver.iter() {
+            match message {
+                Message::Request(request) => {
+                    if connection
+                        .handle_shutdown(&request)
+                        .map_err(|e| e.to_string())?
+                    {
+                        return Ok(());
+                    }
+
+                    let response = self.handle_request(request);
+                    send(connection, response.into())?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(diagnostics) = self.handle_notification(notification) {
+                        let notification =
+                            Notification::new(PublishDiagnostics::METHOD.to_string(), diagnostics);
+                        send(connection, notification.into())?;
+                    }
+                }
+                Message::Response(_) => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            HoverRequest::METHOD => respond::<HoverRequest>(request, |p| self.hover(p)),
+            GotoDefinition::METHOD => respond::<GotoDefinition>(request, |p| self.definition(p)),
+            Completion::METHOD => respond::<Completion>(request, |p| self.completion(p)),
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unknown method '{}'", request.method),
+            ),
+        }
+    }
+
+    /// Returns diagnostics to publish when a document was changed.
+    fn handle_notification(
+        &mut self,
+        notification: Notification,
+    ) -> Option<PublishDiagnosticsParams> {
+        let (uri, text) = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams = extract(notification)?;
+                (params.text_document.uri, params.text_document.text)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams = extract(notification)?;
+                // full synchronization, the last change holds the whole text
+                let change = params.content_changes.into_iter().last()?;
+                (params.text_document.uri, change.text)
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams = extract(notification)?;
+                self.documents.remove(&params.text_document.uri);
+                return Some(PublishDiagnosticsParams::new(
+                    params.text_document.uri,
+                    vec![],
+                    None,
+                ));
+            }
+            _ => return None,
+        };
+
+        let path = uri_to_path(&uri)?;
+        let (analysis, diagnostics) = Analysis::new(&path, &text);
+        let previous = self.documents.remove(&uri).and_then(|d| d.analysis);
+
+        let document = Document {
+            text,
+            analysis: analysis.or(previous),
+        };
+        self.documents.insert(uri.clone(), document);
+
+        Some(PublishDiagnosticsParams::new(uri, diagnostics, None))
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let (document, analysis) = self.document(&position.text_document.uri)?;
+        let offset = position_to_offset(&document.text, position.position);
+        let symbol = analysis.symbol_at(offset)?;
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!("```mluva\n{}\n```", symbol.kind.describe(&symbol.name)),
+            }),
+            range: Some(span_to_range(
+                &document.text,
+                symbol.span.lo,
+                symbol.span.hi,
+            )),
+        })
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let uri = position.text_document.uri;
+        let (document, analysis) = self.document(&uri)?;
+        let offset = position_to_offset(&document.text, position.position);
+        let symbol = analysis.symbol_at(offset)?;
+
+        let location = match &symbol.kind {
+            SymbolKind::Variable { definition, .. } | SymbolKind::Function { definition, .. } => {
+                Location::new(
+                    uri.clone(),
+                    span_to_range(&document.text, definition.lo, definition.hi),
+                )
+            }
+            SymbolKind::ForeignFunction { module_name, .. } => {
+                let path = analysis.module_path(module_name);
+                let text = read_source(&path)?;
+                let ast = Ast::from_string(&text, 0).ok()?;
+                let span = ast.get_function_signiture(&symbol.name)?.span;
+                Location::new(path_to_uri(&path)?, span_to_range(&text, span.lo, span.hi))
+            }
+            SymbolKind::Method { .. } => return None,
+        };
+
+        Some(GotoDefinitionResponse::Scalar(location))
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let position = params.text_document_position;
+        let (document, analysis) = self.document(&position.text_document.uri)?;
+        let offset = position_to_offset(&document.text, position.position);
+
+        let before = &document.text[..offset];
+        let word = before.trim_end_matches(is_ident_char);
+        let qualifier = |separator| {
+            let rest = word.strip_suffix(separator)?;
+            Some(&rest[rest.trim_end_matches(is_ident_char).len()..])
+        };
+
+        let items = if let Some(module_name) = qualifier(':') {
+            let module = analysis.imported_module(module_name)?;
+            module
+                .get_functions()
+                .into_iter()
+                .filter(|(_, signiture)| signiture.visibility.is_public())
+                .map(|(name, signiture)| {
+                    let kind = SymbolKind::ForeignFunction {
+                        module_name: module_name.to_string(),
+                        signiture: signiture.clone(),
+                    };
+                    completion_item(name, CompletionItemKind::FUNCTION, kind.describe(name))
+                })
+                .collect()
+        } else if let Some(variable) = qualifier('.') {
+            let (_, data_type) = analysis
+                .variables_at(offset)
+                .into_iter()
+                .find(|(name, _)| *name == variable)?;
+
+            data_type
+                .method_names()
+                .iter()
+                .map(|name| completion_item(name, CompletionItemKind::METHOD, data_type))
+                .collect()
+        } else {
+            self.general_completion(analysis, offset)
+        };
+
+        Some(CompletionResponse::Array(items))
+    }
+
+    /// Variables in scope, functions of the document, imported modules and functions and builtins.
+    fn general_completion(&self, analysis: &Analysis, offset: usize) -> Vec<CompletionItem> {
+        let mut items = vec![];
+        for (name, data_type) in analysis.variables_at(offset) {
+            items.push(completion_item(
+                name,
+                CompletionItemKind::VARIABLE,
+                data_type,
+            ));
+        }
+
+        for (name, signiture) in analysis.functions.iter() {
+            let kind = SymbolKind::Function {
+                signiture: signiture.clone().into(),
+                definition: signiture.span,
+            };
+            items.push(completion_item(
+                name,
+                CompletionItemKind::FUNCTION,
+                kind.describe(name),
+            ));
+        }
+
+        for import in analysis.imports.iter() {
+            items.push(completion_item(
+                import.name(),
+                CompletionItemKind::MODULE,
+                "module",
+            ));
+
+            let Some(module) = analysis.imported_module(import.name()) else {
+                continue;
+            };
+            for (name, _) in import.functions.iter() {
+                let Some(signiture) = module.get_function_signiture(name) else {
+                    continue;
+                };
+                let kind = SymbolKind::ForeignFunction {
+                    module_name: import.path.to_string(),
+                    signiture: signiture.clone(),
+                };
+                items.push(completion_item(
+                    name,
+                    CompletionItemKind::FUNCTION,
+                    kind.describe(name),
+                ));
+            }
+        }
+
+        let mut builtins: Vec<_> = BuiltinFunction::str_variants().into_iter().collect();
+        builtins.sort();
+        for name in builtins {
+            items.push(completion_item(
+                name,
+                CompletionItemKind::FUNCTION,
+                "builtin",
+            ));
+        }
+
+        items
+    }
+
+    fn document(&self, uri: &Uri) -> Option<(&Document, &Analysis)> {
+        let document = self.documents.get(uri)?;
+        Some((document, document.analysis.as_ref()?))
+    }
+}
+
+fn respond<R: LspRequest>(
+    request: Request,
+    handler: impl FnOnce(R::Params) -> R::Result,
+) -> Response {
+    match serde_json::from_value(request.params) {
+        Ok(params) => Response::new_ok(request.id, handler(params)),
+        Err(e) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, e.to_string()),
+    }
+}
+
+fn extract<P: serde::de::DeserializeOwned>(notification: Notification) -> Option<P> {
+    serde_json::from_value(notification.params)
+        .inspect_err(|e| eprintln!("Invalid notification '{}': {}", notification.method, e))
+        .ok()
+}
+
+fn send(connection: &Connection, message: Message) -> Result<(), String> {
+    connection.sender.send(message).map_err(|e| e.to_string())
+}
+
+fn completion_item(label: &str, kind: CompletionItemKind, detail: impl ToString) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        detail: Some(detail.to_string()),
+        ..Default::default()
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn uri_to_path(uri: &Uri) -> Option<PathBuf> {
+    let path = uri.as_str().strip_prefix("file://")?;
+    let mut bytes = vec![];
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+
+        let hex = std::str::from_utf8(rest.get(..2)?).ok()?;
+        bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        rest = &rest[2..];
+    }
+
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+fn path_to_uri(path: &std::path::Path) -> Option<Uri> {
+    let path = std::path::absolute(path).ok()?;
+    let mut uri = "file://".to_string();
+    for byte in path.to_str()?.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    Uri::from_str(&uri).ok()
+}
@@ -0,0 +1,165 @@
+use std::path::{Path, PathBuf};
+
+use codespan_reporting::files::SimpleFiles;
+use lsp_types::{Diagnostic, DiagnosticSeverity, Range};
+use mluva::{
+    ast::{Ast, Import, SpannedFunctionSigniture},
+    compiler::{Symbol, SymbolKind, TypeChecker},
+    data_type::DataType,
+    errors::CompileError,
+    module::Module,
+};
+
+use super::position::span_to_range;
+use crate::{config::Config, module_loader::ModuleLoader};
+
+/// Result of checking a single document, imported modules are compiled from disk.
+pub struct Analysis {
+    pub loader: ModuleLoader,
+    pub symbols: Vec<Symbol>,
+    /// Functions of the document sorted by position
+    pub functions: Vec<(String, SpannedFunctionSigniture)>,
+    pub imports: Vec<Import>,
+}
+
+/// Imports are resolved under the source root of the project containing the document,
+/// or next to the document when it is not part of a project.
+fn import_base_dir(path: &Path) -> PathBuf {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    for ancestor in dir.ancestors() {
+        if let Some(config) = Config::read_from(&ancestor.join(Config::FILE_PATH)) {
+            return ancestor.join(config.source_root);
+        }
+    }
+
+    dir.to_path_buf()
+}
+
+impl Analysis {
+    /// Analyses the document, `None` is returned when the document can not be parsed.
+    pub fn new(path: &Path, text: &str) -> (Option<Self>, Vec<Diagnostic>) {
+        let mut files = SimpleFiles::new();
+        let file_id = files.add(path.to_string_lossy().to_string(), text.to_string());
+
+        let ast = match Ast::from_string(text, file_id) {
+            Ok(ast) => ast,
+            Err(e) => return (None, vec![to_diagnostic(text, &e)]),
+        };
+
+        let mut diagnostics = vec![];
+        let mut loader = ModuleLoader::new(import_base_dir(path));
+        for import in ast.get_imports() {
+            let name = &import.path.to_string();
+            let message = match loader.load(name, &mut files) {
+                Ok(_) => continue,
+                Err(Some(e)) => format!("Failed to compile module '{}': {}", name, e.message),
+                Err(None) => format!("Failed to load module '{}'", name),
+            };
+
+            diagnostics.push(error_diagnostic(
+                span_to_range(text, import.span.lo, import.span.hi),
+                message,
+            ));
+        }
+
+        let (result, symbols) = TypeChecker::new(&ast, loader.get_modules()).check_with_symbols();
+        if let Err(e) = result {
+            diagnostics.push(to_diagnostic(text, &e));
+        }
+
+        let mut functions: Vec<_> = ast
+            .get_function_map()
+            .iter()
+            .map(|(name, slot)| {
+                let signiture = ast.get_function_signiture_by_slot(*slot).unwrap();
+                (name.clone(), signiture.clone())
+            })
+            .collect();
+        functions.sort_by_key(|(_, signiture)| signiture.span.lo);
+
+        let analysis = Self {
+            loader,
+            symbols,
+            functions,
+            imports: ast.get_imports().clone(),
+        };
+
+        (Some(analysis), diagnostics)
+    }
+
+    /// The innermost symbol at the offset.
+    pub fn symbol_at(&self, offset: usize) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .filter(|s| s.span.lo <= offset && offset <= s.span.hi)
+            .min_by_key(|s| s.span.hi - s.span.lo)
+    }
+
+    /// Variables declared in the function containing the offset before the offset.
+    pub fn variables_at(&self, offset: usize) -> Vec<(&str, DataType)> {
+        let Some(function_start) = self
+            .functions
+            .iter()
+            .rev()
+            .map(|(_, signiture)| signiture.span.lo)
+            .find(|lo| *lo <= offset)
+        else {
+            return vec![];
+        };
+
+        let mut variables: Vec<(&str, DataType)> = vec![];
+        for symbol in self.symbols.iter() {
+            let SymbolKind::Variable {
+                data_type,
+                definition,
+            } = symbol.kind
+            else {
+                continue;
+            };
+
+            let visible = function_start <= definition.lo && definition.hi <= offset;
+            if visible && !variables.iter().any(|(name, _)| *name == symbol.name) {
+                variables.push((&symbol.name, data_type));
+            }
+        }
+
+        variables
+    }
+
+    /// Module called as `name:function()`, by its alias or the last segment of its path.
+    pub fn imported_module(&self, name: &str) -> Option<&Module> {
+        let import = self.imports.iter().find(|import| import.name() == name)?;
+        self.loader.get_modules().get(&import.path.to_string())
+    }
+
+    pub fn module_path(&self, name: &str) -> PathBuf {
+        self.loader.module_path(name)
+    }
+}
+
+fn to_diagnostic(text: &str, error: &CompileError) -> Diagnostic {
+    let diagnostic = error.to_diagnostic();
+    let range = diagnostic
+        .labels
+        .first()
+        .map(|label| span_to_range(text, label.range.start, label.range.end))
+        .unwrap_or_default();
+
+    let mut message = diagnostic.message;
+    for note in diagnostic.notes {
+        message.push('\n');
+        message.push_str(&note);
+    }
+
+    error_diagnostic(range, message)
+}
+
+fn error_diagnostic(range: Range, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("mluva".to_string()),
+        message,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,58 @@
+use lsp_types::{Position, Range};
+
+/// Converts a byte offset of the text to a position, columns are counted in UTF-16 code units.
+pub fn offset_to_position(text: &str, offset: usize) -> Position {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+    Position::new(
+        before.matches('\n').count() as u32,
+        before[line_start..].encode_utf16().count() as u32,
+    )
+}
+
+/// Converts a position to a byte offset, positions past the end of the line are clamped.
+pub fn position_to_offset(text: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+
+    let mut units = 0;
+    for (i, ch) in text[line_start..].char_indices() {
+        if ch == '\n' || units >= position.character as usize {
+            return line_start + i;
+        }
+        units += ch.len_utf16();
+    }
+
+    text.len()
+}
+
+pub fn span_to_range(text: &str, lo: usize, hi: usize) -> Range {
+    Range::new(offset_to_position(text, lo), offset_to_position(text, hi))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn offsets_and_positions() {
+        let text = "let a = 1\nlet č = 'ž'\n";
+        assert_eq!(offset_to_position(text, 0), Position::new(0, 0));
+        assert_eq!(offset_to_position(text, 10), Position::new(1, 0));
+
+        // 'č' and 'ž' take two bytes but a single UTF-16 unit
+        let offset = text.find('ž').unwrap();
+        assert_eq!(offset_to_position(text, offset), Position::new(1, 9));
+        assert_eq!(position_to_offset(text, Position::new(1, 9)), offset);
+
+        assert_eq!(position_to_offset(text, Position::new(0, 100)), 9);
+        assert_eq!(position_to_offset(text, Position::new(5, 0)), text.len());
+    }
+}
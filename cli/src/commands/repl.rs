@@ -0,0 +1,226 @@
+use std::io::{BufRead, Write};
+
+use codespan_reporting::files::SimpleFiles;
+use mluva::{
+    data_type::DataType,
+    session::{Session, SessionError, SessionInput},
+    value::Value,
+};
+
+use crate::{
+    diagnostics::emit_compile_error,
+    module_loader::{ModuleLoader, read_source},
+};
+
+const PROMPT: &str = ">> ";
+const CONTINUATION_PROMPT: &str = ".. ";
+
+const HELP: &str = "Commands:
+  :type <expr>     print type of the expression without evaluating it
+  :load <file.mv>  evaluate the file in the current session
+  :import <module> import module from the current directory
+  :vars            list declared variables
+  :help            print this help
+  :quit            exit the session";
+
+pub fn command() -> Result<(), ()> {
+    let mut repl = Repl::new();
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    println!("Mluva REPL, type ':help' for help.");
+
+    while let Some(input) = read_input(&mut lines) {
+        let input = input.trim();
+        if input.is_empty() {
+            continue;
+        }
+
+        if !repl.handle_input(input) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads lines until all braces are closed. Returns `None` on the end of input.
+fn read_input(lines: &mut impl Iterator<Item = std::io::Result<String>>) -> Option<String> {
+    let mut input = String::new();
+    let mut prompt = PROMPT;
+
+    loop {
+        print!("{}", prompt);
+        let _ = std::io::stdout().flush();
+
+        let Some(Ok(line)) = lines.next() else {
+            println!();
+            return if input.is_empty() { None } else { Some(input) };
+        };
+
+        input.push_str(&line);
+        input.push('\n');
+
+        if brace_depth(&input) <= 0 {
+            return Some(input);
+        }
+
+        prompt = CONTINUATION_PROMPT;
+    }
+}
+
+/// Counts unclosed braces, ignoring the ones in strings and comments.
+fn brace_depth(input: &str) -> i32 {
+    let mut depth = 0;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '#' => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '\'' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '\'' => break,
+                        _ => (),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    depth
+}
+
+struct Repl {
+    session: Session,
+    loader: ModuleLoader,
+    files: SimpleFiles<String, String>,
+}
+
+impl Repl {
+    fn new() -> Self {
+        Self {
+            session: Session::new(),
+            loader: ModuleLoader::new("."),
+            files: SimpleFiles::new(),
+        }
+    }
+
+    /// Returns `false` when the session should end.
+    fn handle_input(&mut self, input: &str) -> bool {
+        let Some(command) = input.strip_prefix(':') else {
+            self.eval("<repl>", input.to_string());
+            return true;
+        };
+
+        let (name, arg) = command
+            .split_once(char::is_whitespace)
+            .map(|(name, arg)| (name, arg.trim()))
+            .unwrap_or((command, ""));
+
+        match name {
+            "quit" | "q" => return false,
+            "help" | "h" => println!("{}", HELP),
+            "type" | "t" => self.print_type(arg),
+            "load" | "l" => {
+                if let Some(source) = read_source(std::path::Path::new(arg)) {
+                    self.eval(arg, source);
+                }
+            }
+            "import" | "i" => self.eval("<repl>", format!("import {}", arg)),
+            "vars" => {
+                for (name, data_type) in self.session.variables() {
+                    println!("{}: {}", name, data_type);
+                }
+            }
+            _ => eprintln!("Unknown command ':{}', type ':help' for help", name),
+        }
+
+        true
+    }
+
+    fn eval(&mut self, name: &str, source: String) {
+        let file_id = self.files.add(name.to_string(), source.clone());
+        let input = match SessionInput::parse(&source, file_id) {
+            Ok(input) => input,
+            Err(e) => return emit_compile_error(&self.files, &e),
+        };
+
+        if !self.load_imports(&input) {
+            return;
+        }
+
+        match self.session.eval(input) {
+            Ok(Some((value, data_type))) if data_type != DataType::Void => {
+                println!("{}: {}", format_value(&value), data_type);
+            }
+            Ok(_) => (),
+            Err(SessionError::Compile(e)) => emit_compile_error(&self.files, &e),
+            Err(e @ SessionError::Runtime(_)) => eprintln!("{}", e),
+        }
+    }
+
+    fn print_type(&mut self, expr: &str) {
+        let file_id = self.files.add("<repl>".to_string(), expr.to_string());
+        match self.session.type_of(expr, file_id) {
+            Ok(data_type) => println!("{}", data_type),
+            Err(e) => emit_compile_error(&self.files, &e),
+        }
+    }
+
+    /// Compiles imported modules that are not part of the session yet.
+    fn load_imports(&mut self, input: &SessionInput) -> bool {
+        for import in input.get_imports() {
+            let name = import.path.to_string();
+            if self.session.has_dependency(&name) {
+                continue;
+            }
+
+            match self.loader.load(&name, &mut self.files) {
+                Ok(_) => (),
+                Err(Some(e)) => {
+                    emit_compile_error(&self.files, &e);
+                    return false;
+                }
+                Err(None) => return false,
+            }
+        }
+
+        for (name, module) in self.loader.get_modules() {
+            if !self.session.has_dependency(name) {
+                self.session.add_dependency(name.clone(), module.clone());
+            }
+        }
+
+        true
+    }
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'")),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn brace_depth_ignores_strings_and_comments() {
+        assert_eq!(brace_depth("Int f() {\n"), 1);
+        assert_eq!(brace_depth("Int f() {\n return 1\n}\n"), 0);
+        assert_eq!(brace_depth("print('{')\n"), 0);
+        assert_eq!(brace_depth("print('\\'{')\n"), 0);
+        assert_eq!(brace_depth("while true { # }\n"), 1);
+    }
+}
@@ -1,30 +1,136 @@
-use mluva::runtime::Runtime;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
-use crate::commands;
+use codespan_reporting::files::SimpleFiles;
+use mluva::{ast::Ast, errors::CompileError, module::Module, runtime::Runtime, value::Value};
 
-pub fn command() -> Result<(), ()> {
-    let (config, modules) = commands::build::command()?;
-    let main_module_name = &config.root_module;
-    let Some(main_module) = modules.get(main_module_name) else {
+use crate::{
+    commands,
+    diagnostics::emit_compile_error,
+    module_loader::{ModuleLoader, read_source},
+};
+
+/// Exit code used when the program was compiled but failed while running, `EX_SOFTWARE`
+/// of `sysexits.h`. It is reserved, `main` returning it is a runtime error too.
+const RUNTIME_ERROR_EXIT_CODE: u8 = 70;
+
+pub fn command(file: Option<PathBuf>, args: Vec<String>) -> ExitCode {
+    let compiled = match file {
+        Some(file) => compile_script(&file),
+        None => compile_project(),
+    };
+
+    let Ok((main_module_name, modules)) = compiled else {
+        return ExitCode::FAILURE;
+    };
+
+    let main_module = &modules[&main_module_name];
+    let runtime = Runtime::new(main_module, &modules).with_args(&args);
+    let result = runtime.execute();
+
+    match result {
+        Ok(Value::Int(code)) => match u8::try_from(code) {
+            Ok(RUNTIME_ERROR_EXIT_CODE) => {
+                eprintln!(
+                    "Runtime error: 'main' returned {}, the exit status is reserved for runtime errors",
+                    code
+                );
+                ExitCode::from(RUNTIME_ERROR_EXIT_CODE)
+            }
+            Ok(code) => ExitCode::from(code),
+            Err(_) => {
+                eprintln!(
+                    "Runtime error: 'main' returned {}, exit status must be between 0 and 255",
+                    code
+                );
+                ExitCode::from(RUNTIME_ERROR_EXIT_CODE)
+            }
+        },
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Runtime error: {}", e);
+            ExitCode::from(RUNTIME_ERROR_EXIT_CODE)
+        }
+    }
+}
+
+/// Compiled modules with the name of the main module, the main module stays among them,
+/// so modules importing it call the same module.
+type Compiled = (String, HashMap<String, Module>);
+
+fn compile_project() -> Result<Compiled, ()> {
+    let (config, modules) = commands::build::command(false)?;
+    let main_module_name = config.root_module;
+    if !modules.contains_key(&main_module_name) {
         eprintln!(
             "Root module '{}' not found in compiled modules",
             main_module_name
         );
         return Err(());
-    };
+    }
 
     println!("Running the Mluva project...\n");
 
-    let runtime = Runtime::new(main_module, &modules);
-    let result = runtime.execute();
+    Ok((main_module_name, modules))
+}
+
+/// Compiles the script in memory, imports are resolved relative to its directory.
+/// The script is named by its file stem, the name other modules import it with.
+fn compile_script(path: &Path) -> Result<Compiled, ()> {
+    let mut files = SimpleFiles::new();
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let mut loader = ModuleLoader::new(base_dir);
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let result = compile_script_module(path, &name, &mut loader, &mut files);
 
+    let mut modules = loader.into_modules();
     match result {
-        Ok(_) => (),
-        Err(e) => {
-            eprintln!("Runtime error: {}", e);
+        Ok(Some(module)) => {
+            modules.insert(name.clone(), module);
+        }
+        Ok(None) => (),
+        Err(Some(e)) => {
+            emit_compile_error(&files, &e);
             return Err(());
         }
+        Err(None) => return Err(()),
+    };
+
+    if !modules[&name].is_executable() {
+        eprintln!("Script {} has no 'main' function", path.display());
+        return Err(());
+    }
+
+    Ok((name, modules))
+}
+
+/// A script that is a module of its directory is loaded with its imports, so modules it
+/// imports can import it back. Other scripts, e.g. without the `.mv` extension, are compiled
+/// separately and returned.
+fn compile_script_module(
+    path: &Path,
+    name: &str,
+    loader: &mut ModuleLoader,
+    files: &mut SimpleFiles<String, String>,
+) -> Result<Option<Module>, Option<CompileError>> {
+    if loader.module_path(name) == path {
+        loader.load(name, files)?;
+        return Ok(None);
     }
 
-    Ok(())
+    let source = read_source(path).ok_or(None)?;
+    let file_id = files.add(path.to_string_lossy().to_string(), source.clone());
+    let ast = Ast::from_string(&source, file_id)?;
+
+    loader.load_imports(&ast, files)?;
+    let module = Module::from_ast_and_dependencies(ast, loader.get_modules())?;
+
+    Ok(Some(module))
 }
@@ -0,0 +1,86 @@
+use std::{
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use mluva::{errors::RuntimeError, runtime::Runtime};
+
+use crate::commands;
+
+struct TestFailure {
+    name: String,
+    error: RuntimeError,
+}
+
+/// Runs test functions of all project modules, every test gets its own runtime.
+/// `filter` selects tests whose full name (`module:test_name`) contains it.
+pub fn command(filter: Option<String>) -> ExitCode {
+    let Ok((_, modules)) = commands::build::command(false) else {
+        return ExitCode::FAILURE;
+    };
+
+    let mut module_names: Vec<_> = modules.keys().collect();
+    module_names.sort();
+
+    let mut tests = vec![];
+    let mut filtered_out = 0;
+    for module_name in module_names {
+        let module = &modules[module_name];
+        for test_name in module.get_test_names() {
+            let full_name = format!("{}:{}", module_name, test_name);
+            if filter.as_ref().is_some_and(|f| !full_name.contains(f)) {
+                filtered_out += 1;
+                continue;
+            }
+
+            tests.push((module, test_name, full_name));
+        }
+    }
+
+    println!("\nRunning {} tests", tests.len());
+
+    let start = Instant::now();
+    let mut failures = vec![];
+    for (module, test_name, full_name) in tests.iter() {
+        let test_start = Instant::now();
+        let result = Runtime::new(module, &modules).call(test_name, vec![]);
+        let elapsed = format_duration(test_start.elapsed());
+
+        match result {
+            Ok(_) => println!("test {} ... ok ({})", full_name, elapsed),
+            Err(error) => {
+                println!("test {} ... FAILED ({})", full_name, elapsed);
+                failures.push(TestFailure {
+                    name: full_name.clone(),
+                    error,
+                });
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nFailures:");
+        for failure in failures.iter() {
+            println!("    {}: {}", failure.name, failure.error);
+        }
+    }
+
+    println!(
+        "\nTest result: {}. {} passed; {} failed; {} filtered out; finished in {}",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        tests.len() - failures.len(),
+        failures.len(),
+        filtered_out,
+        format_duration(start.elapsed())
+    );
+
+    if failures.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
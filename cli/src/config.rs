@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -8,6 +8,10 @@ pub struct Config {
 
     #[serde(default = "default_root_module", skip_serializing)]
     pub root_module: String,
+
+    /// Directory with module sources, `import utils.strings` reads `<source_root>/utils/strings.mv`
+    #[serde(default = "default_source_root", skip_serializing)]
+    pub source_root: String,
 }
 
 impl Default for Config {
@@ -15,6 +19,7 @@ impl Default for Config {
         Config {
             project_name: "My Mluva Project".to_string(),
             root_module: default_root_module(),
+            source_root: default_source_root(),
         }
     }
 }
@@ -30,6 +35,12 @@ impl Config {
         }
     }
 
+    /// Reads the config without reporting errors, `None` when it is missing or invalid.
+    pub fn read_from(path: &Path) -> Option<Self> {
+        let file = std::fs::File::open(path).ok()?;
+        serde_yaml::from_reader(file).ok()
+    }
+
     pub fn load_from_file() -> Result<Self, ()> {
         let path = Path::new(Self::FILE_PATH);
 
@@ -62,13 +73,34 @@ impl Config {
         Ok(())
     }
 
+    /// Source file of the module, `module` is a dotted path such as `utils.strings`.
+    pub fn module_file_path(&self, module: &str) -> PathBuf {
+        module_file_path(Path::new(&self.source_root), module)
+    }
+
+    /// Dotted module path of the source file, inverse of `module_file_path`.
+    pub fn module_name(&self, file_path: &str) -> Option<String> {
+        let path = Path::new(file_path);
+        let relative = match Path::new(&self.source_root) {
+            root if root == Path::new(".") => path,
+            root => path.strip_prefix(root).ok()?,
+        }
+        .with_extension("");
+
+        let segments: Vec<_> = relative
+            .components()
+            .map(|c| c.as_os_str().to_str())
+            .collect::<Option<_>>()?;
+        Some(segments.join("."))
+    }
+
     pub fn root_module_file_path(&self) -> String {
-        let root_module_path = Path::new(&self.root_module).with_extension("mv");
+        let root_module_path = self.module_file_path(&self.root_module);
         root_module_path.to_string_lossy().to_string()
     }
 
     pub fn save_root_module_to_file(&self, source: &str) -> Result<(), ()> {
-        let root_module_path = Path::new(&self.root_module).with_extension("mv");
+        let root_module_path = self.module_file_path(&self.root_module);
 
         let Ok(_) = std::fs::write(&root_module_path, source) else {
             eprintln!(
@@ -82,11 +114,25 @@ impl Config {
     }
 
     pub fn root_module_file_exists(&self) -> bool {
-        let root_module_path = Path::new(&self.root_module).with_extension("mv");
-        root_module_path.is_file()
+        self.module_file_path(&self.root_module).is_file()
     }
 }
 
+/// Source file of a dotted module path relative to the directory.
+pub fn module_file_path(dir: &Path, module: &str) -> PathBuf {
+    let mut path = PathBuf::new();
+    if dir != Path::new(".") {
+        path.push(dir);
+    }
+
+    path.extend(module.split('.'));
+    path.with_extension("mv")
+}
+
 fn default_root_module() -> String {
     "main".to_string()
 }
+
+fn default_source_root() -> String {
+    ".".to_string()
+}
@@ -0,0 +1,19 @@
+use codespan_reporting::{
+    files::SimpleFiles,
+    term::{
+        Config as CodespanConfig, emit_to_io_write,
+        termcolor::{ColorChoice, StandardStream},
+    },
+};
+use mluva::errors::CompileError;
+
+/// Writes compile error with the source snippet to stderr.
+pub fn emit_compile_error(files: &SimpleFiles<String, String>, error: &CompileError) {
+    let diag = error.to_diagnostic();
+    let writer = StandardStream::stderr(ColorChoice::Auto);
+    let Ok(_) = emit_to_io_write(&mut writer.lock(), &CodespanConfig::default(), files, &diag)
+    else {
+        eprintln!("Failed to write diagnostics");
+        return;
+    };
+}
@@ -1,21 +1,33 @@
+use std::process::ExitCode;
+
 use clap::Parser;
 
 use crate::{cli::Cli, commands::Commands};
 mod cli;
 mod commands;
 mod config;
+mod diagnostics;
+mod module_loader;
 mod module_metadata;
 
-fn main() {
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
     let result = match cli.command {
         Commands::Init => commands::init::command(),
-        Commands::Run => commands::run::command(),
-        Commands::Build => commands::build::command().map(|_| ()),
+        Commands::Run { file, args } => return commands::run::command(file, args),
+        Commands::Build { all } => commands::build::command(all).map(|_| ()),
+        Commands::Clean => commands::clean::command(),
+        Commands::Repl => commands::repl::command(),
+        Commands::Fmt { paths, check } => commands::fmt::command(paths, check),
+        Commands::Test { filter } => return commands::test::command(filter),
+        Commands::Lsp => commands::lsp::command(),
+        Commands::Disasm { target } => commands::disasm::command(target),
+        Commands::Asm { file, output } => commands::asm::command(file, output),
     };
 
-    if result.is_err() {
-        std::process::exit(1);
+    match result {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(_) => ExitCode::FAILURE,
     }
 }
@@ -0,0 +1,141 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use codespan_reporting::files::SimpleFiles;
+use mluva::{ast::Ast, errors::CompileError, module::Module, stdlib::standard_module};
+
+use crate::config::module_file_path;
+
+/// Compiles modules in memory, without touching the `.mluva` directory.
+/// Module names are dotted paths resolved relative to the base directory,
+/// e.g. `utils.strings` is read from `<base_dir>/utils/strings.mv`. Imports without a file,
+/// like `math`, are resolved to standard modules.
+pub struct ModuleLoader {
+    base_dir: PathBuf,
+    modules: HashMap<String, Module>,
+}
+
+impl ModuleLoader {
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            modules: HashMap::new(),
+        }
+    }
+
+    pub fn module_path(&self, name: &str) -> PathBuf {
+        module_file_path(&self.base_dir, name)
+    }
+
+    pub fn get_modules(&self) -> &HashMap<String, Module> {
+        &self.modules
+    }
+
+    pub fn into_modules(self) -> HashMap<String, Module> {
+        self.modules
+    }
+
+    /// Compiles all imports of the ast that are not loaded yet.
+    /// `Err(None)` means that the error was already reported.
+    pub fn load_imports(
+        &mut self,
+        ast: &Ast,
+        files: &mut SimpleFiles<String, String>,
+    ) -> Result<(), Option<CompileError>> {
+        for import in ast.get_imports() {
+            self.load(&import.path.to_string(), files)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compiles the module and all of its dependencies. Modules can import each other,
+    /// interfaces of the new modules are collected before their bodies are compiled.
+    /// `Err(None)` means that the error was already reported.
+    pub fn load(
+        &mut self,
+        name: &str,
+        files: &mut SimpleFiles<String, String>,
+    ) -> Result<(), Option<CompileError>> {
+        let mut asts = HashMap::new();
+        let mut order = vec![];
+        let mut standard = HashMap::new();
+        self.parse(name, &mut asts, &mut order, &mut standard, files)?;
+        self.modules.extend(standard);
+        if order.is_empty() {
+            return Ok(());
+        }
+
+        let interfaces = Module::interfaces(&asts, &self.modules)?;
+        let mut dependencies = self.modules.clone();
+        dependencies.extend(interfaces);
+
+        for name in order {
+            let ast = asts.remove(&name).unwrap();
+            let module = Module::from_ast_and_dependencies(ast, &dependencies)?;
+            self.modules.insert(name, module);
+        }
+
+        Ok(())
+    }
+
+    /// Parses the module and its imports that are not loaded yet,
+    /// `order` lists them with imports before the importing modules,
+    /// imported standard modules are collected in `standard`.
+    fn parse(
+        &self,
+        name: &str,
+        asts: &mut HashMap<String, Ast>,
+        order: &mut Vec<String>,
+        standard: &mut HashMap<String, Module>,
+        files: &mut SimpleFiles<String, String>,
+    ) -> Result<(), Option<CompileError>> {
+        if self.modules.contains_key(name) || asts.contains_key(name) || standard.contains_key(name)
+        {
+            return Ok(());
+        }
+
+        let path = self.module_path(name);
+        if !path.exists()
+            && let Some(module) = standard_module(name)
+        {
+            standard.insert(name.to_string(), module);
+            return Ok(());
+        }
+
+        let source = read_source(&path).ok_or(None)?;
+        let file_id = files.add(path.to_string_lossy().to_string(), source.clone());
+        let ast = Ast::from_string(&source, file_id)?;
+
+        let imports: Vec<String> = ast
+            .get_imports()
+            .iter()
+            .map(|import| import.path.to_string())
+            .collect();
+        asts.insert(name.to_string(), ast);
+
+        for import in imports {
+            self.parse(&import, asts, order, standard, files)?;
+        }
+        order.push(name.to_string());
+
+        Ok(())
+    }
+}
+
+/// Reads the source file, errors are reported to stderr.
+pub fn read_source(path: &Path) -> Option<String> {
+    let Ok(content) = std::fs::read(path) else {
+        eprintln!("Failed to read module file: {}", path.display());
+        return None;
+    };
+
+    let Ok(source) = String::from_utf8(content) else {
+        eprintln!("Module file is not valid UTF-8: {}", path.display());
+        return None;
+    };
+
+    Some(source)
+}
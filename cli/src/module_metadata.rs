@@ -1,8 +1,17 @@
 use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use mluva::{bytecode::BytecodeHeader, module::Module};
+use sha2::{Digest, Sha256};
 use std::{collections::HashMap, path::Path};
 
+use crate::config::Config;
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct ModuleMetadataStorage {
+    /// Version of the compiler that produced the cached bytecode
+    #[serde(default)]
+    pub compiler_version: String,
+    #[serde(default)]
+    pub bytecode_version: u8,
     #[serde(default = "default_hashes")]
     pub map: HashMap<String, ModuleMetadata>, // source_path -> content_hash (for change detection)
 }
@@ -46,19 +55,48 @@ impl ModuleMetadataStorage {
         Ok(storage)
     }
 
-    /// Check if module needs recompilation
-    pub fn needs_recompilation(&self, source_path: &str, content: &[u8]) -> bool {
-        self.map
-            .get(source_path)
-            .is_none_or(|m| m.needs_recompilation(content))
+    /// Whether the cache was produced by this compiler and bytecode version.
+    pub fn is_current(&self) -> bool {
+        self.compiler_version == mluva::VERSION
+            && self.bytecode_version == BytecodeHeader::CURRENT_VERSION
+    }
+
+    /// Forgets all cached modules, so every module is compiled again.
+    pub fn clear(&mut self) {
+        self.compiler_version = mluva::VERSION.to_string();
+        self.bytecode_version = BytecodeHeader::CURRENT_VERSION;
+        self.map.clear();
+    }
+
+    /// Check if module needs recompilation, either its source changed or an interface
+    /// of a dependency differs from the one the module was compiled against.
+    /// `dependency_hashes` are current interface hashes of the module's imports.
+    pub fn needs_recompilation(
+        &self,
+        source_path: &str,
+        content: &[u8],
+        dependency_hashes: &HashMap<String, String>,
+    ) -> bool {
+        self.map.get(source_path).is_none_or(|m| {
+            m.needs_recompilation(content) || m.dependency_hashes != *dependency_hashes
+        })
     }
 
-    /// Update hash for a module
-    pub fn update_hash(&mut self, source_path: &str, content: &[u8]) {
-        let hash = ModuleMetadata::calculate_content_hash(content);
+    /// Update hashes for a compiled module
+    pub fn update_hash(
+        &mut self,
+        source_path: &str,
+        content: &[u8],
+        module: &Module,
+        dependency_hashes: HashMap<String, String>,
+    ) {
         self.map.insert(
             source_path.to_string(),
-            ModuleMetadata { content_hash: hash },
+            ModuleMetadata {
+                content_hash: ModuleMetadata::calculate_content_hash(content),
+                interface_hash: ModuleMetadata::calculate_interface_hash(module),
+                dependency_hashes,
+            },
         );
     }
 }
@@ -70,16 +108,22 @@ fn default_hashes() -> HashMap<String, ModuleMetadata> {
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct ModuleMetadata {
     pub content_hash: String,
+    /// Hash of the functions and slots other modules can call, see `Module::interface_bytecode`
+    #[serde(default)]
+    pub interface_hash: String,
+    /// Interface hashes of imported modules at the time the module was compiled
+    #[serde(default)]
+    pub dependency_hashes: HashMap<String, String>,
 }
 
 impl ModuleMetadata {
+    /// SHA-256 of the content, stable across platforms and Rust releases.
     fn calculate_content_hash(content: &[u8]) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+        format!("{:x}", Sha256::digest(content))
+    }
 
-        let mut hasher = DefaultHasher::new();
-        content.hash(&mut hasher);
-        format!("{:x}", hasher.finish())
+    pub fn calculate_interface_hash(module: &Module) -> String {
+        Self::calculate_content_hash(&module.interface_bytecode())
     }
 
     fn needs_recompilation(&self, content: &[u8]) -> bool {
@@ -100,3 +144,34 @@ impl ModuleMetadata {
         URL_SAFE_NO_PAD.encode(normalized.as_bytes())
     }
 }
+
+/// Modules cached in `.mluva/`, used to resolve names of foreign functions.
+/// The modules are not verified and must not be executed.
+pub fn load_cached_modules() -> HashMap<String, Module> {
+    let mut modules = HashMap::new();
+    if !Path::new(ModuleMetadataStorage::FILE_PATH).exists() {
+        return modules;
+    }
+
+    let Ok(storage) = ModuleMetadataStorage::load_from_file() else {
+        return modules;
+    };
+
+    let config = Config::read_from(Path::new(Config::FILE_PATH)).unwrap_or_default();
+    for source_path in storage.map.keys() {
+        let path = ModuleMetadata::source_to_bytecode_path(source_path);
+        let Some(bytecode) = std::fs::read(path).ok() else {
+            continue;
+        };
+
+        let Some(name) = config.module_name(source_path) else {
+            continue;
+        };
+
+        if let Ok(module) = Module::from_bytecode_bytes(&bytecode) {
+            modules.insert(name, module);
+        }
+    }
+
+    modules
+}
@@ -0,0 +1,193 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::{cli, project_dir};
+
+/// Names of the modules the build reported as compiled, in the build order.
+fn compiled_modules(stdout: &str) -> Vec<&str> {
+    stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix("Compiling "))
+        .collect()
+}
+
+#[test]
+fn dependents_are_rebuilt_when_interface_changes() {
+    let dir = project_dir("interface_changes");
+    std::fs::write(
+        dir.join("main.mv"),
+        "import math\n\nInt main() {\n    return math:add(1, 2)\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("math.mv"),
+        "pub Int add(Int a, Int b) {\n    return a + b\n}\n",
+    )
+    .unwrap();
+
+    let (code, stdout) = cli(&dir, &["run"]);
+    assert_eq!(code, Some(3));
+    assert_eq!(compiled_modules(&stdout), ["math", "main"]);
+
+    // only the body changed, main keeps its cached bytecode
+    std::fs::write(
+        dir.join("math.mv"),
+        "pub Int add(Int a, Int b) {\n    return b + a\n}\n",
+    )
+    .unwrap();
+    let (code, stdout) = cli(&dir, &["run"]);
+    assert_eq!(code, Some(3));
+    assert_eq!(compiled_modules(&stdout), ["math"]);
+    assert!(stdout.contains("main is up to date"), "{}", stdout);
+
+    // a new function shifts the slot of `add`, main has to be compiled again
+    std::fs::write(
+        dir.join("math.mv"),
+        "Int mul(Int a, Int b) {\n    return a * b * 10\n}\n\npub Int add(Int a, Int b) {\n    return b + a\n}\n",
+    )
+    .unwrap();
+    let (code, stdout) = cli(&dir, &["run"]);
+    assert_eq!(code, Some(3));
+    assert_eq!(compiled_modules(&stdout), ["math", "main"]);
+}
+
+#[test]
+fn cache_is_versioned_and_can_be_cleaned() {
+    let dir = project_dir("versioned_cache");
+    std::fs::write(dir.join("main.mv"), "Int main() {\n    return 7\n}\n").unwrap();
+
+    let (code, stdout) = cli(&dir, &["build"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(compiled_modules(&stdout), ["main"]);
+    let metadata_path = dir.join(".mluva/modules.yaml");
+    let metadata = std::fs::read_to_string(&metadata_path).unwrap();
+    assert!(metadata.contains(&format!("compiler_version: {}", env!("CARGO_PKG_VERSION"))));
+    assert!(metadata.contains("bytecode_version: 3"));
+
+    let (code, stdout) = cli(&dir, &["build"]);
+    assert_eq!(code, Some(0));
+    assert!(compiled_modules(&stdout).is_empty(), "{}", stdout);
+    assert!(stdout.contains("main is up to date"), "{}", stdout);
+
+    let (code, stdout) = cli(&dir, &["build", "--all"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(compiled_modules(&stdout), ["main"]);
+
+    // cache of another compiler version is not reused
+    let other_version = metadata.replace(
+        &format!("compiler_version: {}", env!("CARGO_PKG_VERSION")),
+        "compiler_version: 0.0.1",
+    );
+    std::fs::write(&metadata_path, other_version).unwrap();
+    let (code, stdout) = cli(&dir, &["run"]);
+    assert_eq!(code, Some(7));
+    assert_eq!(compiled_modules(&stdout), ["main"]);
+
+    assert_eq!(cli(&dir, &["clean"]).0, Some(0));
+    assert!(!dir.join(".mluva").exists());
+    assert!(dir.join("main.mv").exists());
+    assert_eq!(cli(&dir, &["clean"]).0, Some(0));
+
+    let not_project = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("clean_not_project");
+    std::fs::create_dir_all(not_project.join(".mluva")).unwrap();
+    assert_eq!(cli(&not_project, &["clean"]).0, Some(1));
+    assert!(not_project.join(".mluva").exists());
+}
+
+#[test]
+fn nested_modules_under_source_root() {
+    let dir = project_dir("nested_modules");
+    std::fs::write(
+        dir.join("mluva.yaml"),
+        "project_name: Nested\nsource_root: src\n",
+    )
+    .unwrap();
+    std::fs::create_dir_all(dir.join("src/utils")).unwrap();
+    std::fs::create_dir_all(dir.join("src/text")).unwrap();
+
+    std::fs::write(
+        dir.join("src/main.mv"),
+        "import utils.strings\nimport text.strings\n\nInt main() {\n    return utils.strings:value() * 10 + text.strings:value()\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("src/utils/strings.mv"),
+        "import utils.chars\n\npub Int value() {\n    return chars:value() + 1\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("src/utils/chars.mv"),
+        "pub Int value() {\n    return 3\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("src/text/strings.mv"),
+        "pub Int value() {\n    return 2\n}\n",
+    )
+    .unwrap();
+
+    assert_eq!(cli(&dir, &["run"]).0, Some(42));
+
+    let (_, listing) = cli(&dir, &["disasm", "utils.strings"]);
+    assert!(
+        listing.contains("foreign_call utils.chars:value"),
+        "{}",
+        listing
+    );
+
+    // the last segment alone is ambiguous when both modules are imported
+    std::fs::write(
+        dir.join("src/main.mv"),
+        "import utils.strings\nimport text.strings\n\nInt main() {\n    return strings:value()\n}\n",
+    )
+    .unwrap();
+    assert_eq!(cli(&dir, &["run"]).0, Some(1));
+}
+
+#[test]
+fn modules_importing_each_other() {
+    let dir = project_dir("cyclic_imports");
+    std::fs::write(
+        dir.join("main.mv"),
+        "import parity\n\npub const Int LIMIT = 4\nlet calls = 0\n\npub Bool is_even(Int n) {\n    calls = calls + 1\n    if n == 0 {\n        return true\n    }\n    return parity:is_odd(n - 1)\n}\n\nInt main() {\n    if is_even(parity:TOP) {\n        return calls\n    }\n    return 0\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("parity.mv"),
+        "import main\n\npub const Int TOP = main:LIMIT * 2\n\npub Bool is_odd(Int n) {\n    if n == 0 {\n        return false\n    }\n    return main:is_even(n - 1)\n}\n",
+    )
+    .unwrap();
+
+    // is_even is called for 8, 6, 4, 2 and 0, all calls count in the same global
+    assert_eq!(cli(&dir, &["run"]).0, Some(5));
+    assert_eq!(cli(&dir, &["run"]).0, Some(5), "cached modules");
+
+    // constants of the two modules can not depend on each other
+    std::fs::write(
+        dir.join("main.mv"),
+        "import parity\n\npub const Int LIMIT = parity:TOP\n\nInt main() {\n    return LIMIT\n}\n",
+    )
+    .unwrap();
+    assert_eq!(cli(&dir, &["run"]).0, Some(1));
+}
+
+#[test]
+fn standard_math_module() {
+    let dir = project_dir("standard_math_module");
+    std::fs::write(
+        dir.join("main.mv"),
+        "import math\n\nInt main() {\n    return (math:sqrt(81.0) + math:PI.floor()).to_int()\n}\n",
+    )
+    .unwrap();
+
+    let (code, stdout) = cli(&dir, &["run"]);
+    assert_eq!(code, Some(12));
+    // the standard module is not compiled and cached with the project modules
+    assert_eq!(compiled_modules(&stdout), ["main"]);
+    let cached = std::fs::read_dir(dir.join(".mluva/modules"))
+        .unwrap()
+        .count();
+    assert_eq!(cached, 1);
+    assert_eq!(cli(&dir, &["run"]).0, Some(12));
+}
@@ -0,0 +1,41 @@
+//! Helpers shared by the CLI integration tests.
+// every test crate includes this module and uses only some of the helpers
+#![allow(dead_code)]
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// Runs the CLI in `dir` and returns everything it printed.
+pub fn cli_output(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cli"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+/// Runs the CLI in `dir`, returns its exit code and standard output.
+pub fn cli(dir: &Path, args: &[&str]) -> (Option<i32>, String) {
+    let output = cli_output(dir, args);
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+/// Empty directory called `name` in the target directory, previous content is removed.
+pub fn empty_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Empty project called `name` in the target directory, previous content is removed.
+pub fn project_dir(name: &str) -> PathBuf {
+    let dir = empty_dir(name);
+    std::fs::write(dir.join("mluva.yaml"), format!("project_name: {}\n", name)).unwrap();
+    dir
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use common::{cli, cli_output, project_dir};
+
+#[test]
+fn disasm_module_and_bytecode_file() {
+    let dir = project_dir("disasm_module_and_bytecode_file");
+    std::fs::write(
+        dir.join("main.mv"),
+        "import math\n\nInt main() {\n    return math:add(1, 2)\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("math.mv"),
+        "pub Int add(Int a, Int b) {\n    return a + b\n}\n",
+    )
+    .unwrap();
+
+    let (code, _) = cli(&dir, &["disasm", "main"]);
+    assert_eq!(code, Some(1));
+
+    assert_eq!(cli(&dir, &["build"]).0, Some(0));
+
+    let (code, listing) = cli(&dir, &["disasm", "main"]);
+    assert_eq!(code, Some(0));
+    assert!(listing.starts_with("# version 3\n# main slot 0\n"));
+    assert!(listing.contains("function Int main() slots 0\n"));
+    assert!(listing.contains("foreign_call math:add"));
+
+    let bytecode = std::fs::read_dir(dir.join(".mluva/modules"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| {
+            let (_, listing) = cli(&dir, &["disasm", path.to_str().unwrap()]);
+            listing.contains("function pub Int add(Int a, Int b) slots 2\n")
+        });
+    assert!(bytecode.is_some());
+}
+
+#[test]
+fn asm_round_trip() {
+    let dir = project_dir("asm_round_trip");
+    std::fs::write(
+        dir.join("main.mv"),
+        "Int main() {\n    let i = 0\n    while i < 3 {\n        i = i + 1\n    }\n    return i\n}\n",
+    )
+    .unwrap();
+
+    assert_eq!(cli(&dir, &["build"]).0, Some(0));
+    let (_, listing) = cli(&dir, &["disasm", "main"]);
+    std::fs::write(dir.join("main.mva"), &listing).unwrap();
+
+    let (code, stdout) = cli(&dir, &["asm", "main.mva", "-o", "out.mvb"]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("Assembled out.mvb"));
+
+    let (_, reassembled) = cli(&dir, &["disasm", "out.mvb"]);
+    assert_eq!(reassembled, listing);
+
+    std::fs::write(
+        dir.join("broken.mva"),
+        "function Int main()\n    jump nowhere\n",
+    )
+    .unwrap();
+    assert_eq!(cli(&dir, &["asm", "broken.mva"]).0, Some(1));
+    assert!(!dir.join("broken.mvb").exists());
+}
+
+#[test]
+fn invalid_cached_bytecode_is_recompiled() {
+    let dir = project_dir("invalid_cached_bytecode");
+    std::fs::write(dir.join("main.mv"), "Int main() {\n    return 3\n}\n").unwrap();
+    assert_eq!(cli(&dir, &["build"]).0, Some(0));
+
+    // replace the cached module with bytecode that adds an int to a string
+    let bytecode = std::fs::read_dir(dir.join(".mluva/modules"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|e| e == "mvb"))
+        .unwrap();
+    std::fs::write(
+        dir.join("broken.mva"),
+        "function Int main()\n    push 1\n    push 'a'\n    add\n    return\n",
+    )
+    .unwrap();
+    let (code, _) = cli(
+        &dir,
+        &["asm", "broken.mva", "-o", bytecode.to_str().unwrap()],
+    );
+    assert_eq!(code, Some(0));
+
+    let (code, listing) = cli(&dir, &["disasm", "main"]);
+    assert_eq!(code, Some(1));
+    assert!(listing.contains("push 'a'"));
+
+    let output = cli_output(&dir, &["run"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr.contains("Invalid bytecode of module main.mv, recompiling"));
+    assert!(stderr.contains("expected two numbers of the same type, found int and string"));
+
+    let (code, _) = cli(&dir, &["disasm", "main"]);
+    assert_eq!(code, Some(0));
+}
+
+#[test]
+fn corrupted_function_slot_is_reported() {
+    let dir = project_dir("corrupted_function_slot");
+    std::fs::write(
+        dir.join("main.mv"),
+        "Int helper() {\n    return 1\n}\n\nInt main() {\n    return helper()\n}\n",
+    )
+    .unwrap();
+    assert_eq!(cli(&dir, &["build"]).0, Some(0));
+
+    // point 'helper' in the function map at slot 5, the module has 2 functions
+    let bytecode = std::fs::read_dir(dir.join(".mluva/modules"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|e| e == "mvb"))
+        .unwrap();
+    let mut bytes = std::fs::read(&bytecode).unwrap();
+    let name = [&6u32.to_le_bytes()[..], b"helper"].concat();
+    let at = bytes
+        .windows(name.len())
+        .position(|window| window == name)
+        .unwrap()
+        + name.len();
+    bytes[at..at + 4].copy_from_slice(&5u32.to_le_bytes());
+    std::fs::write(&bytecode, bytes).unwrap();
+
+    let output = cli_output(&dir, &["disasm", bytecode.to_str().unwrap()]);
+    let listing = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(listing.contains("# function helper has <invalid slot 5>"));
+    assert!(listing.contains("function <invalid slot 0> slots"));
+    assert!(stderr.contains("Bytecode verification failed"));
+    assert!(!stderr.contains("panicked"));
+}
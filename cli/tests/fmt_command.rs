@@ -0,0 +1,34 @@
+mod common;
+
+use std::path::Path;
+
+use common::{cli, empty_dir};
+
+fn fmt(dir: &Path, args: &[&str]) -> Option<i32> {
+    let args: Vec<&str> = ["fmt"].into_iter().chain(args.iter().copied()).collect();
+    cli(dir, &args).0
+}
+
+#[test]
+fn fmt_check_and_write() {
+    let dir = empty_dir("fmt_check_and_write");
+    std::fs::create_dir_all(dir.join("utils")).unwrap();
+
+    let source = "# entry\nVoid main(){\nprint( 1+2 ) # sum\n}\n";
+    std::fs::write(dir.join("main.mv"), source).unwrap();
+    std::fs::write(dir.join("utils/strings.mv"), source).unwrap();
+
+    assert_eq!(fmt(&dir, &["--check"]), Some(1));
+    assert_eq!(
+        std::fs::read_to_string(dir.join("main.mv")).unwrap(),
+        source
+    );
+
+    assert_eq!(fmt(&dir, &[]), Some(0));
+    assert_eq!(fmt(&dir, &["--check"]), Some(0));
+
+    let expected = "# entry\nVoid main() {\n    print(1 + 2) # sum\n}\n";
+    for file in ["main.mv", "utils/strings.mv"] {
+        assert_eq!(std::fs::read_to_string(dir.join(file)).unwrap(), expected);
+    }
+}
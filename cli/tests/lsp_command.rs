@@ -0,0 +1,196 @@
+mod common;
+
+use std::{
+    io::{BufReader, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use common::empty_dir;
+
+use lsp_server::{Message, Notification, Request, RequestId};
+use serde_json::{Value, json};
+
+const MAIN_SOURCE: &str = "import math
+
+Int main() {
+    let x = math:add(1, 2)
+    return x
+}
+";
+
+fn uri(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+fn request(id: i32, method: &str, params: Value) -> Message {
+    Request::new(RequestId::from(id), method.to_string(), params).into()
+}
+
+fn notification(method: &str, params: Value) -> Message {
+    Notification::new(method.to_string(), params).into()
+}
+
+fn position(uri: &str, line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": { "uri": uri },
+        "position": { "line": line, "character": character },
+    })
+}
+
+/// Sends all messages to the server and returns everything it wrote.
+fn run_session(dir: &Path, messages: Vec<Message>) -> Vec<Message> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cli"))
+        .arg("lsp")
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    for message in messages {
+        message.write(&mut stdin).unwrap();
+    }
+    stdin.flush().unwrap();
+    drop(stdin);
+
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut output = vec![];
+    while let Some(message) = Message::read(&mut stdout).unwrap() {
+        output.push(message);
+    }
+
+    assert!(child.wait().unwrap().success());
+    output
+}
+
+fn response(output: &[Message], id: i32) -> Value {
+    output
+        .iter()
+        .find_map(|message| match message {
+            Message::Response(r) if r.id == RequestId::from(id) => r.result.clone(),
+            _ => None,
+        })
+        .unwrap()
+}
+
+fn diagnostics(output: &[Message]) -> Vec<Value> {
+    output
+        .iter()
+        .filter_map(|message| match message {
+            Message::Notification(n) if n.method == "textDocument/publishDiagnostics" => {
+                Some(n.params["diagnostics"].clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn lsp_session() {
+    let dir = empty_dir("lsp_session");
+
+    let math_source = "pub Int add(Int a, Int b) {\n    return a + b\n}\n";
+    std::fs::write(dir.join("math.mv"), math_source).unwrap();
+    std::fs::write(dir.join("main.mv"), MAIN_SOURCE).unwrap();
+
+    let main_uri = uri(&dir.join("main.mv"));
+    let changed_source = MAIN_SOURCE.replace("return x", "let y = math:\n    return x");
+
+    let output = run_session(
+        &dir,
+        vec![
+            request(1, "initialize", json!({ "capabilities": {} })),
+            notification("initialized", json!({})),
+            notification(
+                "textDocument/didOpen",
+                json!({ "textDocument": {
+                    "uri": main_uri, "languageId": "mluva", "version": 1, "text": MAIN_SOURCE,
+                }}),
+            ),
+            request(2, "textDocument/hover", position(&main_uri, 4, 11)),
+            request(3, "textDocument/definition", position(&main_uri, 3, 18)),
+            request(4, "textDocument/hover", position(&main_uri, 3, 18)),
+            notification(
+                "textDocument/didChange",
+                json!({
+                    "textDocument": { "uri": main_uri, "version": 2 },
+                    "contentChanges": [{ "text": changed_source }],
+                }),
+            ),
+            request(5, "textDocument/completion", position(&main_uri, 4, 17)),
+            request(6, "textDocument/completion", position(&main_uri, 5, 4)),
+            request(7, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+        ],
+    );
+
+    let hover = response(&output, 2);
+    assert!(
+        hover["contents"]["value"]
+            .as_str()
+            .unwrap()
+            .contains("int x")
+    );
+
+    let definition = response(&output, 3);
+    assert_eq!(definition["uri"], uri(&dir.join("math.mv")));
+    assert_eq!(definition["range"]["start"]["line"], 0);
+
+    let hover = response(&output, 4);
+    let text = hover["contents"]["value"].as_str().unwrap();
+    assert!(text.contains("int math:add(int a, int b)"));
+
+    let diagnostics = diagnostics(&output);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0], json!([]));
+    assert_eq!(diagnostics[1].as_array().unwrap().len(), 1);
+
+    // the changed document does not parse, the last analysis is used
+    let labels = |id| {
+        response(&output, id)
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["label"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(labels(5), vec!["add"]);
+
+    let labels = labels(6);
+    for label in ["x", "main", "math", "print"] {
+        assert!(labels.contains(&label.to_string()), "missing {}", label);
+    }
+}
+
+#[test]
+fn lsp_type_error_diagnostic() {
+    let dir = empty_dir("lsp_type_error_diagnostic");
+
+    let source = "Void main() {\n    Int x = 'text'\n}\n";
+    let main_uri = uri(&dir.join("main.mv"));
+    let output = run_session(
+        &dir,
+        vec![
+            request(1, "initialize", json!({ "capabilities": {} })),
+            notification("initialized", json!({})),
+            notification(
+                "textDocument/didOpen",
+                json!({ "textDocument": {
+                    "uri": main_uri, "languageId": "mluva", "version": 1, "text": source,
+                }}),
+            ),
+            request(2, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+        ],
+    );
+
+    let diagnostics = diagnostics(&output);
+    let diagnostic = &diagnostics[0][0];
+    assert_eq!(diagnostic["severity"], 1);
+    assert_eq!(
+        diagnostic["range"]["start"],
+        json!({ "line": 1, "character": 4 })
+    );
+}
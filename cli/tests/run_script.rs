@@ -0,0 +1,108 @@
+mod common;
+
+use common::{cli, cli_output, empty_dir};
+
+#[test]
+fn run_script_with_relative_import() {
+    let dir = empty_dir("run_script_with_relative_import");
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    std::fs::write(
+        dir.join("lib/math.mv"),
+        "pub Int square(Int x) {\n    return x * x\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("lib/script.mv"),
+        "#!/usr/bin/env -S mluva run\nimport math\n\nVoid main() {\n    print(math:square(7))\n}\n",
+    )
+    .unwrap();
+
+    let (code, stdout) = cli(&dir, &["run", "lib/script.mv"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "49\n");
+    assert!(!dir.join(".mluva").exists());
+}
+
+#[test]
+fn run_script_exit_codes() {
+    let dir = empty_dir("run_script_exit_codes");
+    std::fs::write(
+        dir.join("compile.mv"),
+        "Void main() {\n    let x = 1 + true\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("runtime.mv"),
+        "Void main() {\n    let x = 1 / 0\n}\n",
+    )
+    .unwrap();
+
+    assert_eq!(cli(&dir, &["run", "compile.mv"]).0, Some(1));
+    assert_eq!(cli(&dir, &["run", "runtime.mv"]).0, Some(70));
+    assert_eq!(cli(&dir, &["run", "missing.mv"]).0, Some(1));
+
+    for (code, expected) in [
+        ("69", 69),
+        ("70", 70),
+        ("255", 255),
+        ("256", 70),
+        ("-1", 70),
+    ] {
+        let source = format!("Int main() {{\n    return {}\n}}\n", code);
+        std::fs::write(dir.join("status.mv"), source).unwrap();
+        assert_eq!(
+            cli(&dir, &["run", "status.mv"]).0,
+            Some(expected),
+            "{}",
+            code
+        );
+    }
+
+    let output = cli_output(&dir, &["run", "status.mv"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("'main' returned -1, exit status must be between 0 and 255"),
+        "{}",
+        stderr
+    );
+
+    // 70 is reserved, returning it is reported like any other runtime error
+    std::fs::write(dir.join("status.mv"), "Int main() {\n    return 70\n}\n").unwrap();
+    let output = cli_output(&dir, &["run", "status.mv"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(70));
+    assert!(
+        stderr.contains("'main' returned 70, the exit status is reserved for runtime errors"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn run_script_with_arguments() {
+    let dir = empty_dir("run_script_with_arguments");
+    std::fs::write(
+        dir.join("args.mv"),
+        "Int main() {\n    let i = 0\n    while i < arg_count() {\n        print(arg(i))\n        i = i + 1\n    }\n    return arg_count() + 40\n}\n",
+    )
+    .unwrap();
+
+    let output = cli_output(&dir, &["run", "args.mv", "--", "a", "b c"]);
+
+    assert_eq!(output.status.code(), Some(42));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "a\nb c\n");
+}
+
+#[test]
+fn run_script_with_standard_module() {
+    let dir = empty_dir("run_script_with_standard_module");
+    std::fs::write(
+        dir.join("circle.mv"),
+        "import math\n\nVoid main() {\n    print((math:PI * 4.0).round(), ' ', 2.pow(10), ' ', (-7).abs().to_float().sqrt().floor())\n}\n",
+    )
+    .unwrap();
+
+    let (code, stdout) = cli(&dir, &["run", "circle.mv"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "13 1024 2\n");
+}
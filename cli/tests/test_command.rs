@@ -0,0 +1,46 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::{cli, project_dir};
+
+fn test_project(name: &str) -> PathBuf {
+    let dir = project_dir(name);
+    std::fs::write(
+        dir.join("main.mv"),
+        "import math\n\nVoid main() {\n}\n\nVoid test_main() {\n    assert_eq(math:add(1, 2), 3)\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("math.mv"),
+        "pub Int add(Int a, Int b) {\n    return a + b\n}\n\nVoid test_add() {\n    assert_eq(add(2, 2), 5)\n}\n\nVoid test_ok() {\n    assert(add(1, 1) == 2)\n}\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn run_tests(name: &str, filter: Option<&str>) -> (Option<i32>, String) {
+    let mut args = vec!["test"];
+    args.extend(filter);
+    cli(&test_project(name), &args)
+}
+
+#[test]
+fn failing_test_is_reported() {
+    let (code, stdout) = run_tests("failing_test_is_reported", None);
+
+    assert_eq!(code, Some(1));
+    assert!(stdout.contains("test main:test_main ... ok"));
+    assert!(stdout.contains("test math:test_add ... FAILED"));
+    assert!(stdout.contains("math:test_add: Assertion failed: expected 5, actual 4"));
+    assert!(stdout.contains("Test result: FAILED. 2 passed; 1 failed; 0 filtered out"));
+}
+
+#[test]
+fn tests_are_filtered_by_name() {
+    let (code, stdout) = run_tests("tests_are_filtered_by_name", Some("_ok"));
+
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("test math:test_ok ... ok"));
+    assert!(stdout.contains("Test result: ok. 1 passed; 0 failed; 2 filtered out"));
+}
@@ -0,0 +1,698 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::BuiltinFunction,
+    data_type::DataType,
+    diagnostics::{FileId, Span},
+    errors::CompileError,
+    function::{FunctionSigniture, FunctionSource, Parameter, Visibility},
+    global::{Constant, Global},
+    instruction::Instruction,
+    module::Module,
+    value::Value,
+};
+
+/// Assembles textual bytecode (`.mva`) into a module, the disassembler listing is a valid input.
+///
+/// Every function starts with a header `function Int add(Int a, Int b) slots 2`, where `slots`
+/// is optional and public functions start with `pub` (`function pub Int add(...)`), followed
+/// by one instruction per line. Instructions can be prefixed with their index, which is checked.
+/// Jump targets are labels (`loop:`) or indices, called functions are referenced by name or
+/// slot, names of foreign functions are resolved from `dependencies`.
+///
+/// Constants are declared as `const pub Int MAX 100`, globals as `global Int count` in the slot
+/// order. Instructions of the initializer of globals follow the `init slots 0` header.
+pub fn assemble(
+    source: &str,
+    file_id: FileId,
+    dependencies: &HashMap<String, Module>,
+) -> Result<Module, CompileError> {
+    let Sections {
+        functions,
+        init,
+        constants,
+        globals,
+    } = split_sections(source, file_id)?;
+
+    let mut function_map = HashMap::new();
+    for (slot, function) in functions.iter().enumerate() {
+        if function_map
+            .insert(function.name.clone(), slot as u32)
+            .is_some()
+        {
+            return Err(CompileError::function_already_defined_at(
+                &function.name,
+                function.span,
+            ));
+        }
+    }
+
+    let mut function_signitures = vec![];
+    let mut function_sources = vec![];
+    for function in functions {
+        let body = assemble_body(&function.lines, &function_map, dependencies)?;
+        let slot_count = function
+            .slot_count
+            .unwrap_or_else(|| required_slot_count(&function.signiture, &body));
+
+        function_signitures.push(function.signiture);
+        function_sources.push(FunctionSource::new(slot_count, body));
+    }
+
+    let init_source = match init {
+        Some(init) => {
+            let body = assemble_body(&init.lines, &function_map, dependencies)?;
+            let slot_count = init
+                .slot_count
+                .unwrap_or_else(|| required_slot_count(&init.signiture, &body));
+            Some(FunctionSource::new(slot_count, body))
+        }
+        None => None,
+    };
+
+    let main_slot = function_map.get("main").copied();
+    let module = Module::new(
+        main_slot,
+        function_map,
+        function_signitures,
+        function_sources,
+    )
+    .with_constants(constants)
+    .with_globals(globals, init_source);
+
+    Ok(module)
+}
+
+/// Non-empty line without the comment.
+struct Line<'a> {
+    text: &'a str,
+    span: Span,
+}
+
+impl Line<'_> {
+    fn error(&self, msg: impl Into<String>) -> CompileError {
+        CompileError::invalid_assembly_at(msg, self.span)
+    }
+}
+
+struct FunctionAssembly<'a> {
+    name: String,
+    signiture: FunctionSigniture,
+    slot_count: Option<usize>,
+    lines: Vec<Line<'a>>,
+    span: Span,
+}
+
+#[derive(Default)]
+struct Sections<'a> {
+    functions: Vec<FunctionAssembly<'a>>,
+    /// initializer of globals
+    init: Option<FunctionAssembly<'a>>,
+    constants: Vec<Constant>,
+    globals: Vec<Global>,
+}
+
+fn split_sections(source: &str, file_id: FileId) -> Result<Sections<'_>, CompileError> {
+    let mut sections = Sections::default();
+    let mut in_init = false;
+    let mut offset = 0;
+
+    for raw_line in source.split_inclusive('\n') {
+        let line_start = offset;
+        offset += raw_line.len();
+
+        let without_comment = strip_comment(raw_line);
+        let text = without_comment.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let lo = line_start + (without_comment.len() - without_comment.trim_start().len());
+        let line = Line {
+            text,
+            span: Span::new(file_id, lo, lo + text.len()),
+        };
+
+        if let Some(header) = text.strip_prefix("function ") {
+            sections.functions.push(parse_header(header, &line)?);
+            in_init = false;
+            continue;
+        }
+
+        if let Some(declaration) = text.strip_prefix("const ") {
+            sections.constants.push(parse_constant(declaration, &line)?);
+            continue;
+        }
+
+        if let Some(declaration) = text.strip_prefix("global ") {
+            sections.globals.push(parse_global(declaration, &line)?);
+            continue;
+        }
+
+        let (first, rest) = split_word(text);
+        if first == "init" {
+            if sections.init.is_some() {
+                return Err(line.error("initializer of globals is already defined"));
+            }
+            sections.init = Some(parse_init_header(rest, &line)?);
+            in_init = true;
+            continue;
+        }
+
+        let function = match (in_init, &mut sections.init) {
+            (true, Some(init)) => init,
+            _ => sections
+                .functions
+                .last_mut()
+                .ok_or_else(|| line.error("instruction outside of a function"))?,
+        };
+        function.lines.push(line);
+    }
+
+    Ok(sections)
+}
+
+/// Removes `#` comment, `#` inside of a string literal is kept.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, ch) in line.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '\'' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => (),
+        }
+    }
+
+    line
+}
+
+/// Parses `Int add(Int a, Int b) slots 2`, public functions start with `pub`.
+fn parse_header<'a>(header: &str, line: &Line) -> Result<FunctionAssembly<'a>, CompileError> {
+    let invalid = || {
+        line.error(
+            "invalid function header, expected e.g. 'function Int add(Int a, Int b) slots 2'",
+        )
+    };
+
+    let (head, rest) = header.split_once('(').ok_or_else(invalid)?;
+    let (params, tail) = rest.split_once(')').ok_or_else(invalid)?;
+
+    let (visibility, head) = match head.trim_start().strip_prefix("pub ") {
+        Some(head) => (Visibility::Public, head),
+        None => (Visibility::Private, head),
+    };
+
+    let mut head = head.split_whitespace();
+    let (Some(return_type), Some(name), None) = (head.next(), head.next(), head.next()) else {
+        return Err(invalid());
+    };
+    let return_type = parse_data_type(return_type, line)?;
+
+    let mut parameters = vec![];
+    for param in params.split(',').filter(|p| !p.trim().is_empty()) {
+        let mut parts = param.split_whitespace();
+        let (Some(data_type), Some(name), None) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        parameters.push(Parameter::new(
+            name.to_string(),
+            parse_data_type(data_type, line)?,
+        ));
+    }
+
+    let slot_count = match tail.split_whitespace().collect::<Vec<_>>()[..] {
+        [] => None,
+        ["slots", count] => Some(count.parse().map_err(|_| invalid())?),
+        _ => return Err(invalid()),
+    };
+
+    Ok(FunctionAssembly {
+        name: name.to_string(),
+        signiture: FunctionSigniture::new(return_type, parameters).with_visibility(visibility),
+        slot_count,
+        lines: vec![],
+        span: line.span,
+    })
+}
+
+/// Parses `slots 2` behind the `init` keyword, the slots are optional.
+fn parse_init_header<'a>(tail: &str, line: &Line) -> Result<FunctionAssembly<'a>, CompileError> {
+    let slot_count = match tail.split_whitespace().collect::<Vec<_>>()[..] {
+        [] => None,
+        ["slots", count] => Some(
+            count
+                .parse()
+                .map_err(|_| line.error(format!("expected a number, found '{}'", count)))?,
+        ),
+        _ => return Err(line.error("invalid initializer header, expected e.g. 'init slots 0'")),
+    };
+
+    Ok(FunctionAssembly {
+        name: String::new(),
+        signiture: FunctionSigniture::new(DataType::Void, vec![]),
+        slot_count,
+        lines: vec![],
+        span: line.span,
+    })
+}
+
+/// Parses `pub Int MAX 100`, the type must match the literal.
+fn parse_constant(declaration: &str, line: &Line) -> Result<Constant, CompileError> {
+    let (visibility, declaration) = match declaration.strip_prefix("pub ") {
+        Some(declaration) => (Visibility::Public, declaration),
+        None => (Visibility::Private, declaration),
+    };
+
+    let (data_type, rest) = split_word(declaration);
+    let (name, literal) = split_word(rest);
+    let data_type = parse_data_type(data_type, line)?;
+    let value = parse_literal(literal)
+        .ok_or_else(|| line.error(format!("invalid literal '{}'", literal)))?;
+
+    if value.get_type() != data_type {
+        return Err(line.error(format!(
+            "constant {} of type {} has value of type {}",
+            name,
+            data_type,
+            value.get_type()
+        )));
+    }
+
+    Ok(Constant::new(name.to_string(), visibility, value))
+}
+
+/// Parses `Int count`.
+fn parse_global(declaration: &str, line: &Line) -> Result<Global, CompileError> {
+    let mut parts = declaration.split_whitespace();
+    let (Some(data_type), Some(name), None) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(line.error("invalid global, expected e.g. 'global Int count'"));
+    };
+
+    Ok(Global::new(
+        name.to_string(),
+        parse_data_type(data_type, line)?,
+    ))
+}
+
+fn parse_data_type(text: &str, line: &Line) -> Result<DataType, CompileError> {
+    match text {
+        "Void" => Ok(DataType::Void),
+        "Int" => Ok(DataType::Int),
+        "Int64" => Ok(DataType::Int64),
+        "Byte" => Ok(DataType::Byte),
+        "Float" => Ok(DataType::Float),
+        "Bool" => Ok(DataType::Bool),
+        "String" => Ok(DataType::String),
+        _ => Err(line.error(format!("unknown type '{}'", text))),
+    }
+}
+
+fn is_label(text: &str) -> bool {
+    text.strip_suffix(':').is_some_and(|name| {
+        !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+fn assemble_body(
+    lines: &[Line],
+    function_map: &HashMap<String, u32>,
+    dependencies: &HashMap<String, Module>,
+) -> Result<Vec<Instruction>, CompileError> {
+    let mut labels = HashMap::new();
+    let mut index = 0;
+    for line in lines {
+        if !is_label(line.text) {
+            index += 1;
+            continue;
+        }
+
+        let name = &line.text[..line.text.len() - 1];
+        if labels.insert(name, index).is_some() {
+            return Err(line.error(format!("label '{}' is already defined", name)));
+        }
+    }
+
+    let mut body = vec![];
+    for line in lines.iter().filter(|line| !is_label(line.text)) {
+        let (first, rest) = split_word(line.text);
+        let (mnemonic, operand) = match first.parse::<usize>() {
+            Ok(number) if number != body.len() => {
+                return Err(line.error(format!(
+                    "instruction number {} does not match its position {}",
+                    number,
+                    body.len()
+                )));
+            }
+            Ok(_) => split_word(rest),
+            Err(_) => (first, rest),
+        };
+
+        let instruction =
+            parse_instruction(mnemonic, operand, line, &labels, function_map, dependencies)?;
+        body.push(instruction);
+    }
+
+    Ok(body)
+}
+
+fn split_word(text: &str) -> (&str, &str) {
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (text, ""),
+    }
+}
+
+fn parse_instruction(
+    mnemonic: &str,
+    operand: &str,
+    line: &Line,
+    labels: &HashMap<&str, u32>,
+    function_map: &HashMap<String, u32>,
+    dependencies: &HashMap<String, Module>,
+) -> Result<Instruction, CompileError> {
+    let simple = match mnemonic {
+        "pop" => Some(Instruction::Pop),
+        "add" => Some(Instruction::Add),
+        "sub" => Some(Instruction::Sub),
+        "mul" => Some(Instruction::Mul),
+        "div" => Some(Instruction::Div),
+        "modulo" => Some(Instruction::Modulo),
+        "equal" => Some(Instruction::Equal),
+        "not_equal" => Some(Instruction::NotEqual),
+        "less" => Some(Instruction::Less),
+        "less_equal" => Some(Instruction::LessEqual),
+        "greater" => Some(Instruction::Greater),
+        "greater_equal" => Some(Instruction::GreaterEqual),
+        "and" => Some(Instruction::And),
+        "or" => Some(Instruction::Or),
+        "not" => Some(Instruction::Not),
+        "negate" => Some(Instruction::Negate),
+        "bit_and" => Some(Instruction::BitAnd),
+        "bit_or" => Some(Instruction::BitOr),
+        "bit_xor" => Some(Instruction::BitXor),
+        "bit_not" => Some(Instruction::BitNot),
+        "shift_left" => Some(Instruction::ShiftLeft),
+        "shift_right" => Some(Instruction::ShiftRight),
+        "return" => Some(Instruction::Return),
+        _ => None,
+    };
+
+    if let Some(instruction) = simple {
+        if !operand.is_empty() {
+            return Err(line.error(format!("'{}' does not take an operand", mnemonic)));
+        }
+        return Ok(instruction);
+    }
+
+    let number = |text: &str| {
+        text.parse::<u32>()
+            .map_err(|_| line.error(format!("expected a number, found '{}'", text)))
+    };
+
+    // name and argument count of builtin and method calls
+    let call = || {
+        let (name, count) = split_word(operand);
+        Ok::<_, CompileError>((name, number(count)?))
+    };
+
+    let target = || match labels.get(operand) {
+        Some(index) => Ok(*index),
+        None => number(operand).map_err(|_| line.error(format!("unknown label '{}'", operand))),
+    };
+
+    let instruction = match mnemonic {
+        "store" => Instruction::Store {
+            slot: number(operand)?,
+        },
+        "load" => Instruction::Load {
+            slot: number(operand)?,
+        },
+        "store_global" => Instruction::StoreGlobal {
+            slot: number(operand)?,
+        },
+        "load_global" => Instruction::LoadGlobal {
+            slot: number(operand)?,
+        },
+        "push" => Instruction::Push(
+            parse_literal(operand)
+                .ok_or_else(|| line.error(format!("invalid literal '{}'", operand)))?,
+        ),
+        "cast" => Instruction::Cast(parse_data_type(operand, line)?),
+        "jump" => Instruction::Jump(target()?),
+        "jump_if_false" => Instruction::JumpIfFalse(target()?),
+        "call" => {
+            let call_slot = match function_map.get(operand) {
+                Some(slot) => *slot,
+                None => number(operand)
+                    .map_err(|_| line.error(format!("unknown function '{}'", operand)))?,
+            };
+            Instruction::Call { call_slot }
+        }
+        "foreign_call" => {
+            let (module_name, function) = operand
+                .split_once(':')
+                .ok_or_else(|| line.error("expected 'module:function'"))?;
+            let call_slot = match function.parse::<u32>() {
+                Ok(slot) => slot,
+                Err(_) => dependencies
+                    .get(module_name)
+                    .and_then(|module| module.get_slot(function))
+                    .ok_or_else(|| {
+                        line.error(format!("unknown function '{}:{}'", module_name, function))
+                    })?,
+            };
+            Instruction::ForeignCall {
+                module_name: module_name.to_string(),
+                call_slot,
+            }
+        }
+        "builtin" => {
+            let (name, arg_count) = call()?;
+            let function: BuiltinFunction = name
+                .parse()
+                .map_err(|_| line.error(format!("unknown builtin function '{}'", name)))?;
+            Instruction::BuiltinFunctionCall {
+                function,
+                arg_count,
+            }
+        }
+        "method" => {
+            let (name, arg_count) = call()?;
+            Instruction::MethodCall {
+                method_name: name.to_string(),
+                arg_count,
+            }
+        }
+        _ => return Err(line.error(format!("unknown instruction '{}'", mnemonic))),
+    };
+
+    Ok(instruction)
+}
+
+/// Parses literal in the format of the disassembler, floats always contain a dot.
+fn parse_literal(text: &str) -> Option<Value> {
+    match text {
+        "void" => return Some(Value::Void),
+        "true" => return Some(Value::Bool(true)),
+        "false" => return Some(Value::Bool(false)),
+        _ => (),
+    }
+
+    if let Some(quoted) = text.strip_prefix('\'') {
+        return parse_string(quoted).map(Value::String);
+    }
+
+    if let Some(int) = text.strip_suffix("i64") {
+        return int.parse().ok().map(Value::Int64);
+    }
+
+    if let Some(byte) = text.strip_suffix("u8") {
+        return byte.parse().ok().map(Value::Byte);
+    }
+
+    if !text.contains('.') {
+        return text.parse().ok().map(Value::Int);
+    }
+
+    // `NaN` and `inf` are printed with `.0` suffix as well
+    text.parse()
+        .ok()
+        .or_else(|| text.strip_suffix(".0")?.parse().ok())
+        .map(Value::Float)
+}
+
+/// Unescapes string literal without the opening quote, the closing quote must end the text.
+fn parse_string(quoted: &str) -> Option<String> {
+    let mut string = String::new();
+    let mut chars = quoted.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next()? {
+                'n' => string.push('\n'),
+                't' => string.push('\t'),
+                other => string.push(other),
+            },
+            '\'' => return chars.as_str().is_empty().then_some(string),
+            ch => string.push(ch),
+        }
+    }
+
+    None
+}
+
+/// Slots needed for parameters and all used local variables.
+fn required_slot_count(signiture: &FunctionSigniture, body: &[Instruction]) -> usize {
+    body.iter()
+        .filter_map(|instruction| match instruction {
+            Instruction::Load { slot } | Instruction::Store { slot } => Some(*slot as usize + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+        .max(signiture.params.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{bytecode::BytecodeSerializable, disassembler::disassemble};
+
+    #[test]
+    fn round_trip_examples() {
+        for entry in std::fs::read_dir("examples").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|e| e != "mv") {
+                continue;
+            }
+
+            let source = std::fs::read_to_string(&path).unwrap();
+            let module = Module::from_string(&source, 0).unwrap();
+            let listing = disassemble(&module, &HashMap::new());
+
+            let assembled = assemble(&listing, 0, &HashMap::new()).unwrap();
+            assert_eq!(disassemble(&assembled, &HashMap::new()), listing);
+            assert_eq!(
+                assembled.to_bytecode(),
+                module.to_bytecode(),
+                "{}",
+                path.display()
+            );
+
+            let loaded = Module::from_bytecode(&module.to_bytecode(), &mut 0).unwrap();
+            assert_eq!(loaded.to_bytecode(), module.to_bytecode());
+        }
+    }
+
+    #[test]
+    fn assemble_labels_and_names() {
+        let source = "
+# counts down from the argument
+function Int countdown(Int n)
+    store 0
+loop:
+    load 0
+    push 0
+    greater
+    jump_if_false end
+    load 0
+    push 1
+    sub
+    store 0
+    jump loop
+end:
+    load 0
+    return
+
+function Void main()
+    push 3
+    call countdown
+    builtin print 1
+    push 'a # b\\'c'
+    builtin print 1
+    push void
+    return
+";
+        let module = assemble(source, 0, &HashMap::new()).unwrap();
+        assert_eq!(module.get_main_slot(), Some(1));
+
+        let countdown = module.get_function_source_by_slot(0).unwrap();
+        assert_eq!(countdown.slot_count, 1);
+        assert_eq!(countdown.body[4], Instruction::JumpIfFalse(10));
+        assert_eq!(countdown.body[9], Instruction::Jump(1));
+
+        let main = module.get_function_source_by_slot(1).unwrap();
+        assert_eq!(main.body[1], Instruction::Call { call_slot: 0 });
+        assert_eq!(
+            main.body[3],
+            Instruction::Push(Value::String("a # b'c".to_string()))
+        );
+
+        assert!(module.execute_without_dependencies().is_ok());
+    }
+
+    #[test]
+    fn assemble_constants_and_globals() {
+        let source = "
+const pub Int MAX 10
+global Int count
+
+function Int main()
+    load_global 0
+    return
+
+init slots 0
+    push 3
+    store_global 0
+    push void
+    return
+";
+        let module = assemble(source, 0, &HashMap::new()).unwrap();
+        let max = module.get_constant("MAX").unwrap();
+        assert_eq!(max.value, Value::Int(10));
+        assert!(max.visibility.is_public());
+        assert_eq!(module.get_globals().len(), 1);
+        assert_eq!(module.get_init_source().unwrap().body.len(), 4);
+
+        assert_eq!(
+            module.execute_without_dependencies().unwrap(),
+            Value::Int(3)
+        );
+
+        let error = |source: &str| assemble(source, 0, &HashMap::new()).unwrap_err();
+        assert!(error("const Int MAX 'ten'\n").message.contains("type"));
+        assert!(error("global count\n").message.contains("invalid global"));
+        assert!(error("init\ninit\n").message.contains("already defined"));
+    }
+
+    #[test]
+    fn assemble_errors() {
+        let error = |source: &str| assemble(source, 0, &HashMap::new()).unwrap_err();
+
+        let e = error("push 1\n");
+        assert!(matches!(
+            e.kind,
+            crate::errors::CompileErrorKind::InvalidAssembly
+        ));
+
+        let e = error("function Void main()\n    jump nowhere\n");
+        assert_eq!(e.span.map(|s| (s.lo, s.hi)), Some((25, 37)));
+
+        assert!(error("function Void main()\n    1  return\n")
+            .message
+            .contains("position 0"));
+        assert!(error("function Void main()\n    call missing\n")
+            .message
+            .contains("missing"));
+        assert!(error("function Void main()\n    foreign_call math:add\n")
+            .message
+            .contains("math:add"));
+        assert!(error("function Int f(Number a)\n    return\n")
+            .message
+            .contains("Number"));
+    }
+}
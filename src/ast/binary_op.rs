@@ -5,6 +5,11 @@ pub enum BinaryOp {
     Mul,
     Div,
     Modulo,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
     Equal,
     NotEqual,
     Less,
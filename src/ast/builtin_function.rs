@@ -1,12 +1,20 @@
 use std::{collections::HashSet, str::FromStr};
 
-use crate::{errors::RuntimeError, value::Value};
+use crate::{
+    data_type::DataType,
+    diagnostics::Span,
+    errors::{CompileError, RuntimeError},
+    value::Value,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum BuiltinFunction {
     Print,
     Assert,
+    AssertEq,
     Format,
+    ArgCount,
+    Arg,
 }
 
 impl BuiltinFunction {
@@ -14,20 +22,110 @@ impl BuiltinFunction {
         match self {
             BuiltinFunction::Print => "print",
             BuiltinFunction::Assert => "assert",
+            BuiltinFunction::AssertEq => "assert_eq",
             BuiltinFunction::Format => "format",
+            BuiltinFunction::ArgCount => "arg_count",
+            BuiltinFunction::Arg => "arg",
         }
     }
 
     pub fn str_variants() -> HashSet<&'static str> {
         let mut set = HashSet::new();
-        for variant in ["print", "assert", "format"] {
+        for variant in ["print", "assert", "assert_eq", "format", "arg_count", "arg"] {
             set.insert(variant);
         }
 
         set
     }
 
-    pub fn execute(&self, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    /// Checks types of the arguments and returns the type of the result.
+    pub fn check_argument_types(
+        &self,
+        args: &[(DataType, Span)],
+        call_span: Span,
+    ) -> Result<DataType, CompileError> {
+        match self {
+            BuiltinFunction::Print => {
+                // Print can take any type of arguments
+                Ok(DataType::Void)
+            }
+            BuiltinFunction::Assert => {
+                // Assert arguments must be bool
+                for (arg_type, arg_span) in args {
+                    if *arg_type != DataType::Bool {
+                        return Err(CompileError::wrong_type_at(
+                            DataType::Bool,
+                            *arg_type,
+                            *arg_span,
+                        ));
+                    }
+                }
+
+                Ok(DataType::Void)
+            }
+            BuiltinFunction::AssertEq => {
+                // first argument is the actual value, second the expected one
+                let [(actual, _), (expected, expected_span)] = args else {
+                    return Err(CompileError::wrong_number_of_arguments_at(
+                        2,
+                        args.len(),
+                        call_span,
+                    ));
+                };
+
+                if actual != expected {
+                    return Err(CompileError::wrong_type_at(
+                        *actual,
+                        *expected,
+                        *expected_span,
+                    ));
+                }
+
+                Ok(DataType::Void)
+            }
+            BuiltinFunction::Format => {
+                // Format can take any type of arguments
+                Ok(DataType::String)
+            }
+            BuiltinFunction::ArgCount => {
+                if !args.is_empty() {
+                    return Err(CompileError::wrong_number_of_arguments_at(
+                        0,
+                        args.len(),
+                        call_span,
+                    ));
+                }
+
+                Ok(DataType::Int)
+            }
+            BuiltinFunction::Arg => {
+                let [(index, index_span)] = args else {
+                    return Err(CompileError::wrong_number_of_arguments_at(
+                        1,
+                        args.len(),
+                        call_span,
+                    ));
+                };
+
+                if *index != DataType::Int {
+                    return Err(CompileError::wrong_type_at(
+                        DataType::Int,
+                        *index,
+                        *index_span,
+                    ));
+                }
+
+                Ok(DataType::String)
+            }
+        }
+    }
+
+    /// `program_args` are command line arguments passed to the program.
+    pub fn execute(
+        &self,
+        args: Vec<Value>,
+        program_args: &[String],
+    ) -> Result<Value, RuntimeError> {
         match self {
             BuiltinFunction::Print => {
                 for arg in args {
@@ -45,6 +143,20 @@ impl BuiltinFunction {
 
                 Ok(Value::Void)
             }
+            BuiltinFunction::AssertEq => {
+                let [actual, expected] = args.as_slice() else {
+                    return Err(RuntimeError::TypeError);
+                };
+
+                if actual != expected {
+                    return Err(RuntimeError::AssertionNotEqual {
+                        expected: expected.clone(),
+                        actual: actual.clone(),
+                    });
+                }
+
+                Ok(Value::Void)
+            }
             BuiltinFunction::Format => {
                 let mut result = String::new();
                 for arg in args {
@@ -52,6 +164,24 @@ impl BuiltinFunction {
                 }
                 Ok(Value::String(result))
             }
+            BuiltinFunction::ArgCount => Ok(Value::Int(program_args.len() as i32)),
+            BuiltinFunction::Arg => {
+                let [Value::Int(index)] = args.as_slice() else {
+                    return Err(RuntimeError::TypeError);
+                };
+
+                usize::try_from(*index)
+                    .ok()
+                    .and_then(|index| program_args.get(index))
+                    .map(|arg| Value::String(arg.clone()))
+                    .ok_or_else(|| {
+                        RuntimeError::Other(format!(
+                            "Argument index {} out of range, program has {} arguments",
+                            index,
+                            program_args.len()
+                        ))
+                    })
+            }
         }
     }
 }
@@ -63,7 +193,10 @@ impl FromStr for BuiltinFunction {
         match s {
             "print" => Ok(BuiltinFunction::Print),
             "assert" => Ok(BuiltinFunction::Assert),
+            "assert_eq" => Ok(BuiltinFunction::AssertEq),
             "format" => Ok(BuiltinFunction::Format),
+            "arg_count" => Ok(BuiltinFunction::ArgCount),
+            "arg" => Ok(BuiltinFunction::Arg),
             _ => Err("Not a builtin function".to_string()),
         }
     }
@@ -1,5 +1,5 @@
-use super::{binary_op::BinaryOp, UnaryOp};
-use crate::{ast::BuiltinFunction, diagnostics::Span, value::Value};
+use super::{binary_op::BinaryOp, Statement, UnaryOp};
+use crate::{ast::BuiltinFunction, data_type::DataType, diagnostics::Span, value::Value};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Expr {
@@ -40,6 +40,37 @@ impl Expr {
         }
     }
 
+    pub fn cast(expr: Self, data_type: DataType, span: Span) -> Self {
+        Self {
+            kind: ExprKind::Cast {
+                expr: Box::new(expr),
+                data_type,
+            },
+            span,
+        }
+    }
+
+    pub fn if_expr(condition: Self, if_block: Self, else_block: Self, span: Span) -> Self {
+        Self {
+            kind: ExprKind::If {
+                condition: Box::new(condition),
+                if_block: Box::new(if_block),
+                else_block: Box::new(else_block),
+            },
+            span,
+        }
+    }
+
+    pub fn block(statements: Vec<Statement>, result: Self, span: Span) -> Self {
+        Self {
+            kind: ExprKind::Block {
+                statements,
+                result: Box::new(result),
+            },
+            span,
+        }
+    }
+
     pub fn function_call(func_name: String, args: Vec<Self>, span: Span) -> Self {
         Self {
             kind: ExprKind::FunctionCall { func_name, args },
@@ -63,6 +94,13 @@ impl Expr {
         }
     }
 
+    pub fn foreign_constant(module_name: String, name: String, span: Span) -> Self {
+        Self {
+            kind: ExprKind::ForeignConstant { module_name, name },
+            span,
+        }
+    }
+
     pub fn builtin_function_call(function: BuiltinFunction, args: Vec<Self>, span: Span) -> Self {
         Self {
             kind: ExprKind::BuiltinFunctionCall { function, args },
@@ -88,6 +126,22 @@ pub enum ExprKind {
     Var(String),
     BinaryOp(BinaryOp, Box<Expr>, Box<Expr>),
     UnaryOp(UnaryOp, Box<Expr>),
+    /// Conversion of the value to another type, `x as Float`
+    Cast {
+        expr: Box<Expr>,
+        data_type: DataType,
+    },
+    /// `if a { 1 } else { 2 }`, the blocks are `Block` expressions, `else if` is an `If`
+    If {
+        condition: Box<Expr>,
+        if_block: Box<Expr>,
+        else_block: Box<Expr>,
+    },
+    /// Statements followed by the expression giving the value, `{ let x = 2\n x * x }`
+    Block {
+        statements: Vec<Statement>,
+        result: Box<Expr>,
+    },
     FunctionCall {
         func_name: String,
         args: Vec<Expr>,
@@ -97,6 +151,11 @@ pub enum ExprKind {
         func_name: String,
         args: Vec<Expr>,
     },
+    /// Public constant of another module, `module:NAME`
+    ForeignConstant {
+        module_name: String,
+        name: String,
+    },
     BuiltinFunctionCall {
         function: BuiltinFunction,
         args: Vec<Expr>,
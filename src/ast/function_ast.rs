@@ -1,7 +1,8 @@
-use crate::{data_type::DataType, diagnostics::Span, errors::CompileError};
+use crate::{data_type::DataType, diagnostics::Span, errors::CompileError, function::Visibility};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct SpannedFunctionSigniture {
+    pub visibility: Visibility,
     pub return_type: DataType,
     pub params: Vec<SpannedParameter>,
     pub span: Span,
@@ -10,12 +11,18 @@ pub struct SpannedFunctionSigniture {
 impl SpannedFunctionSigniture {
     pub fn new(return_type: DataType, params: Vec<SpannedParameter>, span: Span) -> Self {
         Self {
+            visibility: Visibility::Private,
             return_type,
             params,
             span,
         }
     }
 
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
     pub fn check_argument_types(
         &self,
         args: &[(DataType, Span)],
@@ -0,0 +1,52 @@
+use super::Expr;
+use crate::{data_type::DataType, diagnostics::Span, function::Visibility};
+
+/// Module constant, `const Int MAX = 100`. The value is evaluated at compile time
+/// and inlined where the constant is used. Public constants are readable
+/// from other modules as `module:NAME`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstantDeclaration {
+    pub visibility: Visibility,
+    pub name: String,
+    pub data_type: DataType,
+    pub value: Expr,
+    pub span: Span,
+}
+
+impl ConstantDeclaration {
+    pub fn new(name: String, data_type: DataType, value: Expr, span: Span) -> Self {
+        Self {
+            visibility: Visibility::Private,
+            name,
+            data_type,
+            value,
+            span,
+        }
+    }
+
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+}
+
+/// Module variable declared outside of functions, `let count = 0` or `Int count = 0`.
+/// Globals are initialized in the declaration order before the module is first used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalDeclaration {
+    pub name: String,
+    pub data_type: Option<DataType>,
+    pub value: Expr,
+    pub span: Span,
+}
+
+impl GlobalDeclaration {
+    pub fn new(name: String, data_type: Option<DataType>, value: Expr, span: Span) -> Self {
+        Self {
+            name,
+            data_type,
+            value,
+            span,
+        }
+    }
+}
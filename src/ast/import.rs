@@ -0,0 +1,63 @@
+use std::fmt::Display;
+
+use super::Path;
+use crate::diagnostics::Span;
+
+/// Imported module. Functions of the module are called as `name:function()`, where the name
+/// is the alias (`import geometry.vector as vec`) or the last segment of the path.
+/// Selected functions (`import math:{add, mul}`) are callable without the prefix.
+/// Public imports (`pub import`) re-export the imported functions from the importing module.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub public: bool,
+    pub path: Path,
+    pub alias: Option<String>,
+    pub functions: Vec<(String, Span)>,
+    pub span: Span,
+}
+
+impl Import {
+    pub fn new(path: Path, span: Span) -> Self {
+        Self {
+            public: false,
+            path,
+            alias: None,
+            functions: vec![],
+            span,
+        }
+    }
+
+    /// Name used in calls, alias of the module or the last segment of its path.
+    pub fn name(&self) -> &str {
+        match &self.alias {
+            Some(alias) => alias,
+            None => self.path.get_tail().map(String::as_str).unwrap_or_default(),
+        }
+    }
+
+    pub fn imports_function(&self, name: &str) -> bool {
+        self.functions.iter().any(|(function, _)| function == name)
+    }
+}
+
+/// Formats the import as written in the source, without the `pub` and `import` keywords.
+impl Display for Import {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path)?;
+
+        if let Some(alias) = &self.alias {
+            write!(f, " as {}", alias)?;
+        }
+
+        if !self.functions.is_empty() {
+            let names: Vec<&str> = self
+                .functions
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect();
+            write!(f, ":{{{}}}", names.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
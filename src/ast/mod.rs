@@ -2,6 +2,8 @@ mod binary_op;
 mod builtin_function;
 mod expr;
 mod function_ast;
+mod global;
+mod import;
 mod path;
 mod statement;
 mod unary_op;
@@ -12,14 +14,18 @@ pub use binary_op::BinaryOp;
 pub use builtin_function::BuiltinFunction;
 pub use expr::{Expr, ExprKind};
 pub use function_ast::{SpannedFunctionSigniture, SpannedParameter};
+pub use global::{ConstantDeclaration, GlobalDeclaration};
+pub use import::Import;
 pub use path::Path;
 pub use statement::{Statement, StatementKind};
 pub use unary_op::UnaryOp;
 
 use crate::{
     compiler::{tokenize, Parser},
-    diagnostics::FileId,
+    diagnostics::{FileId, Span},
     errors::CompileError,
+    function::FunctionSigniture,
+    module::Module,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -27,7 +33,9 @@ pub struct Ast {
     function_map: HashMap<String, u32>,
     function_signitures: Vec<SpannedFunctionSigniture>,
     function_bodies: Vec<Vec<Statement>>,
-    imports: Vec<Path>,
+    imports: Vec<Import>,
+    constants: Vec<ConstantDeclaration>,
+    globals: Vec<GlobalDeclaration>,
 }
 
 impl Ast {
@@ -35,13 +43,17 @@ impl Ast {
         function_map: HashMap<String, u32>,
         function_signitures: Vec<SpannedFunctionSigniture>,
         function_bodies: Vec<Vec<Statement>>,
-        imports: Vec<Path>,
+        imports: Vec<Import>,
+        constants: Vec<ConstantDeclaration>,
+        globals: Vec<GlobalDeclaration>,
     ) -> Self {
         Self {
             function_map,
             function_signitures,
             function_bodies,
             imports,
+            constants,
+            globals,
         }
     }
 
@@ -58,6 +70,8 @@ impl Ast {
             function_signitures: vec![],
             function_bodies: vec![],
             imports: vec![],
+            constants: vec![],
+            globals: vec![],
         }
     }
 
@@ -73,8 +87,16 @@ impl Ast {
         self.function_signitures.push(signiture);
     }
 
-    pub fn add_import(&mut self, path: Path) {
-        self.imports.push(path);
+    pub fn add_import(&mut self, import: Import) {
+        self.imports.push(import);
+    }
+
+    pub fn add_constant(&mut self, constant: ConstantDeclaration) {
+        self.constants.push(constant);
+    }
+
+    pub fn add_global(&mut self, global: GlobalDeclaration) {
+        self.globals.push(global);
     }
 
     pub fn function_count(&self) -> u32 {
@@ -104,10 +126,88 @@ impl Ast {
         &self.function_map
     }
 
-    pub fn get_imports(&self) -> &Vec<Path> {
+    pub fn get_imports(&self) -> &Vec<Import> {
         &self.imports
     }
 
+    pub fn get_constants(&self) -> &Vec<ConstantDeclaration> {
+        &self.constants
+    }
+
+    pub fn get_constant(&self, name: &str) -> Option<&ConstantDeclaration> {
+        self.constants.iter().find(|constant| constant.name == name)
+    }
+
+    /// Globals in the declaration order, the index is the slot of the global.
+    pub fn get_globals(&self) -> &Vec<GlobalDeclaration> {
+        &self.globals
+    }
+
+    pub fn get_global_slot(&self, name: &str) -> Option<u32> {
+        self.globals
+            .iter()
+            .position(|global| global.name == name)
+            .map(|slot| slot as u32)
+    }
+
+    /// Full path of the module called as `name:function()`, modules are keyed by it.
+    /// `name` is the alias of an import, its full path (`utils.strings`) or its last segment
+    /// (`strings`), names that are not imported are returned unchanged.
+    pub fn resolve_module_name(&self, name: &str, span: Span) -> Result<String, CompileError> {
+        let exact = self.imports.iter().find(|import| match &import.alias {
+            Some(alias) => alias == name,
+            None => import.path.to_string() == name,
+        });
+        if let Some(import) = exact {
+            return Ok(import.path.to_string());
+        }
+
+        let candidates: Vec<String> = self
+            .imports
+            .iter()
+            .filter(|import| import.alias.is_none() && import.name() == name)
+            .map(|import| import.path.to_string())
+            .collect();
+
+        match candidates.as_slice() {
+            [] => Ok(name.to_string()),
+            [path] => Ok(path.clone()),
+            _ => Err(CompileError::ambiguous_module_at(name, &candidates, span)),
+        }
+    }
+
+    /// Full path of the module the function is selectively imported from,
+    /// e.g. `math` for `add` imported by `import math:{add}`.
+    pub fn get_imported_function_module(&self, name: &str) -> Option<String> {
+        self.imports
+            .iter()
+            .find(|import| import.imports_function(name))
+            .map(|import| import.path.to_string())
+    }
+
+    /// Functions re-exported by `pub import`, the selected functions of the import
+    /// or all public functions of the module. Private and missing functions are skipped.
+    pub fn get_reexported_functions<'m>(
+        &self,
+        dependencies: &'m HashMap<String, Module>,
+    ) -> Vec<(&Import, &'m str, &'m FunctionSigniture)> {
+        let mut functions = vec![];
+        for import in self.imports.iter().filter(|import| import.public) {
+            let Some(module) = dependencies.get(&import.path.to_string()) else {
+                continue;
+            };
+
+            for (name, signiture) in module.get_functions() {
+                let selected = import.functions.is_empty() || import.imports_function(name);
+                if selected && signiture.visibility.is_public() {
+                    functions.push((import, name, signiture));
+                }
+            }
+        }
+
+        functions
+    }
+
     // we need to deconstruct these
     #[allow(clippy::type_complexity)]
     pub fn deconstruct(
@@ -116,13 +216,17 @@ impl Ast {
         HashMap<String, u32>,
         Vec<SpannedFunctionSigniture>,
         Vec<Vec<Statement>>,
-        Vec<Path>,
+        Vec<Import>,
+        Vec<ConstantDeclaration>,
+        Vec<GlobalDeclaration>,
     ) {
         (
             self.function_map,
             self.function_signitures,
             self.function_bodies,
             self.imports,
+            self.constants,
+            self.globals,
         )
     }
 }
@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Path {
     segments: Vec<String>,
@@ -17,4 +19,14 @@ impl Path {
     pub fn get_tail(&self) -> Option<&String> {
         self.segments.last()
     }
+
+    pub fn get_segments(&self) -> &[String] {
+        &self.segments
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.segments.join("."))
+    }
 }
@@ -2,4 +2,5 @@
 pub enum UnaryOp {
     Not,
     Negate,
+    BitNot,
 }
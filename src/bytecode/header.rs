@@ -10,7 +10,7 @@ pub struct BytecodeHeader {
 }
 
 impl BytecodeHeader {
-    pub const CURRENT_VERSION: u8 = 1;
+    pub const CURRENT_VERSION: u8 = 3;
     const ERROR_NOT_ENOUGH_BYTES: &'static str = "Not enough bytes for header";
 
     pub fn new(main_slot: Option<u32>, function_count: u32) -> Self {
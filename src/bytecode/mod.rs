@@ -6,8 +6,11 @@ pub use serializable::BytecodeSerializable;
 mod header;
 mod serializable;
 
+/// Writes the function map sorted by slot, so the same module always gives the same bytes.
 pub fn write_fn_map_bytecode(fn_map: &HashMap<String, u32>, buffer: &mut Vec<u8>) {
-    for (name, slot) in fn_map {
+    let mut functions: Vec<_> = fn_map.iter().map(|(name, slot)| (slot, name)).collect();
+    functions.sort();
+    for (slot, name) in functions {
         name.write_bytecode(buffer);
         slot.write_bytecode(buffer);
     }
@@ -18,10 +21,13 @@ pub fn read_fn_map_bytecode(
     cursor: &mut usize,
     count: usize,
 ) -> Result<HashMap<String, u32>, String> {
-    let mut fn_map = HashMap::with_capacity(count);
+    let mut fn_map = HashMap::new();
     for _ in 0..count {
         let name = String::from_bytecode(bytes, cursor)?;
         let slot = u32::from_bytecode(bytes, cursor)?;
+        if fn_map.contains_key(&name) {
+            return Err(format!("Function '{}' is defined twice", name));
+        }
         fn_map.insert(name, slot);
     }
     Ok(fn_map)
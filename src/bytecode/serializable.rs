@@ -1,5 +1,6 @@
 mod data_type;
 mod function;
+mod global;
 mod instruction;
 mod value;
 
@@ -126,6 +127,21 @@ impl BytecodeSerializable for i32 {
     }
 }
 
+impl BytecodeSerializable for i64 {
+    fn from_bytecode(bytes: &[u8], cursor: &mut usize) -> Result<Self, String> {
+        if *cursor + 8 > bytes.len() {
+            return Err("Unexpected end of bytecode".to_string());
+        }
+        let value = i64::from_le_bytes(bytes[*cursor..*cursor + 8].try_into().unwrap());
+        *cursor += 8;
+        Ok(value)
+    }
+
+    fn write_bytecode(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
 impl BytecodeSerializable for f64 {
     fn from_bytecode(bytes: &[u8], cursor: &mut usize) -> Result<Self, String> {
         if *cursor + 8 > bytes.len() {
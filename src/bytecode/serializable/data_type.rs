@@ -8,6 +8,8 @@ impl DataTypeId {
     pub const INT: u8 = 2;
     pub const FLOAT: u8 = 3;
     pub const STRING: u8 = 4;
+    pub const INT64: u8 = 5;
+    pub const BYTE: u8 = 6;
 }
 
 fn get_id(data_type: &DataType) -> u8 {
@@ -15,6 +17,8 @@ fn get_id(data_type: &DataType) -> u8 {
         DataType::Void => DataTypeId::VOID,
         DataType::Bool => DataTypeId::BOOL,
         DataType::Int => DataTypeId::INT,
+        DataType::Int64 => DataTypeId::INT64,
+        DataType::Byte => DataTypeId::BYTE,
         DataType::Float => DataTypeId::FLOAT,
         DataType::String => DataTypeId::STRING,
     }
@@ -27,6 +31,8 @@ impl BytecodeSerializable for DataType {
             DataTypeId::VOID => Ok(DataType::Void),
             DataTypeId::BOOL => Ok(DataType::Bool),
             DataTypeId::INT => Ok(DataType::Int),
+            DataTypeId::INT64 => Ok(DataType::Int64),
+            DataTypeId::BYTE => Ok(DataType::Byte),
             DataTypeId::FLOAT => Ok(DataType::Float),
             DataTypeId::STRING => Ok(DataType::String),
             _ => Err(format!("Unknown DataType id: {}", id)),
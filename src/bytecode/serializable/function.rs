@@ -1,16 +1,35 @@
 use crate::{
     bytecode::serializable::BytecodeSerializable,
     data_type::DataType,
-    function::{FunctionSigniture, FunctionSource, Parameter},
+    function::{FunctionSigniture, FunctionSource, Parameter, Visibility},
     instruction::Instruction,
 };
 
+impl BytecodeSerializable for Visibility {
+    fn from_bytecode(bytes: &[u8], cursor: &mut usize) -> Result<Self, String> {
+        match u8::from_bytecode(bytes, cursor)? {
+            0 => Ok(Visibility::Private),
+            1 => Ok(Visibility::Public),
+            id => Err(format!("Unknown visibility id: {}", id)),
+        }
+    }
+
+    fn write_bytecode(&self, buffer: &mut Vec<u8>) {
+        let id: u8 = match self {
+            Visibility::Private => 0,
+            Visibility::Public => 1,
+        };
+        id.write_bytecode(buffer);
+    }
+}
+
 impl BytecodeSerializable for FunctionSigniture {
     fn from_bytecode(bytes: &[u8], cursor: &mut usize) -> Result<Self, String> {
+        let visibility = Visibility::from_bytecode(bytes, cursor)?;
         let return_type = DataType::from_bytecode(bytes, cursor)?;
         let param_count = usize::from_bytecode(bytes, cursor)?;
 
-        let mut params = Vec::with_capacity(param_count);
+        let mut params = Vec::new();
         for _ in 0..param_count {
             let name = String::from_bytecode(bytes, cursor)?;
             let datatype = DataType::from_bytecode(bytes, cursor)?;
@@ -18,12 +37,14 @@ impl BytecodeSerializable for FunctionSigniture {
         }
 
         Ok(FunctionSigniture {
+            visibility,
             return_type,
             params,
         })
     }
 
     fn write_bytecode(&self, buffer: &mut Vec<u8>) {
+        self.visibility.write_bytecode(buffer);
         self.return_type.write_bytecode(buffer);
         self.params.len().write_bytecode(buffer);
 
@@ -39,7 +60,7 @@ impl BytecodeSerializable for FunctionSource {
         let slot_count = usize::from_bytecode(bytes, cursor)?;
         let instr_count = usize::from_bytecode(bytes, cursor)?;
 
-        let mut body = Vec::with_capacity(instr_count);
+        let mut body = Vec::new();
         for _ in 0..instr_count {
             let instruction = Instruction::from_bytecode(bytes, cursor)?;
             body.push(instruction);
@@ -0,0 +1,37 @@
+use crate::{
+    bytecode::serializable::BytecodeSerializable,
+    data_type::DataType,
+    function::Visibility,
+    global::{Constant, Global},
+    value::Value,
+};
+
+impl BytecodeSerializable for Constant {
+    fn from_bytecode(bytes: &[u8], cursor: &mut usize) -> Result<Self, String> {
+        let name = String::from_bytecode(bytes, cursor)?;
+        let visibility = Visibility::from_bytecode(bytes, cursor)?;
+        let value = Value::from_bytecode(bytes, cursor)?;
+
+        Ok(Constant::new(name, visibility, value))
+    }
+
+    fn write_bytecode(&self, buffer: &mut Vec<u8>) {
+        self.name.write_bytecode(buffer);
+        self.visibility.write_bytecode(buffer);
+        self.value.write_bytecode(buffer);
+    }
+}
+
+impl BytecodeSerializable for Global {
+    fn from_bytecode(bytes: &[u8], cursor: &mut usize) -> Result<Self, String> {
+        let name = String::from_bytecode(bytes, cursor)?;
+        let data_type = DataType::from_bytecode(bytes, cursor)?;
+
+        Ok(Global::new(name, data_type))
+    }
+
+    fn write_bytecode(&self, buffer: &mut Vec<u8>) {
+        self.name.write_bytecode(buffer);
+        self.data_type.write_bytecode(buffer);
+    }
+}
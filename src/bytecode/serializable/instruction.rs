@@ -1,7 +1,8 @@
 use std::str::FromStr as _;
 
 use crate::{
-    ast::BuiltinFunction, bytecode::BytecodeSerializable, instruction::Instruction, value::Value,
+    ast::BuiltinFunction, bytecode::BytecodeSerializable, data_type::DataType,
+    instruction::Instruction, value::Value,
 };
 
 struct InstructionId;
@@ -33,6 +34,15 @@ impl InstructionId {
     const FOREIGNCALL: u8 = 23;
     const BUILTINCALL: u8 = 24;
     const METHODCALL: u8 = 25;
+    const LOADGLOBAL: u8 = 26;
+    const STOREGLOBAL: u8 = 27;
+    const CAST: u8 = 28;
+    const BITAND: u8 = 29;
+    const BITOR: u8 = 30;
+    const BITXOR: u8 = 31;
+    const BITNOT: u8 = 32;
+    const SHIFTLEFT: u8 = 33;
+    const SHIFTRIGHT: u8 = 34;
 }
 
 fn get_id(instruction: &Instruction) -> u8 {
@@ -53,11 +63,20 @@ fn get_id(instruction: &Instruction) -> u8 {
         Instruction::Or => InstructionId::OR,
         Instruction::Not => InstructionId::NOT,
         Instruction::Negate => InstructionId::NEGATE,
+        Instruction::BitAnd => InstructionId::BITAND,
+        Instruction::BitOr => InstructionId::BITOR,
+        Instruction::BitXor => InstructionId::BITXOR,
+        Instruction::BitNot => InstructionId::BITNOT,
+        Instruction::ShiftLeft => InstructionId::SHIFTLEFT,
+        Instruction::ShiftRight => InstructionId::SHIFTRIGHT,
+        Instruction::Cast(_) => InstructionId::CAST,
         Instruction::Jump(_) => InstructionId::JUMP,
         Instruction::JumpIfFalse(_) => InstructionId::JUMPIFFALSE,
         Instruction::Call { .. } => InstructionId::CALL,
         Instruction::Load { .. } => InstructionId::LOAD,
         Instruction::Store { .. } => InstructionId::STORE,
+        Instruction::LoadGlobal { .. } => InstructionId::LOADGLOBAL,
+        Instruction::StoreGlobal { .. } => InstructionId::STOREGLOBAL,
         Instruction::Pop => InstructionId::POP,
         Instruction::Push(_) => InstructionId::PUSH,
         Instruction::ForeignCall { .. } => InstructionId::FOREIGNCALL,
@@ -76,7 +95,10 @@ impl BytecodeSerializable for Instruction {
             Instruction::Call { call_slot } => call_slot.write_bytecode(buffer),
             Instruction::Load { slot } => slot.write_bytecode(buffer),
             Instruction::Store { slot } => slot.write_bytecode(buffer),
+            Instruction::LoadGlobal { slot } => slot.write_bytecode(buffer),
+            Instruction::StoreGlobal { slot } => slot.write_bytecode(buffer),
             Instruction::Push(value) => value.write_bytecode(buffer),
+            Instruction::Cast(data_type) => data_type.write_bytecode(buffer),
             Instruction::ForeignCall {
                 module_name,
                 call_slot,
@@ -125,6 +147,12 @@ impl BytecodeSerializable for Instruction {
             InstructionId::OR => Ok(Instruction::Or),
             InstructionId::NOT => Ok(Instruction::Not),
             InstructionId::NEGATE => Ok(Instruction::Negate),
+            InstructionId::BITAND => Ok(Instruction::BitAnd),
+            InstructionId::BITOR => Ok(Instruction::BitOr),
+            InstructionId::BITXOR => Ok(Instruction::BitXor),
+            InstructionId::BITNOT => Ok(Instruction::BitNot),
+            InstructionId::SHIFTLEFT => Ok(Instruction::ShiftLeft),
+            InstructionId::SHIFTRIGHT => Ok(Instruction::ShiftRight),
             InstructionId::POP => Ok(Instruction::Pop),
 
             InstructionId::JUMP => {
@@ -147,6 +175,18 @@ impl BytecodeSerializable for Instruction {
                 let slot = u32::from_bytecode(bytes, cursor)?;
                 Ok(Instruction::Store { slot })
             }
+            InstructionId::LOADGLOBAL => {
+                let slot = u32::from_bytecode(bytes, cursor)?;
+                Ok(Instruction::LoadGlobal { slot })
+            }
+            InstructionId::STOREGLOBAL => {
+                let slot = u32::from_bytecode(bytes, cursor)?;
+                Ok(Instruction::StoreGlobal { slot })
+            }
+            InstructionId::CAST => {
+                let data_type = DataType::from_bytecode(bytes, cursor)?;
+                Ok(Instruction::Cast(data_type))
+            }
             InstructionId::PUSH => {
                 let value = Value::from_bytecode(bytes, cursor)?;
                 Ok(Instruction::Push(value))
@@ -6,6 +6,8 @@ fn get_id(value: &Value) -> u8 {
         Value::Void => DataTypeId::VOID,
         Value::Bool(_) => DataTypeId::BOOL,
         Value::Int(_) => DataTypeId::INT,
+        Value::Int64(_) => DataTypeId::INT64,
+        Value::Byte(_) => DataTypeId::BYTE,
         Value::Float(_) => DataTypeId::FLOAT,
         Value::String(_) => DataTypeId::STRING,
     }
@@ -19,6 +21,8 @@ impl BytecodeSerializable for Value {
             Value::Void => (),
             Value::Bool(b) => b.write_bytecode(buffer),
             Value::Int(x) => x.write_bytecode(buffer),
+            Value::Int64(x) => x.write_bytecode(buffer),
+            Value::Byte(x) => x.write_bytecode(buffer),
             Value::Float(x) => x.write_bytecode(buffer),
             Value::String(s) => s.write_bytecode(buffer),
         }
@@ -37,6 +41,14 @@ impl BytecodeSerializable for Value {
                 let x = i32::from_bytecode(bytes, cursor)?;
                 Ok(Value::Int(x))
             }
+            DataTypeId::INT64 => {
+                let x = i64::from_bytecode(bytes, cursor)?;
+                Ok(Value::Int64(x))
+            }
+            DataTypeId::BYTE => {
+                let x = u8::from_bytecode(bytes, cursor)?;
+                Ok(Value::Byte(x))
+            }
             DataTypeId::FLOAT => {
                 let x = f64::from_bytecode(bytes, cursor)?;
                 Ok(Value::Float(x))
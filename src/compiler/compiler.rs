@@ -5,10 +5,15 @@ use crate::ast::{
     Ast, BinaryOp, Expr, ExprKind, SpannedFunctionSigniture, SpannedParameter, Statement,
     StatementKind, UnaryOp,
 };
+use crate::diagnostics::Span;
 use crate::errors::CompileError;
-use crate::function::FunctionSource;
+use crate::function::{FunctionSource, Visibility};
+use crate::global::Global;
 use crate::instruction::Instruction;
 
+use super::constant::{evaluate_constants, find_foreign_constant};
+use super::TypeChecker;
+
 use crate::module::Module;
 use crate::value::Value;
 
@@ -32,10 +37,21 @@ impl<'a> Compiler<'a> {
     }
 
     pub fn compile(mut self) -> Result<Module, CompileError> {
+        self.add_reexported_functions();
+
+        let constants = evaluate_constants(&self.ast, self.dependencies)?;
+        let constant_values = constants
+            .iter()
+            .map(|constant| (constant.name.clone(), constant.value.clone()))
+            .collect();
+
         for slot in 0..self.ast.function_count() {
-            self.compile_function(slot)?;
+            self.compile_function(slot, &constant_values)?;
         }
 
+        let init_source = self.compile_global_initializer(&constant_values)?;
+        let globals = self.globals()?;
+
         let (function_map, spanned_function_signitures, ..) = self.ast.deconstruct();
         let function_signitures = spanned_function_signitures
             .into_iter()
@@ -43,18 +59,115 @@ impl<'a> Compiler<'a> {
             .collect();
 
         let main_slot = function_map.get("main").copied();
-        let module = Module::new(main_slot, function_map, function_signitures, self.sources);
+        let module = Module::new(main_slot, function_map, function_signitures, self.sources)
+            .with_constants(constants)
+            .with_globals(globals, init_source);
 
         Ok(module)
     }
 
-    fn compile_function(&mut self, slot: u32) -> Result<(), CompileError> {
-        let function_map = self.ast.get_function_map();
+    /// Functions of the module with their slots and signitures but without bodies, re-exported
+    /// functions included. Constants are evaluated separately, see `module_interfaces`.
+    pub(crate) fn interface(mut self) -> Module {
+        self.add_reexported_functions();
+
+        let (function_map, spanned_function_signitures, ..) = self.ast.deconstruct();
+        let function_signitures = spanned_function_signitures
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        let main_slot = function_map.get("main").copied();
+        Module::new(main_slot, function_map, function_signitures, vec![])
+    }
+
+    /// Initializer assigns the values to the globals in the declaration order.
+    fn compile_global_initializer(
+        &self,
+        constants: &HashMap<String, Value>,
+    ) -> Result<Option<FunctionSource>, CompileError> {
+        let globals = self.ast.get_globals();
+        if globals.is_empty() {
+            return Ok(None);
+        }
+
+        let body: Vec<Statement> = globals
+            .iter()
+            .map(|global| {
+                Statement::var_assign(global.name.clone(), global.value.clone(), global.span)
+            })
+            .collect();
+
+        let signiture = SpannedFunctionSigniture::new(DataType::Void, vec![], Span::new(0, 0, 0));
+        let source =
+            FunctionCompiler::new(self.dependencies, &self.ast, constants, &body, &signiture)
+                .compile()?;
+
+        Ok(Some(source))
+    }
+
+    /// Globals declared with `let` take the type of their value.
+    fn globals(&self) -> Result<Vec<Global>, CompileError> {
+        let types = TypeChecker::new(&self.ast, self.dependencies).global_types()?;
+        let globals = self
+            .ast
+            .get_globals()
+            .iter()
+            .zip(types)
+            .map(|(global, data_type)| Global::new(global.name.clone(), data_type))
+            .collect();
+
+        Ok(globals)
+    }
+
+    /// Every function re-exported by `pub import` gets a public function
+    /// of the same name forwarding the call to the imported module.
+    fn add_reexported_functions(&mut self) {
+        let mut functions = vec![];
+        for (import, name, signiture) in self.ast.get_reexported_functions(self.dependencies) {
+            let span = import.span;
+            let params: Vec<SpannedParameter> = signiture
+                .params
+                .iter()
+                .map(|p| SpannedParameter::new(p.name.clone(), p.data_type, span))
+                .collect();
+
+            let args = params
+                .iter()
+                .map(|p| Expr::var(p.name.clone(), span))
+                .collect();
+            let call = Expr::new(
+                ExprKind::ForeignFunctionCall {
+                    module_name: import.path.to_string(),
+                    func_name: name.to_string(),
+                    args,
+                },
+                span,
+            );
+
+            let spanned_signiture =
+                SpannedFunctionSigniture::new(signiture.return_type, params, span)
+                    .with_visibility(Visibility::Public);
+            let body = vec![Statement::new(StatementKind::Return(call), span)];
+            functions.push((name.to_string(), spanned_signiture, body));
+        }
+
+        for (name, signiture, body) in functions {
+            self.ast.add_function(name, signiture, body);
+        }
+    }
+
+    fn compile_function(
+        &mut self,
+        slot: u32,
+        constants: &HashMap<String, Value>,
+    ) -> Result<(), CompileError> {
         let signiture = self.ast.get_function_signiture_by_slot(slot).unwrap();
         let body = self.ast.get_function_body_by_slot(slot).unwrap();
 
         let source =
-            FunctionCompiler::new(self.dependencies, function_map, body, signiture).compile()?;
+            FunctionCompiler::new(self.dependencies, &self.ast, constants, body, signiture)
+                .compile()?;
 
         self.sources.push(source);
 
@@ -62,9 +175,57 @@ impl<'a> Compiler<'a> {
     }
 }
 
+/// Compiles statements of interactive session into function source.
+/// `ast` holds functions and imports of the session.
+/// `locals` maps variables of the session to their slots, new variables are added to it.
+/// If the last statement is an expression, its value is returned.
+pub(crate) fn compile_session_statements(
+    ast: &Ast,
+    dependencies: &HashMap<String, Module>,
+    statements: &[Statement],
+    locals: &mut HashMap<String, usize>,
+) -> Result<FunctionSource, CompileError> {
+    let constants = evaluate_constants(ast, dependencies)?
+        .into_iter()
+        .map(|constant| (constant.name, constant.value))
+        .collect();
+
+    let signiture = SpannedFunctionSigniture::new(DataType::Void, vec![], Span::new(0, 0, 0));
+    let mut compiler = FunctionCompiler::new(dependencies, ast, &constants, statements, &signiture);
+    compiler.next_slot = locals.len();
+    compiler.locals = std::mem::take(locals);
+
+    match statements.split_last() {
+        Some((
+            Statement {
+                kind: StatementKind::Expr(expr),
+                ..
+            },
+            rest,
+        )) => {
+            compiler.compile_statements(rest)?;
+            compiler.compile_expr(expr)?;
+        }
+        _ => {
+            compiler.compile_statements(statements)?;
+            compiler.push(Instruction::Push(Value::Void));
+        }
+    }
+
+    compiler.push(Instruction::Return);
+
+    *locals = compiler.locals;
+    Ok(FunctionSource::new(
+        compiler.next_slot,
+        compiler.instructions,
+    ))
+}
+
 struct FunctionCompiler<'b> {
     dependencies: &'b HashMap<String, Module>,
-    function_map: &'b HashMap<String, u32>,
+    ast: &'b Ast,
+    /// values of the module constants, uses of constants are replaced by them
+    constants: &'b HashMap<String, Value>,
     body: &'b [Statement],
     signiture: &'b SpannedFunctionSigniture,
 
@@ -76,13 +237,15 @@ struct FunctionCompiler<'b> {
 impl<'b> FunctionCompiler<'b> {
     fn new(
         dependencies: &'b HashMap<String, Module>,
-        function_map: &'b HashMap<String, u32>,
+        ast: &'b Ast,
+        constants: &'b HashMap<String, Value>,
         body: &'b [Statement],
         signiture: &'b SpannedFunctionSigniture,
     ) -> Self {
         Self {
             dependencies,
-            function_map,
+            ast,
+            constants,
             body,
             signiture,
             locals: HashMap::new(),
@@ -126,7 +289,8 @@ impl<'b> FunctionCompiler<'b> {
     }
 
     fn setup_parameters(&mut self) {
-        for SpannedParameter { name, .. } in &self.signiture.params {
+        // arguments are pushed in order, so the last one is on top of the stack
+        for SpannedParameter { name, .. } in self.signiture.params.iter().rev() {
             let slot = self.get_slot(&name) as u32;
             self.instructions.push(Instruction::Store { slot });
         }
@@ -142,16 +306,27 @@ impl<'b> FunctionCompiler<'b> {
 
     fn compile_statement(&mut self, statement: &Statement) -> Result<(), CompileError> {
         match &statement.kind {
-            // there is no difference between declaration and assignment at this point
             StatementKind::VarDeclare {
                 variable, value, ..
-            }
-            | StatementKind::VarAssign { variable, value } => {
+            } => {
                 self.compile_expr(&value)?;
                 let slot = self.get_slot(&variable) as u32;
                 self.push(Instruction::Store { slot });
             }
 
+            StatementKind::VarAssign { variable, value } => {
+                self.compile_expr(value)?;
+                match self.ast.get_global_slot(variable) {
+                    Some(slot) if !self.locals.contains_key(variable) => {
+                        self.push(Instruction::StoreGlobal { slot })
+                    }
+                    _ => {
+                        let slot = self.get_slot(variable) as u32;
+                        self.push(Instruction::Store { slot });
+                    }
+                }
+            }
+
             StatementKind::Expr(expr) => {
                 self.compile_expr(&expr)?;
                 // We need to pop the expression from stack since we don't use it anywhere.
@@ -163,7 +338,8 @@ impl<'b> FunctionCompiler<'b> {
                 if_block,
                 else_block,
             } => {
-                self.compile_if_statement(&condition, &if_block, else_block.as_deref())?;
+                let else_branch = else_block.as_deref().map(Branch::Statements);
+                self.compile_if(condition, Branch::Statements(if_block), else_branch)?;
             }
 
             StatementKind::While { condition, block } => {
@@ -179,11 +355,11 @@ impl<'b> FunctionCompiler<'b> {
         Ok(())
     }
 
-    fn compile_if_statement(
+    fn compile_if(
         &mut self,
         cond: &Expr,
-        stmts: &[Statement],
-        else_stmts: Option<&[Statement]>,
+        if_branch: Branch,
+        else_branch: Option<Branch>,
     ) -> Result<(), CompileError> {
         // Compile the condition expression
         self.compile_expr(cond)?;
@@ -192,10 +368,10 @@ impl<'b> FunctionCompiler<'b> {
         let cond_jump_index = self.instructions.len();
         self.instructions.push(Instruction::JumpIfFalse(0)); // Placeholder instruction
 
-        // Compile the statements in the "if" block
-        self.compile_statements(stmts)?;
+        // Compile the "if" branch
+        self.compile_branch(if_branch)?;
 
-        if let Some(else_stmts) = else_stmts {
+        if let Some(else_branch) = else_branch {
             // Store the index of the jump instruction for the "else" block
             let if_jump_index = self.instructions.len();
             self.instructions.push(Instruction::Jump(0)); // Placeholder instruction
@@ -209,8 +385,8 @@ impl<'b> FunctionCompiler<'b> {
                 Instruction::JumpIfFalse(post_if_index as u32),
             );
 
-            // Compile the statements in the "else" block
-            self.compile_statements(else_stmts)?;
+            // Compile the "else" branch
+            self.compile_branch(else_branch)?;
 
             // Update the jump instruction to skip over the "else" block
             let post_else_index = self.instructions.len();
@@ -227,6 +403,13 @@ impl<'b> FunctionCompiler<'b> {
         Ok(())
     }
 
+    fn compile_branch(&mut self, branch: Branch) -> Result<(), CompileError> {
+        match branch {
+            Branch::Statements(statements) => self.compile_statements(statements),
+            Branch::Value(expr) => self.compile_expr(expr),
+        }
+    }
+
     fn compile_while_statement(
         &mut self,
         cond: &Expr,
@@ -263,8 +446,29 @@ impl<'b> FunctionCompiler<'b> {
             }
 
             ExprKind::Var(name) => {
-                let slot = self.get_slot(name) as u32;
-                self.instructions.push(Instruction::Load { slot });
+                if self.locals.contains_key(name) {
+                    let slot = self.get_slot(name) as u32;
+                    self.instructions.push(Instruction::Load { slot });
+                } else if let Some(slot) = self.ast.get_global_slot(name) {
+                    self.instructions.push(Instruction::LoadGlobal { slot });
+                } else if let Some(value) = self.constants.get(name) {
+                    self.instructions.push(Instruction::Push(value.clone()));
+                } else {
+                    let slot = self.get_slot(name) as u32;
+                    self.instructions.push(Instruction::Load { slot });
+                }
+            }
+
+            ExprKind::ForeignConstant { module_name, name } => {
+                let (_, constant) = find_foreign_constant(
+                    self.ast,
+                    self.dependencies,
+                    module_name,
+                    name,
+                    expr.span,
+                )?;
+                self.instructions
+                    .push(Instruction::Push(constant.value.clone()));
             }
 
             ExprKind::BinaryOp(op, lhs, rhs) => {
@@ -280,19 +484,44 @@ impl<'b> FunctionCompiler<'b> {
                 self.instructions.push(op_instruction);
             }
 
+            ExprKind::Cast { expr, data_type } => {
+                self.compile_expr(expr)?;
+                self.instructions.push(Instruction::Cast(*data_type));
+            }
+
+            ExprKind::If {
+                condition,
+                if_block,
+                else_block,
+            } => {
+                // both branches leave their value on the stack
+                self.compile_if(
+                    condition,
+                    Branch::Value(if_block),
+                    Some(Branch::Value(else_block)),
+                )?;
+            }
+
+            ExprKind::Block { statements, result } => {
+                self.compile_statements(statements)?;
+                self.compile_expr(result)?;
+            }
+
             ExprKind::FunctionCall { func_name, args } => {
                 for arg in args {
                     self.compile_expr(arg)?;
                 }
 
-                let Some(call_slot) = self.function_map.get(func_name).copied() else {
+                if let Some(call_slot) = self.ast.get_function_slot(func_name) {
+                    self.instructions.push(Instruction::Call { call_slot });
+                } else if let Some(module_name) = self.ast.get_imported_function_module(func_name) {
+                    self.compile_foreign_call(module_name, func_name, expr.span)?;
+                } else {
                     panic!(
                         "Function {} not found, should be handled in typechecker",
                         func_name
                     );
-                };
-
-                self.instructions.push(Instruction::Call { call_slot });
+                }
             }
 
             ExprKind::ForeignFunctionCall {
@@ -304,22 +533,8 @@ impl<'b> FunctionCompiler<'b> {
                     self.compile_expr(arg)?;
                 }
 
-                let Some(call_slot) = self
-                    .dependencies
-                    .get(module_name)
-                    .and_then(|module| module.get_slot(func_name))
-                else {
-                    return Err(CompileError::unknown_foreign_function_at(
-                        module_name,
-                        func_name,
-                        expr.span,
-                    ));
-                };
-
-                self.instructions.push(Instruction::ForeignCall {
-                    module_name: module_name.clone(),
-                    call_slot,
-                });
+                let module_name = self.ast.resolve_module_name(module_name, expr.span)?;
+                self.compile_foreign_call(module_name, func_name, expr.span)?;
             }
 
             ExprKind::BuiltinFunctionCall { function, args } => {
@@ -353,6 +568,39 @@ impl<'b> FunctionCompiler<'b> {
 
         Ok(())
     }
+
+    /// Arguments of the call must be already on the stack.
+    fn compile_foreign_call(
+        &mut self,
+        module_name: String,
+        func_name: &str,
+        span: Span,
+    ) -> Result<(), CompileError> {
+        let Some(call_slot) = self
+            .dependencies
+            .get(&module_name)
+            .and_then(|module| module.get_slot(func_name))
+        else {
+            return Err(CompileError::unknown_foreign_function_at(
+                module_name,
+                func_name,
+                span,
+            ));
+        };
+
+        self.instructions.push(Instruction::ForeignCall {
+            module_name,
+            call_slot,
+        });
+
+        Ok(())
+    }
+}
+
+/// Branch of `if`, statements of an if statement or the value of an if expression.
+enum Branch<'e> {
+    Statements(&'e [Statement]),
+    Value(&'e Expr),
 }
 
 fn bin_op_to_instruction(op: &BinaryOp) -> Instruction {
@@ -362,6 +610,11 @@ fn bin_op_to_instruction(op: &BinaryOp) -> Instruction {
         BinaryOp::Mul => Instruction::Mul,
         BinaryOp::Div => Instruction::Div,
         BinaryOp::Modulo => Instruction::Modulo,
+        BinaryOp::BitAnd => Instruction::BitAnd,
+        BinaryOp::BitOr => Instruction::BitOr,
+        BinaryOp::BitXor => Instruction::BitXor,
+        BinaryOp::ShiftLeft => Instruction::ShiftLeft,
+        BinaryOp::ShiftRight => Instruction::ShiftRight,
         BinaryOp::Equal => Instruction::Equal,
         BinaryOp::NotEqual => Instruction::NotEqual,
         BinaryOp::Less => Instruction::Less,
@@ -377,5 +630,6 @@ fn un_op_to_instruction(op: &UnaryOp) -> Instruction {
     match op {
         UnaryOp::Negate => Instruction::Negate,
         UnaryOp::Not => Instruction::Not,
+        UnaryOp::BitNot => Instruction::BitNot,
     }
 }
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{Ast, BinaryOp, ConstantDeclaration, Expr, ExprKind, UnaryOp},
+    diagnostics::Span,
+    errors::{CompileError, RuntimeError},
+    global::Constant,
+    module::Module,
+    value::Value,
+};
+
+/// Evaluates constants of the module in the declaration order. Constants can use literals,
+/// operators, constants declared before them and public constants of other modules.
+/// Types of the values must be already checked by the type checker.
+pub(crate) fn evaluate_constants(
+    ast: &Ast,
+    dependencies: &HashMap<String, Module>,
+) -> Result<Vec<Constant>, CompileError> {
+    let mut constants: Vec<Constant> = vec![];
+    for declaration in ast.get_constants() {
+        let constant = evaluate_constant(ast, dependencies, &constants, declaration)?;
+        constants.push(constant);
+    }
+
+    Ok(constants)
+}
+
+/// Evaluates the declaration, `constants` are the constants of the module declared before it.
+pub(crate) fn evaluate_constant(
+    ast: &Ast,
+    dependencies: &HashMap<String, Module>,
+    constants: &[Constant],
+    declaration: &ConstantDeclaration,
+) -> Result<Constant, CompileError> {
+    let evaluator = ConstantEvaluator {
+        ast,
+        dependencies,
+        constants,
+        name: &declaration.name,
+    };
+
+    let value = evaluator.evaluate(&declaration.value)?;
+    Ok(Constant::new(
+        declaration.name.clone(),
+        declaration.visibility,
+        value,
+    ))
+}
+
+/// Constants the expression refers to as `(module name, constant name)`,
+/// constants of the same module have no module name.
+pub(crate) fn referenced_constants(expr: &Expr) -> Vec<(Option<&str>, &str)> {
+    match &expr.kind {
+        ExprKind::Var(name) => vec![(None, name)],
+        ExprKind::ForeignConstant { module_name, name } => vec![(Some(module_name), name)],
+        ExprKind::BinaryOp(_, lhs, rhs) => {
+            let mut constants = referenced_constants(lhs);
+            constants.extend(referenced_constants(rhs));
+            constants
+        }
+        ExprKind::UnaryOp(_, operand) | ExprKind::Cast { expr: operand, .. } => {
+            referenced_constants(operand)
+        }
+        ExprKind::If {
+            condition,
+            if_block,
+            else_block,
+        } => {
+            let mut constants = referenced_constants(condition);
+            constants.extend(referenced_constants(if_block));
+            constants.extend(referenced_constants(else_block));
+            constants
+        }
+        ExprKind::Block { result, .. } => referenced_constants(result),
+        _ => vec![],
+    }
+}
+
+/// Finds public constant `name` of the module called `module_name` in the source,
+/// returns full path of the module with the constant.
+pub(crate) fn find_foreign_constant<'m>(
+    ast: &Ast,
+    dependencies: &'m HashMap<String, Module>,
+    module_name: &str,
+    name: &str,
+    span: Span,
+) -> Result<(String, &'m Constant), CompileError> {
+    let module_name = ast.resolve_module_name(module_name, span)?;
+    let Some(module) = dependencies.get(&module_name) else {
+        return Err(CompileError::module_not_found_at(module_name, span));
+    };
+
+    let Some(constant) = module.get_constant(name) else {
+        return Err(CompileError::unknown_foreign_constant_at(
+            module_name,
+            name,
+            span,
+        ));
+    };
+
+    if !constant.visibility.is_public() {
+        return Err(CompileError::private_constant_at(module_name, name, span));
+    }
+
+    Ok((module_name, constant))
+}
+
+struct ConstantEvaluator<'a> {
+    ast: &'a Ast,
+    dependencies: &'a HashMap<String, Module>,
+    /// constants declared before the evaluated one
+    constants: &'a [Constant],
+    name: &'a str,
+}
+
+impl ConstantEvaluator<'_> {
+    fn evaluate(&self, expr: &Expr) -> Result<Value, CompileError> {
+        match &expr.kind {
+            ExprKind::Literal(value) => Ok(value.clone()),
+
+            ExprKind::Var(name) => self
+                .constants
+                .iter()
+                .find(|constant| constant.name == *name)
+                .map(|constant| constant.value.clone())
+                .ok_or_else(|| {
+                    self.invalid(format!("{} is not a constant declared before", name), expr)
+                }),
+
+            ExprKind::ForeignConstant { module_name, name } => {
+                let (_, constant) = find_foreign_constant(
+                    self.ast,
+                    self.dependencies,
+                    module_name,
+                    name,
+                    expr.span,
+                )?;
+                Ok(constant.value.clone())
+            }
+
+            ExprKind::BinaryOp(op, lhs, rhs) => {
+                let lhs = self.evaluate(lhs)?;
+                let rhs = self.evaluate(rhs)?;
+                binary_op_fn(op)(&lhs, rhs).map_err(|e| self.runtime_error(e, expr))
+            }
+
+            ExprKind::UnaryOp(op, operand) => {
+                let operand = self.evaluate(operand)?;
+                let result = match op {
+                    UnaryOp::Not => operand.not(),
+                    UnaryOp::Negate => operand.negate(),
+                    UnaryOp::BitNot => operand.bit_not(),
+                };
+                result.map_err(|e| self.runtime_error(e, expr))
+            }
+
+            ExprKind::Cast {
+                expr: operand,
+                data_type,
+            } => self
+                .evaluate(operand)?
+                .cast(*data_type)
+                .map_err(|e| self.runtime_error(e, expr)),
+
+            ExprKind::If {
+                condition,
+                if_block,
+                else_block,
+            } => match self.evaluate(condition)? {
+                Value::Bool(true) => self.evaluate(if_block),
+                _ => self.evaluate(else_block),
+            },
+
+            ExprKind::Block { statements, result } => {
+                if !statements.is_empty() {
+                    return Err(self.invalid(
+                        "blocks with statements are not evaluated at compile time, use a global instead",
+                        expr,
+                    ));
+                }
+
+                self.evaluate(result)
+            }
+
+            ExprKind::FunctionCall { .. }
+            | ExprKind::ForeignFunctionCall { .. }
+            | ExprKind::BuiltinFunctionCall { .. }
+            | ExprKind::MethodCall { .. } => Err(self.invalid(
+                "calls are not evaluated at compile time, use a global instead",
+                expr,
+            )),
+        }
+    }
+
+    fn invalid(&self, reason: impl Into<String>, expr: &Expr) -> CompileError {
+        CompileError::invalid_constant_at(self.name, reason, expr.span)
+    }
+
+    fn runtime_error(&self, error: RuntimeError, expr: &Expr) -> CompileError {
+        self.invalid(error.to_string().to_lowercase(), expr)
+    }
+}
+
+fn binary_op_fn(op: &BinaryOp) -> fn(&Value, Value) -> Result<Value, RuntimeError> {
+    match op {
+        BinaryOp::Add => Value::add,
+        BinaryOp::Sub => Value::sub,
+        BinaryOp::Mul => Value::mul,
+        BinaryOp::Div => Value::div,
+        BinaryOp::Modulo => Value::modulo,
+        BinaryOp::BitAnd => Value::bit_and,
+        BinaryOp::BitOr => Value::bit_or,
+        BinaryOp::BitXor => Value::bit_xor,
+        BinaryOp::ShiftLeft => Value::shift_left,
+        BinaryOp::ShiftRight => Value::shift_right,
+        BinaryOp::Equal => Value::equal,
+        BinaryOp::NotEqual => Value::not_equal,
+        BinaryOp::Less => Value::less,
+        BinaryOp::LessEqual => Value::less_equal,
+        BinaryOp::Greater => Value::greater,
+        BinaryOp::GreaterEqual => Value::greater_equal,
+        BinaryOp::And => Value::and,
+        BinaryOp::Or => Value::or,
+    }
+}
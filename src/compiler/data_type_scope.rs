@@ -3,8 +3,9 @@ use std::collections::HashMap;
 use crate::data_type::DataType;
 use crate::{diagnostics::Span, errors::CompileError};
 
+#[derive(Debug, Clone)]
 pub struct DataTypeScope {
-    scopes: Vec<HashMap<String, DataType>>,
+    scopes: Vec<HashMap<String, (DataType, Span)>>,
 }
 
 impl DataTypeScope {
@@ -42,12 +43,17 @@ impl DataTypeScope {
         self.scopes
             .last_mut()
             .expect("There is no scope")
-            .insert(name, data_type);
+            .insert(name, (data_type, span));
 
         Ok(())
     }
 
     pub fn get(&self, key: &str) -> Option<&DataType> {
+        self.get_with_span(key).map(|(data_type, _)| data_type)
+    }
+
+    /// Type of the variable with span of its declaration.
+    pub fn get_with_span(&self, key: &str) -> Option<&(DataType, Span)> {
         for scope in self.scopes.iter().rev() {
             if let Some(value) = scope.get(key) {
                 return Some(value);
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use crate::{ast::Ast, errors::CompileError, global::Constant, module::Module};
+
+use super::{
+    constant::{evaluate_constant, referenced_constants},
+    Compiler,
+};
+
+/// Collects interfaces of modules that can import each other, see `Module::interfaces`.
+/// Functions are collected first, modules re-exporting functions of other modules of the group
+/// after them. Constants are evaluated after that in as many passes as needed, a constant
+/// waits until the constants it uses are evaluated.
+pub(crate) fn module_interfaces(
+    modules: &HashMap<String, Ast>,
+    dependencies: &HashMap<String, Module>,
+) -> Result<HashMap<String, Module>, CompileError> {
+    let mut known = dependencies.clone();
+    for name in reexport_order(modules)? {
+        let interface = Compiler::new(modules[&name].clone(), &known).interface();
+        known.insert(name, interface);
+    }
+
+    evaluate_group_constants(modules, &mut known)?;
+
+    let interfaces = modules
+        .keys()
+        .map(|name| (name.clone(), known.remove(name).unwrap()))
+        .collect();
+
+    Ok(interfaces)
+}
+
+#[derive(PartialEq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+/// Names of the modules ordered so that modules re-exported by `pub import` come before
+/// the modules re-exporting them.
+fn reexport_order(modules: &HashMap<String, Ast>) -> Result<Vec<String>, CompileError> {
+    let mut names: Vec<&String> = modules.keys().collect();
+    names.sort();
+
+    let mut order = vec![];
+    let mut visits = HashMap::new();
+    for name in names {
+        visit_reexports(name, modules, &mut visits, &mut vec![], &mut order)?;
+    }
+
+    Ok(order)
+}
+
+fn visit_reexports<'a>(
+    name: &'a str,
+    modules: &'a HashMap<String, Ast>,
+    visits: &mut HashMap<&'a str, Visit>,
+    path: &mut Vec<String>,
+    order: &mut Vec<String>,
+) -> Result<(), CompileError> {
+    if visits.get(name) == Some(&Visit::Done) {
+        return Ok(());
+    }
+
+    visits.insert(name, Visit::InProgress);
+    path.push(name.to_string());
+
+    for import in modules[name].get_imports().iter().filter(|i| i.public) {
+        let Some((imported, _)) = modules.get_key_value(&import.path.to_string()) else {
+            continue;
+        };
+
+        if visits.get(imported.as_str()) == Some(&Visit::InProgress) {
+            let start = path.iter().position(|p| p == imported).unwrap();
+            let mut cycle = path[start..].to_vec();
+            cycle.push(imported.clone());
+            return Err(CompileError::cyclic_reexport_at(&cycle, import.span));
+        }
+
+        visit_reexports(imported, modules, visits, path, order)?;
+    }
+
+    path.pop();
+    visits.insert(name, Visit::Done);
+    order.push(name.to_string());
+
+    Ok(())
+}
+
+/// Evaluates constants of all modules of the group into their interfaces in `known`.
+/// Constants left after a pass without progress wait for each other in a cycle.
+fn evaluate_group_constants(
+    modules: &HashMap<String, Ast>,
+    known: &mut HashMap<String, Module>,
+) -> Result<(), CompileError> {
+    let mut names: Vec<&String> = modules.keys().collect();
+    names.sort();
+
+    // values in the declaration order, `None` for constants that are not evaluated yet
+    let mut values: HashMap<&str, Vec<Option<Constant>>> = names
+        .iter()
+        .map(|name| {
+            (
+                name.as_str(),
+                vec![None; modules[*name].get_constants().len()],
+            )
+        })
+        .collect();
+
+    let mut progress = true;
+    while progress {
+        progress = false;
+        for name in &names {
+            let ast = &modules[*name];
+            for (index, declaration) in ast.get_constants().iter().enumerate() {
+                if values[name.as_str()][index].is_some()
+                    || waits_for_constant(ast, index, &values[name.as_str()], modules, known)
+                {
+                    continue;
+                }
+
+                let earlier: Vec<Constant> = values[name.as_str()][..index]
+                    .iter()
+                    .flatten()
+                    .cloned()
+                    .collect();
+                let constant = evaluate_constant(ast, known, &earlier, declaration)?;
+
+                known.get_mut(*name).unwrap().add_constant(constant.clone());
+                values.get_mut(name.as_str()).unwrap()[index] = Some(constant);
+                progress = true;
+            }
+        }
+    }
+
+    for name in names {
+        let ast = &modules[name];
+        let constants: Option<Vec<Constant>> = values[name.as_str()].iter().cloned().collect();
+        let Some(constants) = constants else {
+            let index = values[name.as_str()]
+                .iter()
+                .position(Option::is_none)
+                .unwrap();
+            let declaration = &ast.get_constants()[index];
+            return Err(CompileError::cyclic_constant_at(
+                name,
+                &declaration.name,
+                declaration.span,
+            ));
+        };
+
+        let interface = known.remove(name).unwrap().with_constants(constants);
+        known.insert(name.clone(), interface);
+    }
+
+    Ok(())
+}
+
+/// Whether the constant at `index` uses a constant of the group that is not evaluated yet.
+/// `evaluated` are the constants of its module. Unknown constants are reported when
+/// the constant is evaluated.
+fn waits_for_constant(
+    ast: &Ast,
+    index: usize,
+    evaluated: &[Option<Constant>],
+    modules: &HashMap<String, Ast>,
+    known: &HashMap<String, Module>,
+) -> bool {
+    let declaration = &ast.get_constants()[index];
+    referenced_constants(&declaration.value)
+        .into_iter()
+        .any(|(module_name, name)| {
+            let Some(module_name) = module_name else {
+                let earlier = ast.get_constants()[..index]
+                    .iter()
+                    .position(|constant| constant.name == name);
+                return earlier.is_some_and(|earlier| evaluated[earlier].is_none());
+            };
+
+            let Ok(module_name) = ast.resolve_module_name(module_name, declaration.span) else {
+                return false;
+            };
+
+            let declared = modules
+                .get(&module_name)
+                .is_some_and(|ast| ast.get_constant(name).is_some());
+            declared && known[&module_name].get_constant(name).is_none()
+        })
+}
@@ -1,7 +1,7 @@
 use std::iter::Peekable;
 use std::str::CharIndices;
 
-use super::token::{Token, TokenKind};
+use super::token::{Comment, Token, TokenKind};
 use crate::data_type::DataType;
 use crate::diagnostics::Span;
 use crate::errors::CompileError;
@@ -10,7 +10,16 @@ use crate::errors::CompileError;
 /// Returns Vec<Token> where each token carries a Span { file, lo, hi }.
 /// `file_id` should come from your SimpleFiles / SourceMap (codespan-reporting).
 pub fn tokenize(input: &str, file_id: usize) -> Result<Vec<Token>, CompileError> {
+    tokenize_with_comments(input, file_id).map(|(tokens, _)| tokens)
+}
+
+/// Same as `tokenize`, but also returns comments in the order of appearance.
+pub fn tokenize_with_comments(
+    input: &str,
+    file_id: usize,
+) -> Result<(Vec<Token>, Vec<Comment>), CompileError> {
     let mut tokens: Vec<Token> = vec![];
+    let mut comments: Vec<Comment> = vec![];
     let mut chars = input.char_indices().peekable();
 
     while let Some((start_idx, ch)) = chars.peek().cloned() {
@@ -22,23 +31,28 @@ pub fn tokenize(input: &str, file_id: usize) -> Result<Vec<Token>, CompileError>
         let token = match ch {
             '#' => {
                 chars.next();
-                while let Some(&(_, c)) = chars.peek() {
+                let mut end = start_idx + 1;
+                while let Some(&(i, c)) = chars.peek() {
                     if c == '\n' {
                         break;
                     }
+                    end = i + c.len_utf8();
                     chars.next();
                 }
 
-                if let Some(&(_, '\n')) = chars.peek() {
-                    chars.next();
-                }
+                comments.push(Comment {
+                    text: input[start_idx..end].trim_end().to_string(),
+                    span: Span::new(file_id, start_idx, end),
+                });
 
+                // line break after the comment still ends the statement
                 continue;
             }
 
             '\'' => tokenize_string(&mut chars, file_id, input),
 
-            '\n' => {
+            // semicolons are optional statement terminators, same as newline
+            '\n' | ';' => {
                 // EOL token; avoid duplicate sequential EOLs
                 chars.next();
                 if let Some(last) = tokens.last() {
@@ -89,24 +103,20 @@ pub fn tokenize(input: &str, file_id: usize) -> Result<Vec<Token>, CompileError>
                 false,
             ),
 
-            '<' => consume_optional_double(
+            '<' => consume_optional_doubles(
                 &mut chars,
                 file_id,
                 start_idx,
-                '=',
                 TokenKind::Less,
-                TokenKind::LessEqual,
-                false,
+                [('=', TokenKind::LessEqual), ('<', TokenKind::ShiftLeft)],
             ),
 
-            '>' => consume_optional_double(
+            '>' => consume_optional_doubles(
                 &mut chars,
                 file_id,
                 start_idx,
-                '=',
                 TokenKind::Greater,
-                TokenKind::GreaterEqual,
-                false,
+                [('=', TokenKind::GreaterEqual), ('>', TokenKind::ShiftRight)],
             ),
 
             '&' => consume_optional_double(
@@ -114,9 +124,9 @@ pub fn tokenize(input: &str, file_id: usize) -> Result<Vec<Token>, CompileError>
                 file_id,
                 start_idx,
                 '&',
+                TokenKind::Ampersand,
                 TokenKind::And,
-                TokenKind::And,
-                true,
+                false,
             ),
 
             '|' => consume_optional_double(
@@ -124,9 +134,9 @@ pub fn tokenize(input: &str, file_id: usize) -> Result<Vec<Token>, CompileError>
                 file_id,
                 start_idx,
                 '|',
+                TokenKind::Pipe,
                 TokenKind::Or,
-                TokenKind::Or,
-                true,
+                false,
             ),
 
             ch if ch.is_ascii_digit() => tokenize_number(&mut chars, file_id),
@@ -149,7 +159,7 @@ pub fn tokenize(input: &str, file_id: usize) -> Result<Vec<Token>, CompileError>
     // push EOL at EOF
     let eof = input.len();
     tokens.push(Token::new(TokenKind::EOL, Span::new(file_id, eof, eof)));
-    Ok(tokens)
+    Ok((tokens, comments))
 }
 
 fn get_single_char_token(chars: &mut Peekable<CharIndices<'_>>, file_id: usize) -> Option<Token> {
@@ -165,6 +175,8 @@ fn get_single_char_token(chars: &mut Peekable<CharIndices<'_>>, file_id: usize)
         '*' => TokenKind::Asterisk,
         '/' => TokenKind::Slash,
         '%' => TokenKind::Modulo,
+        '^' => TokenKind::Caret,
+        '~' => TokenKind::Tilde,
         '(' => TokenKind::ParenL,
         ')' => TokenKind::ParenR,
         '[' => TokenKind::BracketL,
@@ -220,6 +232,33 @@ fn consume_optional_double(
     }
 }
 
+/// Like `consume_optional_double` with several possible second chars, e.g. `<`, `<=` and `<<`.
+/// All chars are ASCII.
+fn consume_optional_doubles<const N: usize>(
+    chars: &mut Peekable<CharIndices<'_>>,
+    file_id: usize,
+    start_idx: usize,
+    single_tok: TokenKind,
+    doubles: [(char, TokenKind); N],
+) -> Result<Token, CompileError> {
+    chars.next();
+    let double = chars
+        .peek()
+        .and_then(|&(_, second)| doubles.into_iter().find(|(ch, _)| *ch == second));
+
+    match double {
+        Some((_, double_tok)) => {
+            chars.next();
+            let span = Span::new(file_id, start_idx, start_idx + 2);
+            Ok(Token::new(double_tok, span))
+        }
+        None => {
+            let span = Span::new(file_id, start_idx, start_idx + 1);
+            Ok(Token::new(single_tok, span))
+        }
+    }
+}
+
 /// On EOF without closing quote we return a token spanning until EOF (to preserve behaviour).
 fn tokenize_string(
     chars: &mut Peekable<CharIndices<'_>>,
@@ -300,7 +339,11 @@ fn tokenize_number(
         }
     }
 
-    if let Some(&(_, '.')) = chars.peek() {
+    // a dot not followed by a digit starts a method call, e.g. `2.pow(3)`
+    let is_fraction = matches!(chars.peek(), Some(&(_, '.')))
+        && matches!(chars.clone().nth(1), Some((_, c)) if c.is_ascii_digit());
+
+    if is_fraction {
         num.push('.');
         if let Some(&(dot_idx, dot_ch)) = chars.peek() {
             span.hi = dot_idx + dot_ch.len_utf8();
@@ -322,13 +365,58 @@ fn tokenize_number(
             Err(_) => Err(CompileError::unexpected_char_at('.', span)),
         }
     } else {
-        match num.parse::<i32>() {
-            Ok(i) => Ok(Token::new(TokenKind::Int(i), span)),
-            Err(_) => Err(CompileError::unexpected_char_at('0', span)),
+        let suffix = integer_suffix(chars);
+        if let Some(suffix) = suffix {
+            span.hi += suffix.len();
+        }
+
+        let out_of_range = |data_type: &str| {
+            CompileError::other_at(
+                format!(
+                    "integer literal {} is out of the range of {}",
+                    num, data_type
+                ),
+                span,
+            )
+        };
+        match suffix {
+            None => num
+                .parse::<i32>()
+                .map(|i| Token::new(TokenKind::Int(i), span))
+                .map_err(|_| out_of_range("Int")),
+            Some("i64") => num
+                .parse::<i64>()
+                .map(|i| Token::new(TokenKind::Int64(i), span))
+                .map_err(|_| out_of_range("Int64")),
+            Some(_) => num
+                .parse::<u8>()
+                .map(|b| Token::new(TokenKind::Byte(b), span))
+                .map_err(|_| out_of_range("Byte")),
         }
     }
 }
 
+/// Consumes the type suffix of an integer literal, `i64` for Int64 and `u8` for Byte.
+fn integer_suffix(chars: &mut Peekable<CharIndices<'_>>) -> Option<&'static str> {
+    for suffix in ["i64", "u8"] {
+        let mut lookahead = chars.clone();
+        let matches = suffix
+            .chars()
+            .all(|expected| matches!(lookahead.next(), Some((_, c)) if c == expected));
+        let ends =
+            !matches!(lookahead.peek(), Some(&(_, c)) if c.is_ascii_alphanumeric() || c == '_');
+
+        if matches && ends {
+            for _ in 0..suffix.len() {
+                chars.next();
+            }
+            return Some(suffix);
+        }
+    }
+
+    None
+}
+
 fn tokenize_ident(
     chars: &mut Peekable<CharIndices<'_>>,
     file_id: usize,
@@ -354,6 +442,8 @@ fn tokenize_ident(
 fn match_kw(ident: String) -> TokenKind {
     match ident.as_str() {
         "Int" => TokenKind::DataType(DataType::Int),
+        "Int64" => TokenKind::DataType(DataType::Int64),
+        "Byte" => TokenKind::DataType(DataType::Byte),
         "Float" => TokenKind::DataType(DataType::Float),
         "Bool" => TokenKind::DataType(DataType::Bool),
         "String" => TokenKind::DataType(DataType::String),
@@ -369,6 +459,9 @@ fn match_kw(ident: String) -> TokenKind {
         "return" => TokenKind::Return,
         // "external" => Token::External,
         "import" => TokenKind::Import,
+        "as" => TokenKind::As,
+        "pub" => TokenKind::Pub,
+        "const" => TokenKind::Const,
 
         _ => TokenKind::Ident(ident),
     }
@@ -407,6 +500,41 @@ mod test {
         assert_eq!(got_kinds, expected_tokens);
     }
 
+    #[test]
+    fn test_tokenize_semicolons() {
+        let input = "let x = 1; x = 2;;\n;print(x)";
+        let kinds: Vec<TokenKind> = tokenize(input, 0)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.kind)
+            .collect();
+        // repeated separators give a single EOL, the input ends with EOL too
+        let eol_indexes: Vec<usize> = (0..kinds.len())
+            .filter(|i| kinds[*i] == TokenKind::EOL)
+            .collect();
+        assert_eq!(eol_indexes, vec![4, 8, 13]);
+    }
+
+    #[test]
+    fn test_tokenize_shebang() {
+        let input = "#!/usr/bin/env -S mluva run\nlet x = 1";
+        let tokens = tokenize(input, 0).unwrap();
+        let got_kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(got_kinds[0], TokenKind::EOL);
+        assert_eq!(got_kinds[1], TokenKind::Let);
+    }
+
+    #[test]
+    fn test_tokenize_comments() {
+        let input = "# first\nlet x = 1 # second  \n#\n";
+        let (tokens, comments) = tokenize_with_comments(input, 0).unwrap();
+
+        let texts: Vec<&str> = comments.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, vec!["# first", "# second", "#"]);
+        assert_eq!(comments[1].span, Span::new(0, 18, 28));
+        assert_eq!(tokens, tokenize(input, 0).unwrap());
+    }
+
     #[test]
     fn test_tokenize_string() {
         let input = "'Hello, World!'";
@@ -434,5 +562,57 @@ mod test {
             TokenKind::Float(v) => assert!((v - 123.45).abs() < 1e-9),
             _ => panic!("expected float"),
         }
+
+        let input_method = "2.pow(3)";
+        let mut chars_method = input_method.char_indices().peekable();
+        let token_method = tokenize_number(&mut chars_method, 0).unwrap();
+        assert_eq!(token_method.kind, TokenKind::Int(2));
+        assert_eq!(chars_method.next(), Some((1, '.')));
+    }
+
+    #[test]
+    fn test_tokenize_bitwise_operators() {
+        let kinds: Vec<_> = tokenize("a & b && c | d || e ^ ~f << 1 >> 2 <= 3", 0)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .filter(|kind| !matches!(kind, TokenKind::Ident(_) | TokenKind::Int(_)))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Ampersand,
+                TokenKind::And,
+                TokenKind::Pipe,
+                TokenKind::Or,
+                TokenKind::Caret,
+                TokenKind::Tilde,
+                TokenKind::ShiftLeft,
+                TokenKind::ShiftRight,
+                TokenKind::LessEqual,
+                TokenKind::EOL,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_integer_suffixes() {
+        let tokens = tokenize("5000000000i64 255u8 7u8x", 0).unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::Int64(5_000_000_000));
+        assert_eq!((tokens[0].span.lo, tokens[0].span.hi), (0, 13));
+        assert_eq!(tokens[1].kind, TokenKind::Byte(255));
+        assert_eq!(tokens[2].kind, TokenKind::Int(7));
+        assert_eq!(tokens[3].kind, TokenKind::Ident("u8x".into()));
+
+        let error = tokenize("256u8", 0).unwrap_err();
+        assert_eq!(
+            error.message,
+            "integer literal 256 is out of the range of Byte"
+        );
+        let error = tokenize("3000000000", 0).unwrap_err();
+        assert_eq!(
+            error.message,
+            "integer literal 3000000000 is out of the range of Int"
+        );
     }
 }
@@ -8,6 +8,14 @@ macro_rules! bin_op_pat {
         BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Modulo
     };
 
+    (BITWISE) => {
+        BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor
+    };
+
+    (SHIFT) => {
+        BinaryOp::ShiftLeft | BinaryOp::ShiftRight
+    };
+
     (NUMERIC_COMPARISON) => {
         BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual
     };
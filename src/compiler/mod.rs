@@ -1,12 +1,18 @@
 mod compiler;
-mod data_type_scope;
+mod constant;
+pub(crate) mod data_type_scope;
+mod interface;
 mod lexer;
 mod macros;
 mod parser;
+mod symbol;
 pub mod token;
 mod type_checker;
 
+pub(crate) use compiler::compile_session_statements;
 pub use compiler::Compiler;
-pub use lexer::tokenize;
+pub(crate) use interface::module_interfaces;
+pub use lexer::{tokenize, tokenize_with_comments};
 pub use parser::Parser;
+pub use symbol::{Symbol, SymbolKind};
 pub use type_checker::TypeChecker;
@@ -2,9 +2,10 @@ use std::str::FromStr as _;
 
 use super::token::{Token, TokenKind};
 use crate::ast::*;
-use crate::diagnostics::FileId;
+use crate::diagnostics::{FileId, Span};
 use crate::errors::CompileError;
 use crate::expect_token;
+use crate::function::Visibility;
 use crate::value::Value;
 
 pub struct Parser<'a> {
@@ -72,62 +73,227 @@ impl<'a> Parser<'a> {
 
     fn parse_top_level(&mut self) -> Result<(), CompileError> {
         while let Some(token) = self.peek() {
-            let token_span = token.span;
-
             match &token.kind {
                 TokenKind::EOL => {
                     self.skip();
                     continue;
                 }
 
-                TokenKind::DataType(_) => {
-                    expect_token!(TokenKind::DataType(return_type) in self);
-                    expect_token!(TokenKind::Ident(name), name_span in self);
-                    expect_token!(TokenKind::ParenL in self);
+                TokenKind::DataType(_)
+                | TokenKind::Import
+                | TokenKind::Pub
+                | TokenKind::Const
+                | TokenKind::Let => self.parse_item()?,
 
-                    let params = self.parse_named_parameters()?;
+                _ => {
+                    return Err(CompileError::unexpected_token_at(
+                        token.kind.clone(),
+                        token.span,
+                    ))
+                }
+            }
+        }
 
-                    expect_token!(TokenKind::ParenR, paren_r_span in self);
-                    expect_token!(TokenKind::BraceL in self);
+        Ok(())
+    }
 
-                    if BuiltinFunction::str_variants().contains(name.as_str()) {
-                        return Err(CompileError::reserved_function_name_at(name, name_span));
-                    }
+    /// Parses input of interactive session, which can contain both top level items
+    /// (functions, imports, constants) and statements.
+    /// Items are added to the returned `Ast`, statements are returned in order.
+    pub fn parse_session_input(mut self) -> Result<(Ast, Vec<Statement>), CompileError> {
+        let mut statements = vec![];
 
-                    let body = self.parse_statements(TokenKind::BraceR)?;
+        while let Some(token) = self.peek() {
+            match &token.kind {
+                TokenKind::EOL => self.skip(),
+                TokenKind::Import | TokenKind::Pub | TokenKind::Const => self.parse_item()?,
+                TokenKind::DataType(_) if self.is_function_definition() => self.parse_item()?,
+                _ => statements.push(self.parse_statement()?),
+            }
+        }
 
-                    expect_token!(TokenKind::BraceR in self);
+        Ok((self.ast, statements))
+    }
 
-                    let signiture = SpannedFunctionSigniture::new(
-                        return_type,
-                        params,
-                        token_span.join(paren_r_span),
-                    );
+    /// Function definition starts with `Type name(`, variable declaration with `Type name =`
+    fn is_function_definition(&self) -> bool {
+        matches!(
+            self.tokens.get(self.index + 2).map(|t| &t.kind),
+            Some(TokenKind::ParenL)
+        )
+    }
 
-                    self.ast.add_function(name, signiture, body);
-                }
+    /// Parses function definition, import, constant or global, optionally preceded by `pub`.
+    fn parse_item(&mut self) -> Result<(), CompileError> {
+        let pub_span = match self.peek_kind() {
+            Some(TokenKind::Pub) => self.next().map(|token| token.span),
+            _ => None,
+        };
 
-                TokenKind::Import => {
-                    expect_token!(TokenKind::Import in self);
-                    expect_token!(TokenKind::Ident(module_name) in self);
-                    expect_token!(TokenKind::EOL in self);
+        match self.peek_kind() {
+            Some(TokenKind::Import) => self.parse_import(pub_span),
+            Some(TokenKind::Const) => self.parse_constant(pub_span),
+            Some(TokenKind::DataType(_)) if self.is_function_definition() => {
+                self.parse_function_definition(pub_span)
+            }
+            Some(TokenKind::Let | TokenKind::DataType(_)) => match pub_span {
+                Some(span) => Err(CompileError::other_at(
+                    "globals can not be public, use a constant or a function",
+                    span,
+                )),
+                None => self.parse_global(),
+            },
+            _ => self.parse_function_definition(pub_span),
+        }
+    }
 
-                    let import_path = Path::single(module_name);
-                    self.ast.add_import(import_path);
-                }
+    /// Parses `const Type NAME = expr`, the type is required.
+    fn parse_constant(&mut self, pub_span: Option<Span>) -> Result<(), CompileError> {
+        expect_token!(TokenKind::Const, const_span in self);
+        let (start_span, visibility) = match pub_span {
+            Some(span) => (span, Visibility::Public),
+            None => (const_span, Visibility::Private),
+        };
+        expect_token!(TokenKind::DataType(data_type) in self);
+        expect_token!(TokenKind::Ident(name) in self);
+        expect_token!(TokenKind::Assign in self);
 
-                _ => {
-                    return Err(CompileError::unexpected_token_at(
-                        token.kind.clone(),
-                        token.span,
-                    ))
+        let value = self.parse_expr()?;
+        expect_token!(TokenKind::EOL in self);
+
+        let span = start_span.join(value.span);
+        let constant =
+            ConstantDeclaration::new(name, data_type, value, span).with_visibility(visibility);
+        self.ast.add_constant(constant);
+        Ok(())
+    }
+
+    /// Parses global declared the same way as a variable, `let name = expr` or `Type name = expr`.
+    fn parse_global(&mut self) -> Result<(), CompileError> {
+        let statement = self.parse_statement()?;
+        let StatementKind::VarDeclare {
+            data_type,
+            variable,
+            value,
+        } = statement.kind
+        else {
+            unreachable!(
+                "declaration starting with a type or 'let' is parsed as variable declaration"
+            );
+        };
+
+        let global = GlobalDeclaration::new(variable, data_type, value, statement.span);
+        self.ast.add_global(global);
+        Ok(())
+    }
+
+    /// `pub_span` is the span of the preceding `pub` keyword of public functions.
+    fn parse_function_definition(&mut self, pub_span: Option<Span>) -> Result<(), CompileError> {
+        expect_token!(TokenKind::DataType(return_type), type_span in self);
+        let (start_span, visibility) = match pub_span {
+            Some(span) => (span, Visibility::Public),
+            None => (type_span, Visibility::Private),
+        };
+        expect_token!(TokenKind::Ident(name), name_span in self);
+        expect_token!(TokenKind::ParenL in self);
+
+        let params = self.parse_named_parameters()?;
+
+        expect_token!(TokenKind::ParenR, paren_r_span in self);
+        expect_token!(TokenKind::BraceL in self);
+
+        if BuiltinFunction::str_variants().contains(name.as_str()) {
+            return Err(CompileError::reserved_function_name_at(name, name_span));
+        }
+
+        let body = self.parse_statements(TokenKind::BraceR)?;
+
+        expect_token!(TokenKind::BraceR in self);
+
+        let signiture =
+            SpannedFunctionSigniture::new(return_type, params, start_span.join(paren_r_span))
+                .with_visibility(visibility);
+
+        self.ast.add_function(name, signiture, body);
+        Ok(())
+    }
+
+    /// Parses `import path`, `import path as alias` or `import path:{function, ...}`.
+    /// `pub_span` is the span of the preceding `pub` keyword of re-exporting imports.
+    fn parse_import(&mut self, pub_span: Option<Span>) -> Result<(), CompileError> {
+        expect_token!(TokenKind::Import, import_span in self);
+        let start_span = pub_span.unwrap_or(import_span);
+        let import_path = self.parse_module_path()?;
+        let mut import = Import::new(import_path, start_span);
+        import.public = pub_span.is_some();
+
+        match self.peek_kind() {
+            Some(TokenKind::As) => {
+                self.skip();
+                expect_token!(TokenKind::Ident(alias), alias_span in self);
+                import.alias = Some(alias);
+                import.span = start_span.join(alias_span);
+            }
+            Some(TokenKind::Colon) => {
+                self.skip();
+                expect_token!(TokenKind::BraceL in self);
+                loop {
+                    expect_token!(TokenKind::Ident(name), name_span in self);
+                    import.functions.push((name, name_span));
+
+                    if let Some(TokenKind::Comma) = self.peek_kind() {
+                        self.skip();
+                    } else {
+                        break;
+                    }
                 }
+                expect_token!(TokenKind::BraceR, brace_r_span in self);
+                import.span = start_span.join(brace_r_span);
+            }
+            _ => {
+                let last_span = self.tokens[self.index - 1].span;
+                import.span = start_span.join(last_span);
             }
         }
 
+        expect_token!(TokenKind::EOL in self);
+
+        self.ast.add_import(import);
         Ok(())
     }
 
+    /// Parses dotted module path, e.g. `utils.strings`.
+    fn parse_module_path(&mut self) -> Result<Path, CompileError> {
+        expect_token!(TokenKind::Ident(segment) in self);
+        let mut segments = vec![segment];
+
+        while let Some(TokenKind::Dot) = self.peek_kind() {
+            self.skip();
+            expect_token!(TokenKind::Ident(segment) in self);
+            segments.push(segment);
+        }
+
+        Ok(Path::new(segments))
+    }
+
+    /// Module path in a foreign call continues with `.segment` and ends with `:`,
+    /// otherwise the dot starts a method call.
+    fn is_foreign_call_path(&self) -> bool {
+        let mut index = self.index;
+        loop {
+            match self.tokens.get(index).map(|t| &t.kind) {
+                Some(TokenKind::Colon) => return true,
+                Some(TokenKind::Dot) => (),
+                _ => return false,
+            }
+
+            match self.tokens.get(index + 1).map(|t| &t.kind) {
+                Some(TokenKind::Ident(_)) => index += 2,
+                _ => return false,
+            }
+        }
+    }
+
     fn parse_named_parameters(&mut self) -> Result<Vec<SpannedParameter>, CompileError> {
         let mut params = vec![];
         while let Some(token) = self.peek() {
@@ -163,87 +329,92 @@ impl<'a> Parser<'a> {
                 break;
             }
 
-            let token_span = token.span;
-            let statement = match token.kind {
-                // lonely EOL -> skip
-                TokenKind::EOL => {
-                    self.skip();
-                    continue;
-                }
+            // lonely EOL -> skip
+            if token.kind == TokenKind::EOL {
+                self.skip();
+                continue;
+            }
 
-                TokenKind::Return => {
-                    expect_token!(TokenKind::Return in self);
-                    if let Some(TokenKind::EOL) = self.peek_kind() {
-                        self.skip();
-                        Statement::return_statement(
-                            Expr::literal(Value::Void, token_span),
-                            token_span,
-                        )
-                    } else {
-                        let expr = self.parse_expr()?;
-                        expect_token!(TokenKind::EOL in self);
-                        Statement::return_statement(expr, token_span)
-                    }
-                }
+            statements.push(self.parse_statement()?);
+        }
 
-                // var declaration with explicit type
-                TokenKind::DataType(_) => {
-                    expect_token!(TokenKind::DataType(data_type) in self);
-                    expect_token!(TokenKind::Ident(ident) in self);
-                    expect_token!(TokenKind::Assign in self);
+        Ok(statements)
+    }
 
-                    let expr = self.parse_expr()?;
+    fn parse_statement(&mut self) -> Result<Statement, CompileError> {
+        let Some(token) = self.peek() else {
+            return Err(CompileError::unexpected_end_of_file(self.file_id));
+        };
 
+        let token_span = token.span;
+        let statement = match token.kind {
+            TokenKind::Return => {
+                expect_token!(TokenKind::Return in self);
+                if let Some(TokenKind::EOL) = self.peek_kind() {
+                    self.skip();
+                    Statement::return_statement(Expr::literal(Value::Void, token_span), token_span)
+                } else {
+                    let expr = self.parse_expr()?;
                     expect_token!(TokenKind::EOL in self);
-
-                    let expr_span = expr.span;
-                    Statement::var_declare(Some(data_type), ident, expr, token_span.join(expr_span))
+                    Statement::return_statement(expr, token_span)
                 }
+            }
 
-                TokenKind::Let => {
-                    expect_token!(TokenKind::Let in self);
-                    expect_token!(TokenKind::Ident(ident) in self);
-                    expect_token!(TokenKind::Assign in self);
+            // var declaration with explicit type
+            TokenKind::DataType(_) => {
+                expect_token!(TokenKind::DataType(data_type) in self);
+                expect_token!(TokenKind::Ident(ident) in self);
+                expect_token!(TokenKind::Assign in self);
 
-                    let expr = self.parse_expr()?;
+                let expr = self.parse_expr()?;
 
-                    expect_token!(TokenKind::EOL in self);
+                expect_token!(TokenKind::EOL in self);
 
-                    let expr_span = expr.span;
-                    Statement::var_declare(None, ident, expr, token_span.join(expr_span))
-                }
+                let expr_span = expr.span;
+                Statement::var_declare(Some(data_type), ident, expr, token_span.join(expr_span))
+            }
 
-                // var assign / function call in expr stmt
-                TokenKind::Ident(_) => self.parse_ident_statement()?,
+            TokenKind::Let => {
+                expect_token!(TokenKind::Let in self);
+                expect_token!(TokenKind::Ident(ident) in self);
+                expect_token!(TokenKind::Assign in self);
 
-                TokenKind::If => self.parse_if_statement()?,
+                let expr = self.parse_expr()?;
 
-                TokenKind::While => {
-                    expect_token!(TokenKind::While in self);
+                expect_token!(TokenKind::EOL in self);
 
-                    let cond = self.parse_expr()?;
+                let expr_span = expr.span;
+                Statement::var_declare(None, ident, expr, token_span.join(expr_span))
+            }
 
-                    expect_token!(TokenKind::BraceL in self);
+            // var assign / function call in expr stmt
+            TokenKind::Ident(_) => self.parse_ident_statement()?,
 
-                    let stmts = self.parse_statements(TokenKind::BraceR)?;
+            TokenKind::If => self.parse_if_statement()?,
 
-                    expect_token!(TokenKind::BraceR, brace_r_span in self);
+            TokenKind::While => {
+                expect_token!(TokenKind::While in self);
 
-                    Statement::while_statement(cond, stmts, token_span.join(brace_r_span))
-                }
+                let cond = self.parse_expr()?;
 
-                _ => {
-                    let expr = self.parse_expr()?;
-                    expect_token!(TokenKind::EOL in self);
-                    let span = expr.span;
-                    Statement::expr_statement(expr, span)
-                }
-            };
+                expect_token!(TokenKind::BraceL in self);
 
-            statements.push(statement);
-        }
+                let stmts = self.parse_statements(TokenKind::BraceR)?;
 
-        Ok(statements)
+                expect_token!(TokenKind::BraceR, brace_r_span in self);
+
+                Statement::while_statement(cond, stmts, token_span.join(brace_r_span))
+            }
+
+            _ => {
+                let expr = self.parse_expr()?;
+                self.expect_expr_statement_end()?;
+                let span = expr.span;
+                Statement::expr_statement(expr, span)
+            }
+        };
+
+        Ok(statement)
     }
 
     fn parse_ident_statement(&mut self) -> Result<Statement, CompileError> {
@@ -268,7 +439,7 @@ impl<'a> Parser<'a> {
             // and parse it as function call
             self.back();
             let expr = self.parse_expr()?;
-            expect_token!(TokenKind::EOL in self);
+            self.expect_expr_statement_end()?;
 
             let expr_span = expr.span;
 
@@ -276,6 +447,18 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Expression statements end with a new line or with the closing brace of the block,
+    /// so the value of a block expression fits on one line, `if a { 1 } else { 2 }`.
+    /// The brace is not consumed.
+    fn expect_expr_statement_end(&mut self) -> Result<(), CompileError> {
+        if let Some(TokenKind::BraceR) = self.peek_kind() {
+            return Ok(());
+        }
+
+        expect_token!(TokenKind::EOL in self);
+        Ok(())
+    }
+
     fn parse_if_statement(&mut self) -> Result<Statement, CompileError> {
         expect_token!(TokenKind::If, if_span in self);
 
@@ -317,7 +500,7 @@ impl<'a> Parser<'a> {
 
     /// Parse logical `BinaryOp` such as and, or
     fn parse_logical_expr(&mut self) -> Result<Expr, CompileError> {
-        let mut lhs = self.parse_comp_expr()?;
+        let mut lhs = self.parse_bit_or_expr()?;
 
         while let Some(token) = self.peek() {
             let Some(op) = token_to_logical_op(&token.kind) else {
@@ -325,7 +508,7 @@ impl<'a> Parser<'a> {
             };
 
             self.skip();
-            let rhs = self.parse_comp_expr()?;
+            let rhs = self.parse_bit_or_expr()?;
             let lhs_span = lhs.span;
             let rhs_span = rhs.span;
             lhs = Expr::binary_op(op, lhs, rhs, lhs_span.join(rhs_span));
@@ -336,13 +519,75 @@ impl<'a> Parser<'a> {
 
     /// Parse eq and neq `BinaryOp`
     fn parse_comp_expr(&mut self) -> Result<Expr, CompileError> {
-        let mut lhs = self.parse_add_expr()?;
+        let mut lhs = self.parse_shift_expr()?;
 
         if let Some(token) = self.peek() {
             let Some(op) = token_to_comp_op(&token.kind) else {
                 return Ok(lhs);
             };
 
+            self.skip();
+            let rhs = self.parse_shift_expr()?;
+            let lhs_span = lhs.span;
+            let rhs_span = rhs.span;
+            lhs = Expr::binary_op(op, lhs, rhs, lhs_span.join(rhs_span));
+        }
+
+        Ok(lhs)
+    }
+
+    /// Parse bitwise or, bitwise operators bind looser than comparisons like in C,
+    /// `flags & MASK != 0` is `flags & (MASK != 0)`
+    fn parse_bit_or_expr(&mut self) -> Result<Expr, CompileError> {
+        let mut lhs = self.parse_bit_xor_expr()?;
+
+        while let Some(TokenKind::Pipe) = self.peek_kind() {
+            self.skip();
+            let rhs = self.parse_bit_xor_expr()?;
+            let span = lhs.span.join(rhs.span);
+            lhs = Expr::binary_op(BinaryOp::BitOr, lhs, rhs, span);
+        }
+
+        Ok(lhs)
+    }
+
+    /// Parse bitwise xor `BinaryOp`
+    fn parse_bit_xor_expr(&mut self) -> Result<Expr, CompileError> {
+        let mut lhs = self.parse_bit_and_expr()?;
+
+        while let Some(TokenKind::Caret) = self.peek_kind() {
+            self.skip();
+            let rhs = self.parse_bit_and_expr()?;
+            let span = lhs.span.join(rhs.span);
+            lhs = Expr::binary_op(BinaryOp::BitXor, lhs, rhs, span);
+        }
+
+        Ok(lhs)
+    }
+
+    /// Parse bitwise and `BinaryOp`
+    fn parse_bit_and_expr(&mut self) -> Result<Expr, CompileError> {
+        let mut lhs = self.parse_comp_expr()?;
+
+        while let Some(TokenKind::Ampersand) = self.peek_kind() {
+            self.skip();
+            let rhs = self.parse_comp_expr()?;
+            let span = lhs.span.join(rhs.span);
+            lhs = Expr::binary_op(BinaryOp::BitAnd, lhs, rhs, span);
+        }
+
+        Ok(lhs)
+    }
+
+    /// Parse shift left and shift right `BinaryOp`
+    fn parse_shift_expr(&mut self) -> Result<Expr, CompileError> {
+        let mut lhs = self.parse_add_expr()?;
+
+        while let Some(token) = self.peek() {
+            let Some(op) = token_to_shift_op(&token.kind) else {
+                return Ok(lhs);
+            };
+
             self.skip();
             let rhs = self.parse_add_expr()?;
             let lhs_span = lhs.span;
@@ -374,7 +619,7 @@ impl<'a> Parser<'a> {
 
     /// Parse multiply, divide and modulo `BinaryOp`
     fn parse_mul_expr(&mut self) -> Result<Expr, CompileError> {
-        let mut lhs = self.parse_unary_op_expr()?;
+        let mut lhs = self.parse_cast_expr()?;
 
         while let Some(token) = self.peek() {
             let Some(op) = token_to_mul_op(token) else {
@@ -382,7 +627,7 @@ impl<'a> Parser<'a> {
             };
 
             self.skip();
-            let rhs = self.parse_unary_op_expr()?;
+            let rhs = self.parse_cast_expr()?;
             let lhs_span = lhs.span;
             let rhs_span = rhs.span;
             lhs = Expr::binary_op(op, lhs, rhs, lhs_span.join(rhs_span));
@@ -391,6 +636,20 @@ impl<'a> Parser<'a> {
         Ok(lhs)
     }
 
+    /// Parse `as` casts, they bind tighter than binary operators, `-x as Float` casts `-x`
+    fn parse_cast_expr(&mut self) -> Result<Expr, CompileError> {
+        let mut expr = self.parse_unary_op_expr()?;
+        while let Some(TokenKind::As) = self.peek_kind() {
+            self.skip();
+            expect_token!(TokenKind::DataType(data_type), end_span in self);
+
+            let span = expr.span.join(end_span);
+            expr = Expr::cast(expr, data_type, span);
+        }
+
+        Ok(expr)
+    }
+
     /// Parse unary `UnaryOp` such as not
     fn parse_unary_op_expr(&mut self) -> Result<Expr, CompileError> {
         let token = self
@@ -408,22 +667,23 @@ impl<'a> Parser<'a> {
         return Ok(Expr::unary_op(op, expr, token_span.join(expr_span)));
     }
 
+    /// Parse method calls, they can be chained as `text.trim().length()`
     fn parse_method_call_expr(&mut self) -> Result<Expr, CompileError> {
-        let base_expr = self.parse_atom_expr()?;
-        if let Some(TokenKind::Dot) = self.peek_kind() {
+        let mut expr = self.parse_atom_expr()?;
+        while let Some(TokenKind::Dot) = self.peek_kind() {
             self.skip();
             expect_token!(TokenKind::Ident(method_name) in self);
             expect_token!(TokenKind::ParenL in self);
             let args = self.parse_args()?;
             expect_token!(TokenKind::ParenR, end_span in self);
 
-            let span = base_expr.span.join(end_span);
-
-            Ok(Expr::method_call(base_expr, method_name, args, span))
-        } else {
-            Ok(base_expr)
+            let span = expr.span.join(end_span);
+            expr = Expr::method_call(expr, method_name, args, span);
         }
+
+        Ok(expr)
     }
+
     /// Parse atom expr such as Ident, Num, Bool, not ops.
     fn parse_atom_expr(&mut self) -> Result<Expr, CompileError> {
         let Some(token) = self.peek() else {
@@ -443,6 +703,16 @@ impl<'a> Parser<'a> {
                 Ok(Expr::literal(Value::Int(int), token_span))
             }
 
+            TokenKind::Int64(_) => {
+                expect_token!(TokenKind::Int64(int) in self);
+                Ok(Expr::literal(Value::Int64(int), token_span))
+            }
+
+            TokenKind::Byte(_) => {
+                expect_token!(TokenKind::Byte(byte) in self);
+                Ok(Expr::literal(Value::Byte(byte), token_span))
+            }
+
             TokenKind::Float(_) => {
                 expect_token!(TokenKind::Float(float) in self);
                 Ok(Expr::literal(Value::Float(float), token_span))
@@ -462,6 +732,21 @@ impl<'a> Parser<'a> {
                 inner
             }
 
+            TokenKind::If => {
+                let statement = self.parse_if_statement()?;
+                if_statement_into_expr(statement)
+            }
+
+            TokenKind::BraceL => {
+                expect_token!(TokenKind::BraceL in self);
+                let statements = self.parse_statements(TokenKind::BraceR)?;
+                expect_token!(TokenKind::BraceR, brace_r_span in self);
+
+                let span = token_span.join(brace_r_span);
+                let block = block_into_expr(statements, span)?;
+                Ok(Expr::new(block.kind, span))
+            }
+
             _ => {
                 return Err(CompileError::unexpected_token_at(
                     self.next().unwrap().kind,
@@ -491,16 +776,27 @@ impl<'a> Parser<'a> {
                 Ok(Expr::function_call(ident, args, ident_span.join(end_span)))
             }
 
-            Some(TokenKind::Colon) => {
+            Some(TokenKind::Colon | TokenKind::Dot) if self.is_foreign_call_path() => {
+                self.back();
+                let module_path = self.parse_module_path()?;
                 expect_token!(TokenKind::Colon in self);
-                expect_token!(TokenKind::Ident(func_name) in self);
+                expect_token!(TokenKind::Ident(func_name), name_span in self);
+
+                // `module:NAME` without arguments is a constant of the module
+                if self.peek_kind() != Some(&TokenKind::ParenL) {
+                    return Ok(Expr::foreign_constant(
+                        module_path.to_string(),
+                        func_name,
+                        ident_span.join(name_span),
+                    ));
+                }
 
                 expect_token!(TokenKind::ParenL in self);
                 let args = self.parse_args()?;
                 expect_token!(TokenKind::ParenR, end_span in self);
 
                 Ok(Expr::foreign_function_call(
-                    ident,
+                    module_path.to_string(),
                     func_name,
                     args,
                     ident_span.join(end_span),
@@ -532,6 +828,69 @@ impl<'a> Parser<'a> {
     }
 }
 
+/// If statement used as a value, it must have an else branch and both branches must end
+/// with an expression.
+fn if_statement_into_expr(statement: Statement) -> Result<Expr, CompileError> {
+    let StatementKind::If {
+        condition,
+        if_block,
+        else_block,
+    } = statement.kind
+    else {
+        unreachable!("if_statement_into_expr called on non-if statement");
+    };
+
+    let Some(else_block) = else_block else {
+        return Err(CompileError::other_at(
+            "'if' used as a value needs an 'else' branch",
+            statement.span,
+        ));
+    };
+
+    let if_block = block_into_expr(if_block, statement.span)?;
+    // `else if` is parsed as else block with single if statement
+    let else_block = match else_block.as_slice() {
+        [Statement {
+            kind: StatementKind::If { .. },
+            ..
+        }] => if_statement_into_expr(else_block.into_iter().next().unwrap())?,
+        _ => block_into_expr(else_block, statement.span)?,
+    };
+
+    Ok(Expr::if_expr(
+        condition,
+        if_block,
+        else_block,
+        statement.span,
+    ))
+}
+
+/// Block used as a value, the last statement is the value. `span` is reported for empty blocks.
+fn block_into_expr(mut statements: Vec<Statement>, span: Span) -> Result<Expr, CompileError> {
+    let Some(last) = statements.pop() else {
+        return Err(CompileError::other_at(
+            "block used as a value must end with an expression",
+            span,
+        ));
+    };
+
+    let block_span = statements
+        .first()
+        .map_or(last.span, |first| first.span.join(last.span));
+    let result = match last.kind {
+        StatementKind::Expr(expr) => expr,
+        kind @ StatementKind::If { .. } => if_statement_into_expr(Statement::new(kind, last.span))?,
+        _ => {
+            return Err(CompileError::other_at(
+                "block used as a value must end with an expression",
+                last.span,
+            ))
+        }
+    };
+
+    Ok(Expr::block(statements, result, block_span))
+}
+
 fn token_to_logical_op(token: &TokenKind) -> Option<BinaryOp> {
     match token {
         TokenKind::And => Some(BinaryOp::And),
@@ -552,6 +911,14 @@ fn token_to_comp_op(token: &TokenKind) -> Option<BinaryOp> {
     }
 }
 
+fn token_to_shift_op(token: &TokenKind) -> Option<BinaryOp> {
+    match token {
+        TokenKind::ShiftLeft => Some(BinaryOp::ShiftLeft),
+        TokenKind::ShiftRight => Some(BinaryOp::ShiftRight),
+        _ => None,
+    }
+}
+
 fn token_to_add_op(token: &TokenKind) -> Option<BinaryOp> {
     match token {
         TokenKind::Plus => Some(BinaryOp::Add),
@@ -573,6 +940,7 @@ fn token_to_unary_op(token: &Token) -> Option<UnaryOp> {
     match &token.kind {
         TokenKind::Not => Some(UnaryOp::Not),
         TokenKind::Minus => Some(UnaryOp::Negate),
+        TokenKind::Tilde => Some(UnaryOp::BitNot),
         _ => None,
     }
 }
@@ -766,6 +1134,154 @@ mod test {
         }
     }
 
+    #[test]
+    fn parse_module_paths() {
+        let ast = Ast::from_string(
+            "import utils.strings\nVoid main() {\n let n = utils.strings:len(s.length())\n}",
+            TEST_FILE_ID,
+        )
+        .unwrap();
+        assert_eq!(ast.get_imports()[0].to_string(), "utils.strings");
+
+        let body = ast.get_function_body_by_slot(0).unwrap();
+        let StatementKind::VarDeclare { value, .. } = &body[0].kind else {
+            panic!("Expected variable declaration");
+        };
+        let ExprKind::ForeignFunctionCall {
+            module_name, args, ..
+        } = &value.kind
+        else {
+            panic!("Expected foreign function call");
+        };
+        assert_eq!(module_name, "utils.strings");
+        assert!(matches!(args[0].kind, ExprKind::MethodCall { .. }));
+    }
+
+    #[test]
+    fn parse_chained_method_calls() {
+        let ast =
+            Ast::from_string("Void main() {\n let n = s.trim().length()\n}", TEST_FILE_ID).unwrap();
+
+        let body = ast.get_function_body_by_slot(0).unwrap();
+        let StatementKind::VarDeclare { value, .. } = &body[0].kind else {
+            panic!("Expected variable declaration");
+        };
+        let ExprKind::MethodCall {
+            callee,
+            method_name,
+            ..
+        } = &value.kind
+        else {
+            panic!("Expected method call");
+        };
+        assert_eq!(method_name, "length");
+        assert_eq!((value.span.lo, value.span.hi), (23, 40));
+        assert!(matches!(
+            &callee.kind,
+            ExprKind::MethodCall { method_name, .. } if method_name == "trim"
+        ));
+    }
+
+    #[test]
+    fn parse_bitwise_precedence() {
+        let parse = |expr: &str| {
+            let source = format!("Void main() {{\n let x = {}\n}}", expr);
+            let ast = Ast::from_string(&source, TEST_FILE_ID).unwrap();
+            let body = ast.get_function_body_by_slot(0).unwrap();
+            let StatementKind::VarDeclare { value, .. } = &body[0].kind else {
+                panic!("Expected variable declaration");
+            };
+            value.clone()
+        };
+
+        // `1 | (2 == 2)` as in C
+        let value = parse("1 | 2 == 2");
+        let ExprKind::BinaryOp(BinaryOp::BitOr, _, rhs) = &value.kind else {
+            panic!("Expected bitwise or, found {:?}", value.kind);
+        };
+        assert!(matches!(rhs.kind, ExprKind::BinaryOp(BinaryOp::Equal, ..)));
+
+        // `(1 << (2 + 1)) < 8`, shifts bind tighter than comparisons and looser than `+`
+        let value = parse("1 << 2 + 1 < 8");
+        let ExprKind::BinaryOp(BinaryOp::Less, lhs, _) = &value.kind else {
+            panic!("Expected comparison, found {:?}", value.kind);
+        };
+        let ExprKind::BinaryOp(BinaryOp::ShiftLeft, _, rhs) = &lhs.kind else {
+            panic!("Expected shift, found {:?}", lhs.kind);
+        };
+        assert!(matches!(rhs.kind, ExprKind::BinaryOp(BinaryOp::Add, ..)));
+
+        // `a || (b | (c ^ (d & e)))`
+        let value = parse("a || b | c ^ d & e");
+        let ExprKind::BinaryOp(BinaryOp::Or, _, rhs) = &value.kind else {
+            panic!("Expected logical or, found {:?}", value.kind);
+        };
+        let ExprKind::BinaryOp(BinaryOp::BitOr, _, rhs) = &rhs.kind else {
+            panic!("Expected bitwise or, found {:?}", rhs.kind);
+        };
+        let ExprKind::BinaryOp(BinaryOp::BitXor, _, rhs) = &rhs.kind else {
+            panic!("Expected bitwise xor, found {:?}", rhs.kind);
+        };
+        assert!(matches!(rhs.kind, ExprKind::BinaryOp(BinaryOp::BitAnd, ..)));
+    }
+
+    #[test]
+    fn parse_if_and_block_expressions() {
+        let source = "Void main() {\n let x = if a { 1 } else if b { 2 } else { 3 }\n}";
+        let ast = Ast::from_string(source, TEST_FILE_ID).unwrap();
+        let body = ast.get_function_body_by_slot(0).unwrap();
+        let StatementKind::VarDeclare { value, .. } = &body[0].kind else {
+            panic!("Expected variable declaration");
+        };
+        let ExprKind::If { else_block, .. } = &value.kind else {
+            panic!("Expected if expression");
+        };
+        assert_eq!((value.span.lo, value.span.hi), (23, 60));
+        assert!(matches!(else_block.kind, ExprKind::If { .. }));
+
+        let source = "Void main() {\n let x = {\n let y = 2\n y * y\n }\n}";
+        let ast = Ast::from_string(source, TEST_FILE_ID).unwrap();
+        let body = ast.get_function_body_by_slot(0).unwrap();
+        let StatementKind::VarDeclare { value, .. } = &body[0].kind else {
+            panic!("Expected variable declaration");
+        };
+        let ExprKind::Block { statements, result } = &value.kind else {
+            panic!("Expected block expression");
+        };
+        assert_eq!(statements.len(), 1);
+        assert!(matches!(result.kind, ExprKind::BinaryOp(BinaryOp::Mul, ..)));
+
+        let error = Ast::from_string("Void main() {\n let x = if a { 1 }\n}", TEST_FILE_ID);
+        assert_eq!(
+            error.unwrap_err().message,
+            "'if' used as a value needs an 'else' branch"
+        );
+        let error = Ast::from_string("Void main() {\n let x = {\n let y = 1\n }\n}", TEST_FILE_ID);
+        assert_eq!(
+            error.unwrap_err().message,
+            "block used as a value must end with an expression"
+        );
+    }
+
+    #[test]
+    fn parse_import_aliases_and_functions() {
+        let source = "import geometry.vector as vec\nimport math:{add, mul}\n";
+        let ast = Ast::from_string(source, TEST_FILE_ID).unwrap();
+        let imports = ast.get_imports();
+
+        assert_eq!(imports[0].path.to_string(), "geometry.vector");
+        assert_eq!(imports[0].name(), "vec");
+        assert_eq!((imports[0].span.lo, imports[0].span.hi), (0, 29));
+
+        assert_eq!(imports[1].name(), "math");
+        assert!(imports[1].imports_function("mul"));
+        assert_eq!(imports[1].to_string(), "math:{add, mul}");
+        assert_eq!(imports[1].functions[0].1.lo, source.find("add").unwrap());
+
+        assert!(Ast::from_string("import math:{}\n", TEST_FILE_ID).is_err());
+        assert!(Ast::from_string("import math as\n", TEST_FILE_ID).is_err());
+    }
+
     #[test]
     fn parse_empty_args() {
         let tokens = vec![create_token(TokenKind::ParenR, 0, 1)];
@@ -848,4 +1364,15 @@ mod test {
             panic!("Expected logical AND at top level");
         }
     }
+
+    #[test]
+    fn parse_semicolon_separated_statements() {
+        let source = "Void main() {\n let x = 1; x = x + 1; print(x);\n}";
+        let ast = Ast::from_string(source, TEST_FILE_ID).unwrap();
+        let body = ast.get_function_body_by_slot(0).unwrap();
+        assert_eq!(body.len(), 3);
+        assert!(matches!(body[0].kind, StatementKind::VarDeclare { .. }));
+        assert!(matches!(body[1].kind, StatementKind::VarAssign { .. }));
+        assert!(matches!(body[2].kind, StatementKind::Expr(_)));
+    }
 }
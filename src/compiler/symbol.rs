@@ -0,0 +1,70 @@
+use crate::{data_type::DataType, diagnostics::Span, function::FunctionSigniture};
+
+/// Occurrence of a name in the source, recorded by the type checker for editor tooling.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub span: Span,
+    pub kind: SymbolKind,
+}
+
+impl Symbol {
+    pub fn new(name: impl Into<String>, span: Span, kind: SymbolKind) -> Self {
+        Self {
+            name: name.into(),
+            span,
+            kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolKind {
+    /// Variable or parameter, `definition` is the span of its declaration
+    Variable {
+        data_type: DataType,
+        definition: Span,
+    },
+    /// Function of the checked module, `definition` is the span of its signiture
+    Function {
+        signiture: FunctionSigniture,
+        definition: Span,
+    },
+    /// Function called as `module:name`
+    ForeignFunction {
+        module_name: String,
+        signiture: FunctionSigniture,
+    },
+    Method {
+        callee_type: DataType,
+        return_type: DataType,
+    },
+}
+
+impl SymbolKind {
+    /// Short description of the symbol, e.g. `int add(int a, int b)` for functions.
+    pub fn describe(&self, name: &str) -> String {
+        match self {
+            SymbolKind::Variable { data_type, .. } => format!("{} {}", data_type, name),
+            SymbolKind::Function { signiture, .. } => describe_function(name, signiture),
+            SymbolKind::ForeignFunction {
+                module_name,
+                signiture,
+            } => describe_function(&format!("{}:{}", module_name, name), signiture),
+            SymbolKind::Method {
+                callee_type,
+                return_type,
+            } => format!("{} {}.{}()", return_type, callee_type, name),
+        }
+    }
+}
+
+fn describe_function(name: &str, signiture: &FunctionSigniture) -> String {
+    let params: Vec<String> = signiture
+        .params
+        .iter()
+        .map(|p| format!("{} {}", p.data_type, p.name))
+        .collect();
+
+    format!("{} {}({})", signiture.return_type, name, params.join(", "))
+}
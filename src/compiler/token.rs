@@ -16,6 +16,14 @@ impl Token {
     }
 }
 
+/// Comment skipped by the lexer, kept for tools that need to preserve it (formatter).
+/// `text` includes the leading `#`, `span` does not include the line break.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub text: String,
+    pub span: Span,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
     EOF,
@@ -28,6 +36,12 @@ pub enum TokenKind {
     Equal,
     NotEqual,
     Modulo,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    ShiftLeft,
+    ShiftRight,
     Less,
     LessEqual,
     Greater,
@@ -43,6 +57,9 @@ pub enum TokenKind {
     While,
     Return,
     Import,
+    As,
+    Pub,
+    Const,
 
     ParenL,
     ParenR,
@@ -59,6 +76,8 @@ pub enum TokenKind {
     Ident(String),
     StringLiteral(String),
     Int(i32),
+    Int64(i64),
+    Byte(u8),
     Float(f64),
     Bool(bool),
 }
@@ -75,6 +94,12 @@ impl Display for TokenKind {
             TokenKind::Equal => write!(f, "'=='"),
             TokenKind::NotEqual => write!(f, "'!='"),
             TokenKind::Modulo => write!(f, "'%'"),
+            TokenKind::Ampersand => write!(f, "'&'"),
+            TokenKind::Pipe => write!(f, "'|'"),
+            TokenKind::Caret => write!(f, "'^'"),
+            TokenKind::Tilde => write!(f, "'~'"),
+            TokenKind::ShiftLeft => write!(f, "'<<'"),
+            TokenKind::ShiftRight => write!(f, "'>>'"),
             TokenKind::Less => write!(f, "'<'"),
             TokenKind::LessEqual => write!(f, "'<='"),
             TokenKind::Greater => write!(f, "'>'"),
@@ -89,6 +114,9 @@ impl Display for TokenKind {
             TokenKind::While => write!(f, "'while'"),
             TokenKind::Return => write!(f, "'return'"),
             TokenKind::Import => write!(f, "'import'"),
+            TokenKind::As => write!(f, "'as'"),
+            TokenKind::Pub => write!(f, "'pub'"),
+            TokenKind::Const => write!(f, "'const'"),
             TokenKind::ParenL => write!(f, "'('"),
             TokenKind::ParenR => write!(f, "')'"),
             TokenKind::BracketL => write!(f, "'['"),
@@ -103,6 +131,8 @@ impl Display for TokenKind {
             TokenKind::Ident(name) => write!(f, "identifier '{}'", name),
             TokenKind::StringLiteral(s) => write!(f, "string literal \"{}\"", s),
             TokenKind::Int(i) => write!(f, "integer literal {}", i),
+            TokenKind::Int64(i) => write!(f, "integer literal {}i64", i),
+            TokenKind::Byte(b) => write!(f, "integer literal {}u8", b),
             TokenKind::Float(fl) => write!(f, "float literal {}", fl),
             TokenKind::Bool(b) => write!(f, "boolean literal {}", b),
         }
@@ -1,63 +1,388 @@
 use std::collections::HashMap;
 
+use super::constant::{evaluate_constants, find_foreign_constant};
 use super::data_type_scope::DataTypeScope;
+use super::symbol::{Symbol, SymbolKind};
 use crate::ast::{
-    Ast, BinaryOp, BuiltinFunction, Expr, ExprKind, Statement, StatementKind, UnaryOp,
+    Ast, BinaryOp, BuiltinFunction, Expr, ExprKind, Import, Statement, StatementKind, UnaryOp,
 };
 use crate::bin_op_pat;
 use crate::data_type::DataType;
 use crate::diagnostics::Span;
 use crate::errors::CompileError;
+use crate::formatter::{binary_op_symbol, data_type_keyword};
 use crate::module::Module;
 
 pub struct TypeChecker<'a> {
     ast: &'a Ast,
     dependencies: &'a HashMap<String, Module>,
     scope: DataTypeScope,
+    symbols: Vec<Symbol>,
+    /// types of the globals checked so far, only these are visible
+    global_types: Vec<DataType>,
+    /// number of constants visible in the checked code, constants can use only the previous ones
+    visible_constants: usize,
+    /// number of block expressions the checked statement is in
+    block_depth: usize,
 }
 
 impl<'a> TypeChecker<'a> {
     pub fn new(ast: &'a Ast, dependencies: &'a HashMap<String, Module>) -> Self {
+        Self::with_scope(ast, dependencies, DataTypeScope::new())
+    }
+
+    /// Type checker for statements of interactive session,
+    /// `scope` holds variables declared by previous inputs.
+    pub(crate) fn with_scope(
+        ast: &'a Ast,
+        dependencies: &'a HashMap<String, Module>,
+        scope: DataTypeScope,
+    ) -> Self {
         Self {
             ast,
             dependencies,
-            scope: DataTypeScope::new(),
+            scope,
+            symbols: vec![],
+            global_types: vec![],
+            visible_constants: ast.get_constants().len(),
+            block_depth: 0,
         }
     }
 
     pub fn check(mut self) -> Result<(), CompileError> {
+        self.check_main_signiture()?;
+        self.check_imports()?;
+        self.check_constants()?;
+        self.check_globals()?;
         self.check_functions()
     }
 
+    /// Checks every function even if some of them contain errors, for editor tooling.
+    /// Returns the first error with symbols of all successfully checked code.
+    pub fn check_with_symbols(mut self) -> (Result<(), CompileError>, Vec<Symbol>) {
+        let mut result = self
+            .check_main_signiture()
+            .and(self.check_imports())
+            .and(self.check_constants())
+            .and(self.check_globals());
+        for slot in 0..self.ast.function_count() {
+            let function_result = self.check_function(slot);
+            if result.is_ok() {
+                result = function_result;
+            }
+        }
+
+        (result, self.symbols)
+    }
+
+    /// Entry point must not take parameters and must return `Void` or `Int`,
+    /// returned `Int` is used as the exit status.
+    fn check_main_signiture(&self) -> Result<(), CompileError> {
+        let Some(signiture) = self.ast.get_function_signiture("main") else {
+            return Ok(());
+        };
+
+        if let Some(param) = signiture.params.first() {
+            return Err(CompileError::invalid_main_signiture_at(
+                "function 'main' must not take parameters",
+                param.span,
+            ));
+        }
+
+        if !matches!(signiture.return_type, DataType::Void | DataType::Int) {
+            return Err(CompileError::invalid_main_signiture_at(
+                format!(
+                    "function 'main' must return void or int, found {}",
+                    signiture.return_type
+                ),
+                signiture.span,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Selectively imported functions must be public functions of their module and their
+    /// names must not clash with functions of the module or with each other. Aliases must not
+    /// clash with names of other imports.
+    fn check_imports(&self) -> Result<(), CompileError> {
+        let imports = self.ast.get_imports();
+        let mut imported_functions: HashMap<&str, &Import> = HashMap::new();
+
+        for (index, import) in imports.iter().enumerate() {
+            let clashing_module = imports[..index].iter().find(|other| {
+                other.name() == import.name() && (import.alias.is_some() || other.alias.is_some())
+            });
+            if let Some(other) = clashing_module {
+                return Err(CompileError::import_clash_at(
+                    import.name(),
+                    format!("import of module {}", other.path),
+                    import.span,
+                ));
+            }
+
+            if import.functions.is_empty() && !import.public {
+                continue;
+            }
+
+            let module_name = import.path.to_string();
+            let Some(module) = self.dependencies.get(&module_name) else {
+                return Err(CompileError::module_not_found_at(module_name, import.span));
+            };
+
+            for (name, span) in import.functions.iter() {
+                let Some(signiture) = module.get_function_signiture(name) else {
+                    return Err(CompileError::unknown_foreign_function_at(
+                        &module_name,
+                        name,
+                        *span,
+                    ));
+                };
+
+                if !signiture.visibility.is_public() {
+                    return Err(CompileError::private_function_at(&module_name, name, *span));
+                }
+
+                if self.ast.get_function_slot(name).is_some() {
+                    return Err(CompileError::import_clash_at(
+                        name,
+                        "function of this module",
+                        *span,
+                    ));
+                }
+
+                if let Some(other) = imported_functions.insert(name, import) {
+                    return Err(CompileError::import_clash_at(
+                        name,
+                        format!("function imported from {}", other.path),
+                        *span,
+                    ));
+                }
+            }
+        }
+
+        self.check_reexports()
+    }
+
+    /// Re-exported functions become functions of the module, so their names must be unique.
+    fn check_reexports(&self) -> Result<(), CompileError> {
+        let mut reexported: HashMap<&str, &Import> = HashMap::new();
+        for (import, name, _) in self.ast.get_reexported_functions(self.dependencies) {
+            if self.ast.get_function_slot(name).is_some() {
+                return Err(CompileError::import_clash_at(
+                    name,
+                    "function of this module",
+                    import.span,
+                ));
+            }
+
+            if let Some(other) = reexported.insert(name, import) {
+                return Err(CompileError::import_clash_at(
+                    name,
+                    format!("function re-exported from {}", other.path),
+                    import.span,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Constants are checked in the declaration order and can use only the previous constants,
+    /// their values must be computable at compile time.
+    fn check_constants(&mut self) -> Result<(), CompileError> {
+        let ast = self.ast;
+        let constants = ast.get_constants();
+
+        for (index, constant) in constants.iter().enumerate() {
+            if constants[..index].iter().any(|c| c.name == constant.name) {
+                return Err(CompileError::variable_redeclaration_at(
+                    &constant.name,
+                    constant.span,
+                ));
+            }
+
+            self.visible_constants = index;
+            let value_type = self.check_expr(&constant.value);
+            self.visible_constants = constants.len();
+
+            let value_type = value_type?;
+            if value_type != constant.data_type {
+                return Err(CompileError::wrong_type_at(
+                    constant.data_type,
+                    value_type,
+                    constant.value.span,
+                ));
+            }
+
+            self.record_variable(&constant.name, constant.span);
+        }
+
+        evaluate_constants(ast, self.dependencies).map(|_| ())
+    }
+
+    /// Globals are initialized in the declaration order, so an initializer can use
+    /// only the previous globals. Functions see all of them.
+    fn check_globals(&mut self) -> Result<(), CompileError> {
+        let ast = self.ast;
+        let globals = ast.get_globals();
+
+        for (index, global) in globals.iter().enumerate() {
+            let redeclared = globals[..index].iter().any(|g| g.name == global.name)
+                || ast.get_constant(&global.name).is_some();
+            if redeclared {
+                return Err(CompileError::variable_redeclaration_at(
+                    &global.name,
+                    global.span,
+                ));
+            }
+
+            let value_type = self.check_expr(&global.value)?;
+            if let Some(data_type) = global.data_type {
+                if value_type != data_type {
+                    return Err(CompileError::wrong_type_at(
+                        data_type,
+                        value_type,
+                        global.span,
+                    ));
+                }
+            }
+
+            self.global_types.push(value_type);
+            self.record_variable(&global.name, global.span);
+        }
+
+        Ok(())
+    }
+
+    /// Types of the globals in slot order.
+    pub(crate) fn global_types(mut self) -> Result<Vec<DataType>, CompileError> {
+        self.check_globals()?;
+        Ok(self.global_types)
+    }
+
+    /// Type of the visible local, global or constant with span of its declaration.
+    fn lookup_variable(&self, name: &str) -> Option<(DataType, Span)> {
+        if let Some(&variable) = self.scope.get_with_span(name) {
+            return Some(variable);
+        }
+
+        let ast = self.ast;
+        if let Some(slot) = ast.get_global_slot(name) {
+            let data_type = self.global_types.get(slot as usize)?;
+            return Some((*data_type, ast.get_globals()[slot as usize].span));
+        }
+
+        ast.get_constants()[..self.visible_constants]
+            .iter()
+            .find(|constant| constant.name == name)
+            .map(|constant| (constant.data_type, constant.span))
+    }
+
+    /// Local variables and parameters must not shadow globals and constants of the module.
+    fn declare_variable(
+        &mut self,
+        name: &str,
+        data_type: DataType,
+        span: Span,
+    ) -> Result<(), CompileError> {
+        let module_variable = if self.ast.get_global_slot(name).is_some() {
+            Some("global")
+        } else if self.ast.get_constant(name).is_some() {
+            Some("constant")
+        } else {
+            None
+        };
+
+        if let Some(kind) = module_variable {
+            return Err(CompileError::variable_redeclaration_at(name, span)
+                .add_note(format!("{} is a {} of the module", name, kind)));
+        }
+
+        self.scope.insert_new(name.to_string(), data_type, span)?;
+        self.record_variable(name, span);
+        Ok(())
+    }
+
+    /// Checks statements outside of any function, declared variables stay in the scope.
+    /// Returns type of the last statement if it is an expression.
+    pub(crate) fn check_session_statements(
+        &mut self,
+        statements: &[Statement],
+    ) -> Result<Option<DataType>, CompileError> {
+        let Some((last, rest)) = statements.split_last() else {
+            return Ok(None);
+        };
+
+        self.check_statements(rest, DataType::Void)?;
+
+        if let StatementKind::Expr(expr) = &last.kind {
+            return self.check_expr(expr).map(Some);
+        }
+
+        self.check_statement(last, DataType::Void)?;
+        Ok(None)
+    }
+
+    pub(crate) fn into_scope(self) -> DataTypeScope {
+        self.scope
+    }
+
     fn check_functions(&mut self) -> Result<(), CompileError> {
         for slot in 0..self.ast.function_count() {
-            self.scope.enter();
-
-            self.ast
-                .get_function_signiture_by_slot(slot)
-                .unwrap()
-                .params
-                .iter()
-                .try_for_each(|param| {
-                    self.scope
-                        .insert_new(param.name.clone(), param.data_type.clone(), param.span)
-                })?;
-
-            let statements = self.ast.get_function_body_by_slot(slot).unwrap();
-            let return_type = self
-                .ast
-                .get_function_signiture_by_slot(slot)
-                .unwrap()
-                .return_type;
-
-            self.check_statements(statements, return_type)?;
-
-            self.scope.exit();
+            self.check_function(slot)?;
         }
 
         Ok(())
     }
 
+    fn check_function(&mut self, slot: u32) -> Result<(), CompileError> {
+        let ast = self.ast;
+        let signiture = ast.get_function_signiture_by_slot(slot).unwrap();
+        if let Some((name, _)) = ast.get_function_map().iter().find(|(_, s)| **s == slot) {
+            self.symbols.push(Symbol::new(
+                name,
+                signiture.span,
+                SymbolKind::Function {
+                    signiture: signiture.clone().into(),
+                    definition: signiture.span,
+                },
+            ));
+        }
+
+        self.scope.enter();
+        let result = self.check_function_body(slot);
+        self.scope.exit();
+
+        result
+    }
+
+    fn check_function_body(&mut self, slot: u32) -> Result<(), CompileError> {
+        let ast = self.ast;
+        let signiture = ast.get_function_signiture_by_slot(slot).unwrap();
+        for param in signiture.params.iter() {
+            self.declare_variable(&param.name, param.data_type, param.span)?;
+        }
+
+        let statements = ast.get_function_body_by_slot(slot).unwrap();
+        self.check_statements(statements, signiture.return_type)
+    }
+
+    fn record_variable(&mut self, name: &str, span: Span) {
+        let Some((data_type, definition)) = self.lookup_variable(name) else {
+            return;
+        };
+
+        self.symbols.push(Symbol::new(
+            name,
+            span,
+            SymbolKind::Variable {
+                data_type,
+                definition,
+            },
+        ));
+    }
+
     fn check_statements(
         &mut self,
         stmts: &[Statement],
@@ -119,18 +444,25 @@ impl<'a> TypeChecker<'a> {
                     expr_type
                 };
 
-                self.scope
-                    .insert_new(variable.clone(), data_type, statement.span)?;
+                self.declare_variable(variable, data_type, statement.span)?;
             }
 
             StatementKind::VarAssign { variable, value } => {
-                let Some(&data_type) = self.scope.get(&variable) else {
+                let Some((data_type, _)) = self.lookup_variable(variable) else {
                     return Err(CompileError::variable_not_found_at(
                         variable.clone(),
                         statement.span,
                     ));
                 };
 
+                if self.scope.get(variable).is_none() && self.ast.get_constant(variable).is_some() {
+                    return Err(CompileError::constant_assignment_at(
+                        variable,
+                        statement.span,
+                    ));
+                }
+
+                self.record_variable(variable, statement.span);
                 let value_type = self.check_expr(&value)?;
 
                 if value_type != data_type {
@@ -160,6 +492,14 @@ impl<'a> TypeChecker<'a> {
             }
 
             StatementKind::Return(expr) => {
+                // operands of the enclosing expression would be left on the stack
+                if self.block_depth > 0 {
+                    return Err(CompileError::other_at(
+                        "'return' can not be used inside a block expression",
+                        statement.span,
+                    ));
+                }
+
                 let expr_type = self.check_expr(&expr)?;
                 if expr_type != return_type {
                     return Err(CompileError::wrong_type_at(
@@ -174,17 +514,28 @@ impl<'a> TypeChecker<'a> {
         Ok(())
     }
 
-    fn check_expr(&self, expr: &Expr) -> Result<DataType, CompileError> {
+    fn check_expr(&mut self, expr: &Expr) -> Result<DataType, CompileError> {
         match &expr.kind {
             ExprKind::Var(ident) => {
-                let Some(data_type) = self.scope.get(&ident) else {
+                let Some((data_type, _)) = self.lookup_variable(ident) else {
                     return Err(CompileError::variable_not_found_at(
                         ident.clone(),
                         expr.span,
                     ));
                 };
 
-                Ok(data_type.clone())
+                self.record_variable(ident, expr.span);
+                Ok(data_type)
+            }
+            ExprKind::ForeignConstant { module_name, name } => {
+                let (_, constant) = find_foreign_constant(
+                    self.ast,
+                    self.dependencies,
+                    module_name,
+                    name,
+                    expr.span,
+                )?;
+                Ok(constant.value.get_type())
             }
             ExprKind::Literal(lit) => Ok(lit.get_type()),
             ExprKind::FunctionCall { func_name, args } => {
@@ -209,19 +560,96 @@ impl<'a> TypeChecker<'a> {
 
             ExprKind::BinaryOp(op, lhs, rhs) => self.check_binary_op_expr(expr, op, lhs, rhs),
             ExprKind::UnaryOp(op, expr) => self.check_unary_op_expr(expr, op),
+            ExprKind::Cast {
+                expr: operand,
+                data_type,
+            } => {
+                let operand_type = self.check_expr(operand)?;
+                if !operand_type.can_cast_to(*data_type) {
+                    return Err(CompileError::invalid_cast_at(
+                        operand_type,
+                        *data_type,
+                        expr.span,
+                    ));
+                }
+
+                Ok(*data_type)
+            }
+
+            ExprKind::If {
+                condition,
+                if_block,
+                else_block,
+            } => self.check_if_expr(condition, if_block, else_block),
+
+            ExprKind::Block { statements, result } => {
+                self.scope.enter();
+                self.block_depth += 1;
+                let result = self
+                    .check_statements(statements, DataType::Void)
+                    .and_then(|_| self.check_expr(result));
+                self.block_depth -= 1;
+                self.scope.exit();
+
+                result
+            }
+        }
+    }
+
+    /// Both branches must have the same type, it is the type of the expression.
+    fn check_if_expr(
+        &mut self,
+        condition: &Expr,
+        if_block: &Expr,
+        else_block: &Expr,
+    ) -> Result<DataType, CompileError> {
+        let cond = self.check_expr(condition)?;
+        if !cond.is_bool() {
+            return Err(CompileError::wrong_type_at(
+                DataType::Bool,
+                cond,
+                condition.span,
+            ));
         }
+
+        let if_type = self.check_expr(if_block)?;
+        let else_type = self.check_expr(else_block)?;
+        if if_type != else_type {
+            let error = CompileError::wrong_type_at(if_type, else_type, value_span(else_block));
+            return Err(error.add_note(format!(
+                "the 'if' branch has type {}, both branches must have the same type",
+                if_type
+            )));
+        }
+
+        Ok(if_type)
     }
 
     fn check_call_expr(
-        &self,
+        &mut self,
         expr: &Expr,
         func_name: &str,
         args: &[Expr],
     ) -> Result<DataType, CompileError> {
-        let Some(signiture) = self.ast.get_function_signiture(&func_name) else {
-            return Err(CompileError::function_not_found_at(func_name, expr.span));
+        let ast = self.ast;
+        let Some(signiture) = ast.get_function_signiture(func_name) else {
+            return match ast.get_imported_function_module(func_name) {
+                Some(module_name) => {
+                    self.check_foreign_call_expr(expr, &module_name, func_name, args)
+                }
+                None => Err(CompileError::function_not_found_at(func_name, expr.span)),
+            };
         };
 
+        self.symbols.push(Symbol::new(
+            func_name,
+            expr.span,
+            SymbolKind::Function {
+                signiture: signiture.clone().into(),
+                definition: signiture.span,
+            },
+        ));
+
         let arg_types: Vec<(DataType, Span)> = args
             .iter()
             .map(|arg| self.check_expr(arg).map(|dt| (dt, arg.span)))
@@ -233,19 +661,37 @@ impl<'a> TypeChecker<'a> {
     }
 
     fn check_foreign_call_expr(
-        &self,
+        &mut self,
         expr: &Expr,
         module_name: &str,
         func_name: &str,
         args: &[Expr],
     ) -> Result<DataType, CompileError> {
-        let signiture = self
-            .dependencies
-            .get(module_name)
+        let module_name = self.ast.resolve_module_name(module_name, expr.span)?;
+        let dependencies = self.dependencies;
+        let signiture = dependencies
+            .get(&module_name)
             .ok_or_else(|| CompileError::module_not_found_at(module_name.clone(), expr.span))?
             .get_function_signiture(&func_name)
             .ok_or_else(|| CompileError::function_not_found_at(func_name.clone(), expr.span))?;
 
+        if !signiture.visibility.is_public() {
+            return Err(CompileError::private_function_at(
+                module_name,
+                func_name,
+                expr.span,
+            ));
+        }
+
+        self.symbols.push(Symbol::new(
+            func_name,
+            expr.span,
+            SymbolKind::ForeignFunction {
+                module_name,
+                signiture: signiture.clone(),
+            },
+        ));
+
         let arg_types: Vec<(DataType, Span)> = args
             .iter()
             .map(|arg| self.check_expr(arg).map(|dt| (dt, arg.span)))
@@ -257,44 +703,21 @@ impl<'a> TypeChecker<'a> {
     }
 
     fn check_builtin_call_expr(
-        &self,
+        &mut self,
         expr: &Expr,
         function: &BuiltinFunction,
         args: &[Expr],
     ) -> Result<DataType, CompileError> {
-        let arg_types: Vec<DataType> = args
+        let arg_types: Vec<(DataType, Span)> = args
             .iter()
-            .map(|arg| self.check_expr(arg))
-            .collect::<Result<Vec<DataType>, CompileError>>()?;
-
-        match function {
-            BuiltinFunction::Print => {
-                // Print can take any type of arguments
-                Ok(DataType::Void)
-            }
-            BuiltinFunction::Assert => {
-                // Assert arguments must be bool
-                for arg_type in arg_types {
-                    if arg_type != DataType::Bool {
-                        return Err(CompileError::wrong_type_at(
-                            DataType::Bool,
-                            arg_type,
-                            expr.span,
-                        ));
-                    }
-                }
+            .map(|arg| self.check_expr(arg).map(|dt| (dt, arg.span)))
+            .collect::<Result<Vec<(DataType, Span)>, CompileError>>()?;
 
-                Ok(DataType::Void)
-            }
-            BuiltinFunction::Format => {
-                // Format can take any type of arguments
-                Ok(DataType::String)
-            }
-        }
+        function.check_argument_types(&arg_types, expr.span)
     }
 
     fn check_method_call_expr(
-        &self,
+        &mut self,
         expr: &Expr,
         callee: &Expr,
         method_name: &str,
@@ -302,16 +725,26 @@ impl<'a> TypeChecker<'a> {
     ) -> Result<DataType, CompileError> {
         let callee_type = self.check_expr(callee)?;
 
-        let arg_types: Vec<DataType> = args
+        let arg_types = args
             .iter()
-            .map(|arg| self.check_expr(arg))
-            .collect::<Result<Vec<DataType>, CompileError>>()?;
+            .map(|arg| self.check_expr(arg).map(|dt| (dt, arg.span)))
+            .collect::<Result<Vec<(DataType, Span)>, CompileError>>()?;
 
-        callee_type.check_method_call(method_name, expr.span, &arg_types)
+        let return_type = callee_type.check_method_call(method_name, expr.span, &arg_types)?;
+        self.symbols.push(Symbol::new(
+            method_name,
+            expr.span,
+            SymbolKind::Method {
+                callee_type,
+                return_type,
+            },
+        ));
+
+        Ok(return_type)
     }
 
     fn check_binary_op_expr(
-        &self,
+        &mut self,
         expr: &Expr,
         op: &BinaryOp,
         lhs: &Expr,
@@ -320,40 +753,83 @@ impl<'a> TypeChecker<'a> {
         let lhs_type = self.check_expr(&lhs)?;
         let rhs_type = self.check_expr(&rhs)?;
         match op {
-            bin_op_pat!(NUMERIC) => match (lhs_type, rhs_type) {
-                (DataType::Int, DataType::Int) => Ok(DataType::Int),
-                (DataType::Float, DataType::Float) => Ok(DataType::Float),
-                (DataType::Int | DataType::Float, _) => {
-                    return Err(CompileError::wrong_type_at(lhs_type, rhs_type, expr.span))
+            BinaryOp::Add => match (lhs_type, rhs_type) {
+                (lhs_type, rhs_type) if lhs_type == rhs_type && lhs_type.is_numeric() => {
+                    Ok(lhs_type)
                 }
-                (_, DataType::Int | DataType::Float) => {
-                    return Err(CompileError::wrong_type_at(rhs_type, lhs_type, expr.span))
-                }
-                _ => {
-                    return Err(CompileError::wrong_type_at(
-                        DataType::Int,
-                        lhs_type,
-                        expr.span,
-                    ))
+                (DataType::String, DataType::String) => Ok(DataType::String),
+                _ => Err(operand_error(
+                    op,
+                    (lhs, lhs_type),
+                    (rhs, rhs_type),
+                    NUMERIC_AND_STRING,
+                    "two numbers of the same type or two strings",
+                )),
+            },
+
+            BinaryOp::Mul => match (lhs_type, rhs_type) {
+                (lhs_type, rhs_type) if lhs_type == rhs_type && lhs_type.is_numeric() => {
+                    Ok(lhs_type)
                 }
+                (DataType::String, DataType::Int) => Ok(DataType::String),
+                _ => Err(operand_error(
+                    op,
+                    (lhs, lhs_type),
+                    (rhs, rhs_type),
+                    NUMERIC_AND_STRING,
+                    "two numbers of the same type, or a string and an int",
+                )),
             },
 
-            bin_op_pat!(NUMERIC_COMPARISON) => match (lhs_type, rhs_type) {
-                (DataType::Int, DataType::Int) => Ok(DataType::Bool),
-                (DataType::Float, DataType::Float) => Ok(DataType::Bool),
-                (DataType::Int | DataType::Float, _) => {
-                    return Err(CompileError::wrong_type_at(lhs_type, rhs_type, expr.span))
+            BinaryOp::Sub | BinaryOp::Div | BinaryOp::Modulo => match (lhs_type, rhs_type) {
+                (lhs_type, rhs_type) if lhs_type == rhs_type && lhs_type.is_numeric() => {
+                    Ok(lhs_type)
                 }
-                (_, DataType::Int | DataType::Float) => {
-                    return Err(CompileError::wrong_type_at(rhs_type, lhs_type, expr.span))
+                _ => Err(operand_error(
+                    op,
+                    (lhs, lhs_type),
+                    (rhs, rhs_type),
+                    NUMERIC,
+                    "two numbers of the same type",
+                )),
+            },
+
+            bin_op_pat!(BITWISE) => match (lhs_type, rhs_type) {
+                (lhs_type, rhs_type) if lhs_type == rhs_type && lhs_type.is_integer() => {
+                    Ok(lhs_type)
                 }
-                _ => {
-                    return Err(CompileError::wrong_type_at(
-                        DataType::Int,
-                        lhs_type,
-                        expr.span,
-                    ))
+                _ => Err(operand_error(
+                    op,
+                    (lhs, lhs_type),
+                    (rhs, rhs_type),
+                    INTEGER,
+                    "two integers of the same type",
+                )),
+            },
+
+            bin_op_pat!(SHIFT) => match (lhs_type, rhs_type) {
+                (lhs_type, DataType::Int) if lhs_type.is_integer() => Ok(lhs_type),
+                _ => Err(operand_error(
+                    op,
+                    (lhs, lhs_type),
+                    (rhs, rhs_type),
+                    INTEGER,
+                    "an integer shifted by an int",
+                )),
+            },
+
+            bin_op_pat!(NUMERIC_COMPARISON) => match (lhs_type, rhs_type) {
+                (lhs_type, rhs_type) if lhs_type == rhs_type && lhs_type.is_numeric() => {
+                    Ok(DataType::Bool)
                 }
+                (DataType::String, DataType::String) => Ok(DataType::Bool),
+                _ => Err(operand_error(
+                    op,
+                    (lhs, lhs_type),
+                    (rhs, rhs_type),
+                    NUMERIC_AND_STRING,
+                    "two numbers of the same type or two strings",
+                )),
             },
 
             bin_op_pat!(COMPARISON) => Ok(DataType::Bool),
@@ -380,7 +856,7 @@ impl<'a> TypeChecker<'a> {
         }
     }
 
-    fn check_unary_op_expr(&self, expr: &Expr, op: &UnaryOp) -> Result<DataType, CompileError> {
+    fn check_unary_op_expr(&mut self, expr: &Expr, op: &UnaryOp) -> Result<DataType, CompileError> {
         let expr_type = self.check_expr(&expr)?;
         match op {
             UnaryOp::Not => {
@@ -395,9 +871,20 @@ impl<'a> TypeChecker<'a> {
                 Ok(DataType::Bool)
             }
 
+            UnaryOp::BitNot => {
+                if !expr_type.is_integer() {
+                    return Err(CompileError::wrong_type_at(
+                        DataType::Int,
+                        expr_type,
+                        expr.span,
+                    ));
+                }
+
+                Ok(expr_type)
+            }
+
             UnaryOp::Negate => match expr_type {
-                DataType::Int => Ok(DataType::Int),
-                DataType::Float => Ok(DataType::Float),
+                DataType::Int | DataType::Int64 | DataType::Float => Ok(expr_type),
                 _ => {
                     return Err(CompileError::wrong_type_at(
                         DataType::Int,
@@ -409,3 +896,401 @@ impl<'a> TypeChecker<'a> {
         }
     }
 }
+
+/// Span of the expression giving the value, the last expression of blocks.
+fn value_span(expr: &Expr) -> Span {
+    match &expr.kind {
+        ExprKind::Block { result, .. } => value_span(result),
+        _ => expr.span,
+    }
+}
+
+const INTEGER: &[DataType] = &[DataType::Int, DataType::Int64, DataType::Byte];
+
+const NUMERIC: &[DataType] = &[
+    DataType::Int,
+    DataType::Int64,
+    DataType::Byte,
+    DataType::Float,
+];
+
+const NUMERIC_AND_STRING: &[DataType] = &[
+    DataType::Int,
+    DataType::Int64,
+    DataType::Byte,
+    DataType::Float,
+    DataType::String,
+];
+
+/// Error for operands the binary operator is not defined for. When the left operand has one of
+/// the `accepted` types, the right operand is reported, it must have the same type except for
+/// the repeat count of `String * Int` and the `Int` amount of shifts. `defined_for` describes the valid operands.
+fn operand_error(
+    op: &BinaryOp,
+    (lhs, lhs_type): (&Expr, DataType),
+    (rhs, rhs_type): (&Expr, DataType),
+    accepted: &[DataType],
+    defined_for: &str,
+) -> CompileError {
+    let error = if accepted.contains(&lhs_type) {
+        let expected = match (op, lhs_type) {
+            (BinaryOp::Mul, DataType::String) => DataType::Int,
+            (bin_op_pat!(SHIFT), _) => DataType::Int,
+            _ => lhs_type,
+        };
+        CompileError::wrong_type_at(expected, rhs_type, rhs.span)
+    } else {
+        let expected = if accepted.contains(&rhs_type) {
+            rhs_type
+        } else {
+            accepted[0]
+        };
+        CompileError::wrong_type_at(expected, lhs_type, lhs.span)
+    };
+
+    let error = error.add_note(format!(
+        "'{}' is defined for {}",
+        binary_op_symbol(*op),
+        defined_for
+    ));
+
+    match (lhs_type, rhs_type) {
+        // the shifted operand keeps its type, the amount is always an int
+        _ if matches!(op, bin_op_pat!(SHIFT)) => error,
+        // ints are never promoted to floats implicitly
+        (DataType::Int, DataType::Float) | (DataType::Float, DataType::Int) => {
+            error.add_note("convert the int operand with 'as Float' or the float one with 'as Int'")
+        }
+        // nor are narrower integers widened
+        (lhs_type, rhs_type) if lhs_type.is_numeric() && rhs_type.is_numeric() => {
+            error.add_note(format!(
+                "convert one of the operands with 'as {}'",
+                data_type_keyword(lhs_type)
+            ))
+        }
+        _ => error,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::errors::CompileErrorKind;
+
+    fn check(source: &str) -> Result<(), CompileError> {
+        let ast = Ast::from_string(source, 0)?;
+        TypeChecker::new(&ast, &HashMap::new()).check()
+    }
+
+    #[test]
+    fn main_signiture() {
+        assert!(check("Void main() {\n}").is_ok());
+        assert!(check("Int main() {\n return 0\n}").is_ok());
+
+        let error = check("Void main(Int x) {\n}").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::InvalidMainSigniture));
+        assert_eq!(error.span.map(|s| (s.lo, s.hi)), Some((10, 15)));
+
+        let error = check("String main() {\n return ''\n}").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::InvalidMainSigniture));
+    }
+
+    #[test]
+    fn arg_builtins() {
+        assert!(check("Void f() {\n let s = arg(arg_count() - 1)\n}").is_ok());
+        assert!(check("Void f() {\n let s = arg('0')\n}").is_err());
+        assert!(check("Void f() {\n let s = arg_count(1)\n}").is_err());
+        assert!(check("Void f() {\n Int s = arg(0)\n}").is_err());
+    }
+
+    #[test]
+    fn assert_eq_arguments() {
+        assert!(check("Void f() {\n assert_eq(1 + 1, 2)\n}").is_ok());
+        assert!(check("Void f() {\n assert_eq(1, 'one')\n}").is_err());
+        assert!(check("Void f() {\n assert_eq(1)\n}").is_err());
+    }
+
+    #[test]
+    fn private_functions_are_not_exported() {
+        let source = "pub Int add(Int a, Int b) {\n return a + b\n}\nInt helper() {\n return 0\n}";
+        let math = Module::from_string(source, 0).unwrap();
+        let dependencies = HashMap::from([("math".to_string(), math)]);
+        let check = |source: &str| {
+            let ast = Ast::from_string(source, 0)?;
+            TypeChecker::new(&ast, &dependencies).check()
+        };
+
+        assert!(check("import math\nInt f() {\n return math:add(1, 2)\n}").is_ok());
+
+        let error = check("import math\nInt f() {\n return math:helper()\n}").unwrap_err();
+        assert!(matches!(
+            error.kind,
+            CompileErrorKind::PrivateFunction { .. }
+        ));
+        assert_eq!(error.message, "function math:helper is private");
+        assert_eq!(error.span.map(|s| (s.lo, s.hi)), Some((30, 43)));
+
+        let error = check("import math:{helper}\n").unwrap_err();
+        assert!(matches!(
+            error.kind,
+            CompileErrorKind::PrivateFunction { .. }
+        ));
+
+        let error = check("pub import math\nInt add() {\n return 0\n}").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::ImportClash(_)));
+        assert!(check("pub import math\nInt helper() {\n return 0\n}").is_ok());
+    }
+
+    #[test]
+    fn import_clashes() {
+        let math = Module::from_string("pub Int add(Int a, Int b) {\n return a + b\n}", 0).unwrap();
+        let dependencies = HashMap::from([
+            ("math".to_string(), math.clone()),
+            ("numbers".to_string(), math),
+        ]);
+        let check = |source: &str| {
+            let ast = Ast::from_string(source, 0)?;
+            TypeChecker::new(&ast, &dependencies).check()
+        };
+
+        assert!(check("import math:{add}\nInt f() {\n return add(1, 2)\n}").is_ok());
+        assert!(check("import math as m\nInt f() {\n return m:add(1, 2)\n}").is_ok());
+
+        let source = "import math:{add}\nInt add() {\n return 0\n}";
+        let error = check(source).unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::ImportClash(_)));
+        assert_eq!(error.span.map(|s| (s.lo, s.hi)), Some((13, 16)));
+
+        let error = check("import math:{add}\nimport numbers:{add}\n").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::ImportClash(_)));
+
+        let error = check("import math\nimport numbers as math\n").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::ImportClash(_)));
+
+        let error = check("import math:{sub}\n").unwrap_err();
+        assert!(matches!(
+            error.kind,
+            CompileErrorKind::UnknownForeignFunction { .. }
+        ));
+
+        let error = check("import math as m\nInt f() {\n return math:add(1, 2)\n}");
+        assert!(error.is_ok(), "full path stays callable");
+    }
+
+    #[test]
+    fn string_operators() {
+        assert!(check("String f(String a) {\n return a + 'b' * 3\n}").is_ok());
+        assert!(check("Bool f(String a) {\n return a < 'b' && a >= 'a'\n}").is_ok());
+
+        let error = check("Void f() {\n let x = 'a' + 1\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected string, found int");
+        assert_eq!(error.span.map(|s| (s.lo, s.hi)), Some((26, 27)));
+        assert_eq!(
+            error.notes,
+            vec!["'+' is defined for two numbers of the same type or two strings"]
+        );
+
+        let error = check("Void f() {\n let x = 2 * 'a'\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected int, found string");
+        assert_eq!(error.span.map(|s| (s.lo, s.hi)), Some((24, 27)));
+
+        let error = check("Void f() {\n let x = 'a' - 'b'\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected int, found string");
+        assert_eq!(error.span.map(|s| (s.lo, s.hi)), Some((20, 23)));
+
+        assert!(check("Void f() {\n let x = 'a' * 1.0\n}").is_err());
+        assert!(check("Void f() {\n let x = true < false\n}").is_err());
+    }
+
+    #[test]
+    fn casts() {
+        assert!(check("Float f(Int a) {\n return a as Float * 1.5\n}").is_ok());
+        assert!(check("String f(Float a) {\n return -a as Int as String\n}").is_ok());
+        assert!(check("const String A = true as String\n").is_ok());
+
+        let error = check("Void f() {\n let x = true as Float\n}").unwrap_err();
+        assert!(matches!(
+            error.kind,
+            CompileErrorKind::InvalidCast {
+                from: DataType::Bool,
+                to: DataType::Float
+            }
+        ));
+        assert_eq!(error.span.map(|s| (s.lo, s.hi)), Some((20, 33)));
+
+        let error = check("Void f() {\n let x = '1' as Int\n}").unwrap_err();
+        assert_eq!(error.message, "cannot cast string to int");
+        assert_eq!(
+            error.notes,
+            vec!["use 'parse_int()' to convert a string to an int"]
+        );
+
+        let error = check("Void f() {\n let x = 1 + 2.0\n}").unwrap_err();
+        assert_eq!(error.notes.len(), 2);
+    }
+
+    #[test]
+    fn integer_types() {
+        assert!(check("Int64 f(Int64 a) {\n return -a * 3i64 % a\n}").is_ok());
+        assert!(check("Bool f(Byte a) {\n return a + 1u8 < 255u8\n}").is_ok());
+        assert!(check("const Byte MASK = 15 as Byte\n").is_ok());
+
+        let error = check("Int64 f(Int64 a) {\n return a + 1\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected int64, found int");
+        assert_eq!(
+            error.notes,
+            vec![
+                "'+' is defined for two numbers of the same type or two strings",
+                "convert one of the operands with 'as Int64'"
+            ]
+        );
+
+        let error = check("Byte f(Byte a) {\n return -a\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected int, found byte");
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        assert!(
+            check("Bool f(Int flags) {\n return (flags & 4) != 0 || (flags ^ 1) == ~flags\n}")
+                .is_ok()
+        );
+        // comparisons bind tighter, `flags & (4 != 0)`
+        let error = check("Bool f(Int flags) {\n return flags & 4 != 0\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected int, found bool");
+        assert!(check("Int64 f(Int64 a) {\n return a << 3 | a >> 1\n}").is_ok());
+
+        let error = check("Int f(Int a) {\n return a & true\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected int, found bool");
+        assert_eq!(
+            error.notes,
+            vec!["'&' is defined for two integers of the same type"]
+        );
+
+        let error = check("Byte f(Byte a) {\n return a << 1u8\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected int, found byte");
+        assert_eq!(
+            error.notes,
+            vec!["'<<' is defined for an integer shifted by an int"]
+        );
+
+        let error = check("Float f(Float a) {\n return ~a\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected int, found float");
+    }
+
+    #[test]
+    fn if_and_block_expressions() {
+        assert!(
+            check("Int f(Bool a) {\n return if a { 1 } else if !a { 2 } else { 3 }\n}").is_ok()
+        );
+        assert!(
+            check("Int f(Int a) {\n let b = {\n let c = a * 2\n c + 1\n }\n return b\n}").is_ok()
+        );
+        assert!(check("const Int A = if true { 1 } else { 2 }\n").is_ok());
+
+        let error = check("Int f(Bool a) {\n return if a { 1 } else { 'one' }\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected int, found string");
+        assert_eq!(
+            error.notes,
+            vec!["the 'if' branch has type int, both branches must have the same type"]
+        );
+
+        let error = check("Int f(Int a) {\n return if a { 1 } else { 2 }\n}").unwrap_err();
+        assert_eq!(error.message, "wrong type: expected bool, found int");
+
+        let error =
+            check("Int f(Bool a) {\n let b = {\n if a {\n return 1\n }\n 2\n }\n return b\n}")
+                .unwrap_err();
+        assert_eq!(
+            error.message,
+            "'return' can not be used inside a block expression"
+        );
+
+        // variables of a block are not visible after it
+        let error = check("Int f() {\n let a = {\n let b = 1\n b\n }\n return b\n}").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::VariableNotFound(_)));
+    }
+
+    #[test]
+    fn constants_and_globals() {
+        assert!(check("const Int A = 2\nconst Int B = A * -3\nInt f() {\n return B\n}").is_ok());
+        assert!(check("let count = 0\nVoid f() {\n count = count + 1\n}").is_ok());
+
+        let error = check("const Int A = B\nconst Int B = 1\n").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::VariableNotFound(_)));
+
+        let error = check("const String A = 1\n").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::WrongType { .. }));
+        assert_eq!(error.span.map(|s| (s.lo, s.hi)), Some((17, 18)));
+
+        let error = check("const Int A = 1 / 0\n").unwrap_err();
+        assert_eq!(
+            error.message,
+            "invalid value of constant A: division by zero"
+        );
+
+        // repetition is evaluated at compile time too, a huge result is an error, not an abort
+        let error = check("const String A = 'ab' * 2147483647\n").unwrap_err();
+        assert!(error
+            .message
+            .starts_with("invalid value of constant A: cannot repeat a string"));
+
+        let error = check("const Int A = 1\nVoid f() {\n A = 2\n}").unwrap_err();
+        assert!(matches!(
+            error.kind,
+            CompileErrorKind::ConstantAssignment(_)
+        ));
+
+        let error = check("let g = 1\nVoid f(Int g) {\n}").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::VarRedeclaration(_)));
+
+        let error = check("let a = b\nlet b = 1\n").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::VariableNotFound(_)));
+    }
+
+    #[test]
+    fn foreign_constants() {
+        let source = "pub const Int MAX = 10\nconst Int MIN = 0\n";
+        let limits = Module::from_string(source, 0).unwrap();
+        let dependencies = HashMap::from([("limits".to_string(), limits)]);
+        let check = |source: &str| {
+            let ast = Ast::from_string(source, 0)?;
+            TypeChecker::new(&ast, &dependencies).check()
+        };
+
+        assert!(check("import limits\nconst Int TWICE = limits:MAX * 2\n").is_ok());
+
+        let error = check("import limits\nInt f() {\n return limits:MIN\n}").unwrap_err();
+        assert!(matches!(
+            error.kind,
+            CompileErrorKind::PrivateConstant { .. }
+        ));
+
+        let error = check("import limits\nString f() {\n return limits:MAX\n}").unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::WrongType { .. }));
+
+        let error = check("import limits\nInt f() {\n return limits:AVG\n}").unwrap_err();
+        assert!(matches!(
+            error.kind,
+            CompileErrorKind::UnknownForeignConstant { .. }
+        ));
+    }
+
+    #[test]
+    fn symbols_are_recorded_after_errors() {
+        let source = "Int add(Int a) {\n return a + b\n}\nVoid main() {\n let x = add(1)\n}";
+        let ast = Ast::from_string(source, 0).unwrap();
+        let (result, symbols) = TypeChecker::new(&ast, &HashMap::new()).check_with_symbols();
+        assert!(result.is_err());
+
+        let x = symbols.iter().find(|s| s.name == "x").unwrap();
+        assert_eq!(x.kind.describe(&x.name), "int x");
+
+        let call = symbols.iter().rfind(|s| s.name == "add").unwrap();
+        assert_eq!(call.kind.describe(&call.name), "int add(int a)");
+        assert!(matches!(
+            call.kind,
+            SymbolKind::Function { definition, .. } if definition.lo == 0
+        ));
+    }
+}
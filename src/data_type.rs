@@ -1,11 +1,15 @@
 use std::fmt::Display;
 
-use crate::{diagnostics::Span, errors::CompileError};
+use crate::{diagnostics::Span, errors::CompileError, method::Method};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DataType {
     Void,
     Int,
+    /// 64-bit signed integer
+    Int64,
+    /// 8-bit unsigned integer
+    Byte,
     Float,
     Bool,
     String,
@@ -16,27 +20,43 @@ impl DataType {
         matches!(self, Self::Bool)
     }
 
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Self::Int | Self::Int64 | Self::Byte)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || *self == Self::Float
+    }
+
+    /// Whether `value as target` is a valid cast, see `Value::cast` for the conversions.
+    pub fn can_cast_to(&self, target: DataType) -> bool {
+        match (self, target) {
+            (DataType::Void, _) | (_, DataType::Void) => false,
+            (from, to) if *from == to => true,
+            (from, to) if from.is_numeric() && to.is_numeric() => true,
+            (from, DataType::String) => from.is_numeric() || from.is_bool(),
+            _ => false,
+        }
+    }
+
+    /// Names of methods that can be called on a value of this type.
+    pub fn method_names(&self) -> Vec<&'static str> {
+        Method::of_type(*self).map(|method| method.name).collect()
+    }
+
+    /// Checks the arguments of method `method_name` called on a value of this type
+    /// and returns the type of the result.
     pub fn check_method_call(
         &self,
         method_name: &str,
         span: Span,
-        args: &[DataType],
+        args: &[(DataType, Span)],
     ) -> Result<DataType, CompileError> {
-        match (self, method_name, args) {
-            (DataType::String, "length", args) => {
-                if !args.is_empty() {
-                    return Err(CompileError::wrong_number_of_arguments_at(
-                        0,
-                        args.len(),
-                        span,
-                    ));
-                }
-
-                Ok(DataType::Int)
-            }
-
-            _ => Err(CompileError::method_not_found_at(*self, method_name, span)),
-        }
+        let Some(method) = Method::find(*self, method_name) else {
+            return Err(CompileError::method_not_found_at(*self, method_name, span));
+        };
+
+        method.check_argument_types(args, span)
     }
 }
 
@@ -45,6 +65,8 @@ impl Display for DataType {
         match self {
             DataType::Void => write!(f, "void"),
             DataType::Int => write!(f, "int"),
+            DataType::Int64 => write!(f, "int64"),
+            DataType::Byte => write!(f, "byte"),
             DataType::Float => write!(f, "float"),
             DataType::Bool => write!(f, "bool"),
             DataType::String => write!(f, "string"),
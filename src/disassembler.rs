@@ -0,0 +1,282 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use crate::{
+    bytecode::BytecodeHeader,
+    formatter::{data_type_keyword, format_literal},
+    function::{FunctionSigniture, FunctionSource},
+    global::Global,
+    instruction::Instruction,
+    module::Module,
+};
+
+/// Returns human readable listing of the module: header, constants, globals, function signitures
+/// and numbered instructions, the initializer of globals is the last section.
+/// Jump targets are labeled and names of foreign functions are resolved from `dependencies`,
+/// unknown ones are printed as slots. The listing is a valid input of the assembler.
+pub fn disassemble(module: &Module, dependencies: &HashMap<String, Module>) -> String {
+    let sources = module.get_sources();
+    let names = function_names(module);
+    let header = BytecodeHeader::new(module.get_main_slot(), sources.len() as u32);
+
+    let mut output = String::new();
+    writeln!(output, "# version {}", header.version).unwrap();
+    match header.main_slot {
+        Some(slot) => writeln!(output, "# main slot {}", slot).unwrap(),
+        None => writeln!(output, "# no main function").unwrap(),
+    }
+
+    // corrupted bytecode, the verifier reports it after the listing
+    let mut invalid: Vec<_> = module
+        .get_function_map()
+        .iter()
+        .filter(|(_, slot)| **slot as usize >= sources.len())
+        .collect();
+    invalid.sort();
+    for (name, slot) in invalid {
+        writeln!(output, "# function {} has <invalid slot {}>", name, slot).unwrap();
+    }
+
+    if !module.get_constants().is_empty() || !module.get_globals().is_empty() {
+        writeln!(output).unwrap();
+    }
+
+    for constant in module.get_constants() {
+        writeln!(
+            output,
+            "const {}{} {} {}",
+            constant.visibility.prefix(),
+            data_type_keyword(constant.value.get_type()),
+            constant.name,
+            format_literal(&constant.value)
+        )
+        .unwrap();
+    }
+
+    for global in module.get_globals() {
+        let data_type = data_type_keyword(global.data_type);
+        writeln!(output, "global {} {}", data_type, global.name).unwrap();
+    }
+
+    let globals = module.get_globals();
+    for (slot, source) in sources.iter().enumerate() {
+        let signiture = module.get_function_signiture_by_slot(slot as u32);
+        let function = match (names[slot], signiture) {
+            (Some(name), Some(signiture)) => format_signiture(name, signiture),
+            _ => format!("<invalid slot {}>", slot),
+        };
+
+        writeln!(output, "\n# slot {}", slot).unwrap();
+        writeln!(output, "function {} slots {}", function, source.slot_count).unwrap();
+        write_body(&mut output, source, &names, globals, dependencies);
+    }
+
+    if let Some(source) = module.get_init_source() {
+        writeln!(output, "\n# initializer of globals").unwrap();
+        writeln!(output, "init slots {}", source.slot_count).unwrap();
+        write_body(&mut output, source, &names, globals, dependencies);
+    }
+
+    output
+}
+
+/// Function names indexed by slot, `None` for slots without a name. Names pointing to slots
+/// out of range are left out, so corrupted bytecode can still be listed.
+fn function_names(module: &Module) -> Vec<Option<&str>> {
+    let mut names = vec![None; module.get_sources().len()];
+    let mut functions: Vec<_> = module.get_function_map().iter().collect();
+    functions.sort();
+    for (name, slot) in functions {
+        if let Some(entry @ None) = names.get_mut(*slot as usize) {
+            *entry = Some(name.as_str());
+        }
+    }
+    names
+}
+
+fn write_body(
+    output: &mut String,
+    source: &FunctionSource,
+    names: &[Option<&str>],
+    globals: &[Global],
+    dependencies: &HashMap<String, Module>,
+) {
+    let targets = jump_targets(&source.body);
+    for (index, instruction) in source.body.iter().enumerate() {
+        if targets.contains(&index) {
+            writeln!(output, "{}:", label(index)).unwrap();
+        }
+
+        let (text, comment) = format_instruction(instruction, names, globals, dependencies);
+        match comment {
+            Some(comment) => writeln!(output, "{:>8}  {:<30} # {}", index, text, comment).unwrap(),
+            None => writeln!(output, "{:>8}  {}", index, text).unwrap(),
+        }
+    }
+
+    // jump right behind the last instruction
+    if targets.contains(&source.body.len()) {
+        writeln!(output, "{}:", label(source.body.len())).unwrap();
+    }
+}
+
+fn jump_targets(body: &[Instruction]) -> HashSet<usize> {
+    body.iter()
+        .filter_map(|instruction| match instruction {
+            Instruction::Jump(target) | Instruction::JumpIfFalse(target) => Some(*target as usize),
+            _ => None,
+        })
+        .collect()
+}
+
+fn label(index: usize) -> String {
+    format!("L{}", index)
+}
+
+fn format_signiture(name: &str, signiture: &FunctionSigniture) -> String {
+    let params: Vec<String> = signiture
+        .params
+        .iter()
+        .map(|p| format!("{} {}", data_type_keyword(p.data_type), p.name))
+        .collect();
+
+    format!(
+        "{}{} {}({})",
+        signiture.visibility.prefix(),
+        data_type_keyword(signiture.return_type),
+        name,
+        params.join(", ")
+    )
+}
+
+/// Instruction text with optional comment, e.g. the slot of a called function.
+fn format_instruction(
+    instruction: &Instruction,
+    names: &[Option<&str>],
+    globals: &[Global],
+    dependencies: &HashMap<String, Module>,
+) -> (String, Option<String>) {
+    let global_name = |slot: u32| globals.get(slot as usize).map(|global| global.name.clone());
+
+    let text = match instruction {
+        Instruction::Store { slot } => format!("store {}", slot),
+        Instruction::Load { slot } => format!("load {}", slot),
+        Instruction::StoreGlobal { slot } => {
+            return (format!("store_global {}", slot), global_name(*slot));
+        }
+        Instruction::LoadGlobal { slot } => {
+            return (format!("load_global {}", slot), global_name(*slot));
+        }
+        Instruction::Push(value) => format!("push {}", format_literal(value)),
+        Instruction::Pop => "pop".to_string(),
+        Instruction::Add => "add".to_string(),
+        Instruction::Sub => "sub".to_string(),
+        Instruction::Mul => "mul".to_string(),
+        Instruction::Div => "div".to_string(),
+        Instruction::Modulo => "modulo".to_string(),
+        Instruction::Equal => "equal".to_string(),
+        Instruction::NotEqual => "not_equal".to_string(),
+        Instruction::Less => "less".to_string(),
+        Instruction::LessEqual => "less_equal".to_string(),
+        Instruction::Greater => "greater".to_string(),
+        Instruction::GreaterEqual => "greater_equal".to_string(),
+        Instruction::And => "and".to_string(),
+        Instruction::Or => "or".to_string(),
+        Instruction::Not => "not".to_string(),
+        Instruction::Negate => "negate".to_string(),
+        Instruction::BitAnd => "bit_and".to_string(),
+        Instruction::BitOr => "bit_or".to_string(),
+        Instruction::BitXor => "bit_xor".to_string(),
+        Instruction::BitNot => "bit_not".to_string(),
+        Instruction::ShiftLeft => "shift_left".to_string(),
+        Instruction::ShiftRight => "shift_right".to_string(),
+        Instruction::Cast(data_type) => format!("cast {}", data_type_keyword(*data_type)),
+        Instruction::Jump(target) => format!("jump {}", label(*target as usize)),
+        Instruction::JumpIfFalse(target) => {
+            format!("jump_if_false {}", label(*target as usize))
+        }
+        Instruction::Call { call_slot } => {
+            let text = match names.get(*call_slot as usize).copied().flatten() {
+                Some(name) => format!("call {}", name),
+                None => format!("call {}", call_slot),
+            };
+            return (text, Some(format!("slot {}", call_slot)));
+        }
+        Instruction::ForeignCall {
+            module_name,
+            call_slot,
+        } => {
+            let name = dependencies.get(module_name).and_then(|module| {
+                let names = function_names(module);
+                names
+                    .get(*call_slot as usize)
+                    .copied()
+                    .flatten()
+                    .map(str::to_string)
+            });
+
+            let text = match name {
+                Some(name) => format!("foreign_call {}:{}", module_name, name),
+                None => format!("foreign_call {}:{}", module_name, call_slot),
+            };
+            return (text, Some(format!("slot {}", call_slot)));
+        }
+        Instruction::Return => "return".to_string(),
+        Instruction::BuiltinFunctionCall {
+            function,
+            arg_count,
+        } => format!("builtin {} {}", function.as_str(), arg_count),
+        Instruction::MethodCall {
+            method_name,
+            arg_count,
+        } => format!("method {} {}", method_name, arg_count),
+    };
+
+    (text, None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn disassemble_module() {
+        let math = Module::from_string("pub Int add(Int a, Int b) {\n return a + b\n}", 0).unwrap();
+        let source = "import math
+Int twice(Int x) {
+    return x * 2
+}
+Int main() {
+    let i = 0
+    while i < 3 {
+        i = math:add(twice(i), 1)
+    }
+    print('done')
+    return i
+}";
+        let ast = crate::ast::Ast::from_string(source, 0).unwrap();
+        let dependencies = HashMap::from([("math".to_string(), math)]);
+        let module = Module::from_ast_and_dependencies(ast, &dependencies).unwrap();
+
+        let listing = disassemble(&module, &dependencies);
+        assert!(listing.starts_with("# version 3\n# main slot 1\n"));
+        assert!(listing.contains("function Int twice(Int x) slots 1\n"));
+        assert!(listing.contains("function Int main() slots 1\n"));
+        assert!(listing.contains("call twice"));
+        assert!(listing.contains("foreign_call math:add"));
+        assert!(listing.contains("builtin print 1"));
+        assert!(listing.contains("push 'done'"));
+
+        // the loop jumps back to the condition and exits after the last loop instruction
+        let (_, main) = listing.split_once("Int main()").unwrap();
+        assert!(main.contains("L2:\n       2  load 0\n"));
+        assert!(main.contains("      11  jump L2\n"));
+        assert!(main.contains("       5  jump_if_false L12\n"));
+        assert!(main.contains("L12:\n      12  push 'done'\n"));
+
+        let listing = disassemble(&module, &HashMap::new());
+        assert!(listing.contains("foreign_call math:0"));
+    }
+}
@@ -102,6 +102,14 @@ impl CompileError {
         .with_span(span)
     }
 
+    pub fn function_already_defined_at(name: impl Into<String> + Clone, span: Span) -> Self {
+        Self::new(
+            CompileErrorKind::FunctionAlreadyDefined(name.clone().into()),
+            format!("function already defined: {}", name.into()),
+        )
+        .with_span(span)
+    }
+
     pub fn module_not_found_at(name: impl Into<String> + Clone, span: Span) -> Self {
         Self::new(
             CompileErrorKind::ModuleNotFound(name.clone().into()),
@@ -110,6 +118,40 @@ impl CompileError {
         .with_span(span)
     }
 
+    pub fn ambiguous_module_at(
+        name: impl Into<String> + Clone,
+        candidates: &[String],
+        span: Span,
+    ) -> Self {
+        Self::new(
+            CompileErrorKind::AmbiguousModule(name.clone().into()),
+            format!(
+                "ambiguous module name: {} can be any of {}",
+                name.into(),
+                candidates.join(", ")
+            ),
+        )
+        .with_span(span)
+    }
+
+    /// Imported name is already used by a function of the module or by another import,
+    /// `other` describes the existing definition.
+    pub fn import_clash_at(
+        name: impl Into<String> + Clone,
+        other: impl Into<String>,
+        span: Span,
+    ) -> Self {
+        Self::new(
+            CompileErrorKind::ImportClash(name.clone().into()),
+            format!(
+                "imported name clashes with {}: {}",
+                other.into(),
+                name.into()
+            ),
+        )
+        .with_span(span)
+    }
+
     pub fn variable_redeclaration_at(name: impl Into<String> + Clone, span: Span) -> Self {
         Self::new(
             CompileErrorKind::VarRedeclaration(name.clone().into()),
@@ -148,6 +190,125 @@ impl CompileError {
         .with_span(span)
     }
 
+    pub fn private_function_at(
+        module: impl Into<String> + Clone,
+        name: impl Into<String> + Clone,
+        span: Span,
+    ) -> Self {
+        let name = name.into();
+        Self::new(
+            CompileErrorKind::PrivateFunction {
+                module: module.clone().into(),
+                name: name.clone(),
+            },
+            format!("function {}:{} is private", module.into(), name),
+        )
+        .with_span(span)
+        .add_note(format!(
+            "mark the function as 'pub {}' to call it from other modules",
+            name
+        ))
+    }
+
+    pub fn unknown_foreign_constant_at(
+        module: impl Into<String> + Clone,
+        name: impl Into<String> + Clone,
+        span: Span,
+    ) -> Self {
+        Self::new(
+            CompileErrorKind::UnknownForeignConstant {
+                module: module.clone().into(),
+                name: name.clone().into(),
+            },
+            format!(
+                "unknown foreign constant: {}:{}",
+                module.into(),
+                name.into()
+            ),
+        )
+        .with_span(span)
+    }
+
+    pub fn private_constant_at(
+        module: impl Into<String> + Clone,
+        name: impl Into<String> + Clone,
+        span: Span,
+    ) -> Self {
+        let name = name.into();
+        Self::new(
+            CompileErrorKind::PrivateConstant {
+                module: module.clone().into(),
+                name: name.clone(),
+            },
+            format!("constant {}:{} is private", module.into(), name),
+        )
+        .with_span(span)
+        .add_note(format!(
+            "mark the constant as 'pub const' to use {} in other modules",
+            name
+        ))
+    }
+
+    /// Value of the constant can not be computed at compile time, `reason` tells why.
+    pub fn invalid_constant_at(
+        name: impl Into<String> + Clone,
+        reason: impl Into<String>,
+        span: Span,
+    ) -> Self {
+        Self::new(
+            CompileErrorKind::InvalidConstant(name.clone().into()),
+            format!(
+                "invalid value of constant {}: {}",
+                name.into(),
+                reason.into()
+            ),
+        )
+        .with_span(span)
+        .add_note("constants can use only literals, operators and other constants")
+    }
+
+    pub fn constant_assignment_at(name: impl Into<String> + Clone, span: Span) -> Self {
+        Self::new(
+            CompileErrorKind::ConstantAssignment(name.clone().into()),
+            format!("cannot assign to constant {}", name.into()),
+        )
+        .with_span(span)
+    }
+
+    /// Constants of modules importing each other depend on each other in a cycle.
+    pub fn cyclic_constant_at(
+        module: impl Into<String> + Clone,
+        name: impl Into<String> + Clone,
+        span: Span,
+    ) -> Self {
+        Self::new(
+            CompileErrorKind::CyclicConstant {
+                module: module.clone().into(),
+                name: name.clone().into(),
+            },
+            format!(
+                "value of constant {}:{} depends on itself",
+                module.into(),
+                name.into()
+            ),
+        )
+        .with_span(span)
+        .add_note(
+            "constants of modules importing each other can not refer to each other in a cycle",
+        )
+    }
+
+    /// `cycle` lists the modules re-exporting each other, e.g. `["a", "b", "a"]`.
+    pub fn cyclic_reexport_at(cycle: &[String], span: Span) -> Self {
+        let cycle = cycle.join(" -> ");
+        Self::new(
+            CompileErrorKind::CyclicReexport(cycle.clone()),
+            format!("cyclic re-export: {}", cycle),
+        )
+        .with_span(span)
+        .add_note("modules importing each other can not re-export each other with 'pub import'")
+    }
+
     pub fn method_not_found_at(
         data_type: DataType,
         method_name: impl Into<String> + Clone,
@@ -167,6 +328,34 @@ impl CompileError {
         .with_span(span)
     }
 
+    pub fn invalid_cast_at(from: DataType, to: DataType, span: Span) -> Self {
+        let error = Self::new(
+            CompileErrorKind::InvalidCast { from, to },
+            format!("cannot cast {} to {}", from, to),
+        )
+        .with_span(span);
+
+        match (from, to) {
+            (DataType::String, DataType::Int) => {
+                error.add_note("use 'parse_int()' to convert a string to an int")
+            }
+            (DataType::String, DataType::Float) => {
+                error.add_note("use 'parse_float()' to convert a string to a float")
+            }
+            _ => error.add_note("ints and floats can be cast to each other, ints, floats and bools can be cast to a string"),
+        }
+    }
+
+    pub fn invalid_main_signiture_at(msg: impl Into<String>, span: Span) -> Self {
+        Self::new(CompileErrorKind::InvalidMainSigniture, msg)
+            .with_span(span)
+            .add_note("use 'Void main()' or 'Int main()', arguments are available through 'arg_count()' and 'arg(i)'")
+    }
+
+    pub fn invalid_assembly_at(msg: impl Into<String>, span: Span) -> Self {
+        Self::new(CompileErrorKind::InvalidAssembly, msg).with_span(span)
+    }
+
     pub fn other_at(msg: impl Into<String>, span: Span) -> Self {
         Self::new(CompileErrorKind::Other, msg).with_span(span)
     }
@@ -224,14 +413,41 @@ pub enum CompileErrorKind {
     FunctionAlreadyDefined(String),
     VarRedeclaration(String),
     ModuleNotFound(String),
+    AmbiguousModule(String),
+    ImportClash(String),
     UnknownForeignFunction {
         module: String,
         name: String,
     },
+    PrivateFunction {
+        module: String,
+        name: String,
+    },
+    UnknownForeignConstant {
+        module: String,
+        name: String,
+    },
+    PrivateConstant {
+        module: String,
+        name: String,
+    },
+    InvalidConstant(String),
+    ConstantAssignment(String),
+    CyclicConstant {
+        module: String,
+        name: String,
+    },
+    CyclicReexport(String),
     ReservedFunctionName(String),
     MethodNotFound {
         data_type: DataType,
         method_name: String,
     },
+    InvalidCast {
+        from: DataType,
+        to: DataType,
+    },
+    InvalidMainSigniture,
+    InvalidAssembly,
     Other,
 }
@@ -1,9 +1,15 @@
 use std::fmt::Display;
 
+use crate::value::Value;
+
 #[derive(Debug, Clone)]
 pub enum RuntimeError {
     ValueStackUnderflow,
     DivisionByZero,
+    /// result of the integer operation does not fit its type, e.g. `addition`
+    IntegerOverflow {
+        operation: &'static str,
+    },
     Unknown,
 
     /// this is for any type error
@@ -12,6 +18,11 @@ pub enum RuntimeError {
     TypeError,
     FunctionDidNotReturn,
     AssertionFailed,
+    /// raised by `assert_eq`
+    AssertionNotEqual {
+        expected: Value,
+        actual: Value,
+    },
     Other(String),
 }
 
@@ -20,11 +31,27 @@ impl Display for RuntimeError {
         match self {
             RuntimeError::ValueStackUnderflow => write!(f, "Value stack underflow"),
             RuntimeError::DivisionByZero => write!(f, "Division by zero"),
+            RuntimeError::IntegerOverflow { operation } => {
+                write!(f, "Integer overflow in {}", operation)
+            }
             RuntimeError::Unknown => write!(f, "Unknown runtime error"),
             RuntimeError::TypeError => write!(f, "Type error"),
             RuntimeError::FunctionDidNotReturn => write!(f, "Function did not return a value"),
             RuntimeError::AssertionFailed => write!(f, "Assertion failed"),
+            RuntimeError::AssertionNotEqual { expected, actual } => write!(
+                f,
+                "Assertion failed: expected {}, actual {}",
+                quoted(expected),
+                quoted(actual)
+            ),
             RuntimeError::Other(msg) => write!(f, "{}", msg),
         }
     }
 }
+
+fn quoted(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s),
+        _ => value.to_string(),
+    }
+}
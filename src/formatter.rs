@@ -0,0 +1,1163 @@
+use crate::{
+    ast::{Ast, BinaryOp, Expr, ExprKind, Statement, StatementKind, UnaryOp},
+    compiler::{
+        token::{Comment, Token, TokenKind},
+        tokenize_with_comments, Parser,
+    },
+    data_type::DataType,
+    diagnostics::{FileId, Span},
+    errors::{CompileError, CompileErrorKind},
+    value::Value,
+};
+
+const INDENT: &str = "    ";
+
+/// Formats the source code into the canonical style, comments and blank lines are preserved.
+/// The output is parsed again and compared with the original, so formatting never
+/// changes meaning of the code. Formatting already formatted code does not change it.
+pub fn format_source(source: &str, file_id: FileId) -> Result<String, CompileError> {
+    let (tokens, comments) = tokenize_with_comments(source, file_id)?;
+    let ast = Parser::new(&tokens, file_id).parse()?;
+
+    let output = Formatter::new(source, &tokens, &comments).format(&ast)?;
+
+    let formatted_ast = Ast::from_string(&output, file_id).map_err(|e| {
+        CompileError::new(
+            CompileErrorKind::Other,
+            format!("formatter produced invalid code: {}", e.message),
+        )
+    })?;
+
+    if erase_spans(formatted_ast) != erase_spans(ast) {
+        return Err(CompileError::new(
+            CompileErrorKind::Other,
+            "formatter would change meaning of the code",
+        ));
+    }
+
+    Ok(output)
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    tokens: &'a [Token],
+    comments: &'a [Comment],
+    next_comment: usize,
+    output: String,
+    indent: usize,
+    /// source position where the last written line ended
+    last_end: usize,
+    /// no blank line right after an opening brace
+    block_start: bool,
+    /// blank line before the next line, used to separate functions
+    force_blank: bool,
+}
+
+enum Item<'a> {
+    Import(String),
+    /// constant or global, the text before its value and the span up to the end of the value
+    Declaration(String, &'a Expr, Span),
+    Function(&'a str, u32),
+}
+
+impl<'a> Formatter<'a> {
+    fn new(source: &'a str, tokens: &'a [Token], comments: &'a [Comment]) -> Self {
+        Self {
+            source,
+            tokens,
+            comments,
+            next_comment: 0,
+            output: String::new(),
+            indent: 0,
+            last_end: 0,
+            block_start: false,
+            force_blank: false,
+        }
+    }
+
+    fn format(mut self, ast: &'a Ast) -> Result<String, CompileError> {
+        // imports, declarations and functions are written in the source order
+        let mut items: Vec<(usize, Item)> = ast
+            .get_imports()
+            .iter()
+            .map(|import| {
+                let keyword = if import.public {
+                    "pub import"
+                } else {
+                    "import"
+                };
+                let text = format!("{} {}", keyword, import);
+                (import.span.lo, Item::Import(text))
+            })
+            .collect();
+
+        for constant in ast.get_constants() {
+            let text = format!(
+                "{}const {} {} = ",
+                constant.visibility.prefix(),
+                data_type_keyword(constant.data_type),
+                constant.name,
+            );
+            let span = constant.span.join(constant.value.span);
+            items.push((
+                constant.span.lo,
+                Item::Declaration(text, &constant.value, span),
+            ));
+        }
+
+        for global in ast.get_globals() {
+            let text = declaration_head(global.data_type, &global.name);
+            let span = global.span.join(global.value.span);
+            items.push((global.span.lo, Item::Declaration(text, &global.value, span)));
+        }
+
+        for (name, slot) in ast.get_function_map() {
+            let signiture = ast.get_function_signiture_by_slot(*slot).unwrap();
+            items.push((signiture.span.lo, Item::Function(name, *slot)));
+        }
+
+        items.sort_by_key(|(lo, _)| *lo);
+
+        // consecutive imports and declarations are not separated by blank lines
+        let mut previous = None;
+        for (lo, item) in items {
+            let kind = std::mem::discriminant(&item);
+            match item {
+                Item::Import(text) => {
+                    self.force_blank = previous != Some(kind);
+                    self.begin_line(lo, true);
+                    self.output.push_str(&text);
+                    let hi = self.line_end(lo);
+                    self.end_line(lo, hi);
+                }
+                Item::Declaration(head, value, span) => {
+                    self.force_blank = previous != Some(kind);
+                    self.begin_line(lo, true);
+                    let text = head + &self.format_expr(value);
+                    self.push_lines(&text);
+                    let hi = self.line_end(span.hi);
+                    self.end_line(lo, hi);
+                }
+                Item::Function(name, slot) => {
+                    self.force_blank = true;
+                    self.format_function(ast, name, slot);
+                }
+            }
+            previous = Some(kind);
+        }
+
+        self.flush_comments(usize::MAX);
+
+        Ok(self.output)
+    }
+
+    fn format_function(&mut self, ast: &Ast, name: &str, slot: u32) {
+        let signiture = ast.get_function_signiture_by_slot(slot).unwrap();
+        let params: Vec<String> = signiture
+            .params
+            .iter()
+            .map(|p| format!("{} {}", data_type_keyword(p.data_type), p.name))
+            .collect();
+
+        let lo = signiture.span.lo;
+        let open = self.open_brace_after(signiture.span.hi);
+        self.begin_line(lo, true);
+        self.output.push_str(&format!(
+            "{}{} {}({}) {{",
+            signiture.visibility.prefix(),
+            data_type_keyword(signiture.return_type),
+            name,
+            params.join(", ")
+        ));
+        self.end_line(lo, self.tokens[open].span.hi);
+
+        let body = ast.get_function_body_by_slot(slot).unwrap();
+        let close = self.format_block(body, open);
+        self.end_line(close.lo, close.hi);
+    }
+
+    /// Writes statements of the block and its closing brace, the line is left open.
+    /// `open` is index of the opening brace token, returns span of the closing one.
+    fn format_block(&mut self, statements: &[Statement], open: usize) -> Span {
+        let close = self.tokens[self.matching_brace(open)].span;
+
+        self.indent += 1;
+        self.block_start = true;
+        for statement in statements {
+            self.format_statement(statement);
+        }
+        self.flush_comments(close.lo);
+        self.indent -= 1;
+
+        self.begin_line(close.lo, false);
+        self.output.push('}');
+        close
+    }
+
+    fn format_statement(&mut self, statement: &Statement) {
+        let lo = statement.span.lo;
+
+        match &statement.kind {
+            StatementKind::If { .. } => {
+                self.begin_line(lo, true);
+                self.format_if(statement, lo);
+            }
+
+            StatementKind::While { condition, block } => {
+                let open = self.open_brace_after(condition.span.hi);
+                self.begin_line(lo, true);
+                let text = format!("while {} {{", self.format_expr(condition));
+                self.push_lines(&text);
+                self.end_line(lo, self.tokens[open].span.hi);
+
+                let close = self.format_block(block, open);
+                self.end_line(close.lo, close.hi);
+            }
+
+            StatementKind::VarDeclare {
+                data_type,
+                variable,
+                value,
+            } => {
+                self.begin_line(lo, true);
+                let text = self.format_declaration(*data_type, variable, value);
+                self.push_lines(&text);
+                self.end_line(lo, self.line_end(statement.span.hi));
+            }
+
+            StatementKind::VarAssign { variable, value } => {
+                self.begin_line(lo, true);
+                let text = format!("{} = {}", variable, self.format_expr(value));
+                self.push_lines(&text);
+                self.end_line(lo, self.line_end(statement.span.hi));
+            }
+
+            StatementKind::Expr(expr) => {
+                self.begin_line(lo, true);
+                let text = self.format_expr_statement(expr);
+                self.push_lines(&text);
+                self.end_line(lo, self.line_end(statement.span.hi));
+            }
+
+            StatementKind::Return(expr) => {
+                self.begin_line(lo, true);
+                let text = self.format_return(expr);
+                self.push_lines(&text);
+                self.end_line(lo, self.line_end(statement.span.hi.max(expr.span.hi)));
+            }
+        }
+    }
+
+    /// Writes if statement starting at the already begun line,
+    /// `line_lo` is source position where the line starts.
+    fn format_if(&mut self, statement: &Statement, line_lo: usize) {
+        let StatementKind::If {
+            condition,
+            if_block,
+            else_block,
+        } = &statement.kind
+        else {
+            unreachable!("format_if called on non-if statement");
+        };
+
+        let open = self.open_brace_after(condition.span.hi);
+        let text = format!("if {} {{", self.format_expr(condition));
+        self.push_lines(&text);
+        self.end_line(line_lo, self.tokens[open].span.hi);
+
+        let close = self.format_block(if_block, open);
+        let close_index = self.matching_brace(open);
+
+        let Some(else_block) = else_block else {
+            self.end_line(close.lo, close.hi);
+            return;
+        };
+
+        // `else if` is parsed as else block with single if statement,
+        // the tokens tell which one was written
+        let is_else_if = self
+            .tokens
+            .get(close_index + 2)
+            .is_some_and(|t| t.kind == TokenKind::If);
+
+        if is_else_if {
+            self.output.push_str(" else ");
+            self.format_if(&else_block[0], close.lo);
+        } else {
+            let else_open = close_index + 2;
+            self.output.push_str(" else {");
+            self.end_line(close.lo, self.tokens[else_open].span.hi);
+
+            let else_close = self.format_block(else_block, else_open);
+            self.end_line(else_close.lo, else_close.hi);
+        }
+    }
+
+    /// Writes text to the begun line, lines of block expressions are indented with the first one.
+    fn push_lines(&mut self, text: &str) {
+        let indent = INDENT.repeat(self.indent);
+        self.output
+            .push_str(&text.replace('\n', &format!("\n{}", indent)));
+    }
+
+    /// Writes comments before the position, blank line if needed and indentation.
+    fn begin_line(&mut self, lo: usize, allow_blank: bool) {
+        self.flush_comments(lo);
+        if allow_blank {
+            self.blank_line(lo);
+        }
+
+        self.force_blank = false;
+        self.block_start = false;
+        self.output.push_str(&INDENT.repeat(self.indent));
+    }
+
+    /// Ends the line written from the source range `lo..hi`,
+    /// a comment following the range on the same line is kept at the end of the line.
+    fn end_line(&mut self, lo: usize, hi: usize) {
+        self.last_end = hi;
+
+        if let Some(comment) = self.comments.get(self.next_comment) {
+            if self.is_trailing(comment, lo, hi) {
+                self.output.push(' ');
+                self.output.push_str(&comment.text);
+                self.last_end = comment.span.hi;
+                self.next_comment += 1;
+            }
+        }
+
+        self.output.push('\n');
+    }
+
+    /// Writes all comments before the position on their own lines.
+    fn flush_comments(&mut self, before: usize) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.span.lo >= before {
+                break;
+            }
+
+            self.blank_line(comment.span.lo);
+            self.force_blank = false;
+            self.block_start = false;
+
+            self.output.push_str(&INDENT.repeat(self.indent));
+            self.output.push_str(&comment.text);
+            self.output.push('\n');
+
+            self.last_end = comment.span.hi;
+            self.next_comment += 1;
+        }
+    }
+
+    /// Keeps at most one blank line from the source.
+    fn blank_line(&mut self, lo: usize) {
+        if self.output.is_empty() || self.block_start {
+            return;
+        }
+
+        let gap = self.source.get(self.last_end..lo).unwrap_or("");
+        if self.force_blank || gap.matches('\n').count() >= 2 {
+            self.output.push('\n');
+        }
+    }
+
+    fn is_trailing(&self, comment: &Comment, lo: usize, hi: usize) -> bool {
+        let index = self.tokens.partition_point(|t| t.span.lo < comment.span.lo);
+        let Some(token) = self.tokens[..index]
+            .iter()
+            .rev()
+            .find(|t| t.kind != TokenKind::EOL)
+        else {
+            return false;
+        };
+
+        token.span.lo >= lo
+            && token.span.hi <= hi
+            && !self.source[token.span.hi..comment.span.lo].contains('\n')
+    }
+
+    /// End of the last token before the end of line.
+    fn line_end(&self, pos: usize) -> usize {
+        let index = self.tokens.partition_point(|t| t.span.lo < pos);
+        let eol = self.tokens[index..]
+            .iter()
+            .position(|t| t.kind == TokenKind::EOL)
+            .map_or(self.tokens.len(), |i| index + i);
+
+        self.tokens[..eol]
+            .last()
+            .map_or(pos, |t| t.span.hi.max(pos))
+    }
+
+    /// Index of the first opening brace token after the position.
+    fn open_brace_after(&self, pos: usize) -> usize {
+        let index = self.tokens.partition_point(|t| t.span.lo < pos);
+        index
+            + self.tokens[index..]
+                .iter()
+                .position(|t| t.kind == TokenKind::BraceL)
+                .expect("parsed block has opening brace")
+    }
+
+    fn matching_brace(&self, open: usize) -> usize {
+        let mut depth = 0;
+        for (index, token) in self.tokens.iter().enumerate().skip(open) {
+            match token.kind {
+                TokenKind::BraceL => depth += 1,
+                TokenKind::BraceR => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return index;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        unreachable!("parsed block has closing brace")
+    }
+
+    /// Index of the opening brace of a block expression, spans of `if` branches
+    /// start at their first statement.
+    fn open_brace_of(&self, block: Span) -> usize {
+        let index = self.tokens.partition_point(|t| t.span.lo <= block.lo);
+        self.tokens[..index]
+            .iter()
+            .rposition(|t| t.kind == TokenKind::BraceL)
+            .expect("parsed block has opening brace")
+    }
+
+    /// Comments before the position inside a block expression, each on its own line.
+    /// Returns whether any comment was written.
+    fn nested_comments(&mut self, before: usize, lines: &mut Vec<String>) -> bool {
+        let first = self.next_comment;
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.span.lo >= before {
+                break;
+            }
+
+            lines.push(comment.text.clone());
+            self.next_comment += 1;
+        }
+
+        self.next_comment > first
+    }
+
+    /// Comment at the end of the line of block expression written from the source range `lo..hi`.
+    /// Returns whether the comment was written.
+    fn nested_trailing_comment(&mut self, line: &mut String, lo: usize, hi: usize) -> bool {
+        let Some(comment) = self.comments.get(self.next_comment) else {
+            return false;
+        };
+
+        if comment.span.lo >= hi || !self.is_trailing(comment, lo, hi) {
+            return false;
+        }
+
+        line.push(' ');
+        line.push_str(&comment.text);
+        self.next_comment += 1;
+        true
+    }
+
+    fn format_expr(&mut self, expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Literal(value) => format_literal(value),
+            ExprKind::Var(name) => name.clone(),
+            ExprKind::BinaryOp(op, lhs, rhs) => {
+                let precedence = binary_op_precedence(*op);
+                // comparisons are not associative, `a == b == c` does not parse
+                let lhs_parens = expr_precedence(lhs) < precedence
+                    || (precedence == COMPARISON_PRECEDENCE && expr_precedence(lhs) == precedence);
+                let rhs_parens = expr_precedence(rhs) <= precedence;
+
+                format!(
+                    "{} {} {}",
+                    self.parenthesize(lhs, lhs_parens),
+                    binary_op_symbol(*op),
+                    self.parenthesize(rhs, rhs_parens)
+                )
+            }
+            ExprKind::UnaryOp(op, operand) => {
+                let symbol = match op {
+                    UnaryOp::Not => "!",
+                    UnaryOp::Negate => "-",
+                    UnaryOp::BitNot => "~",
+                };
+                let parens = expr_precedence(operand) < UNARY_PRECEDENCE;
+                format!("{}{}", symbol, self.parenthesize(operand, parens))
+            }
+            ExprKind::Cast { expr, data_type } => {
+                let parens = expr_precedence(expr) < CAST_PRECEDENCE;
+                format!(
+                    "{} as {}",
+                    self.parenthesize(expr, parens),
+                    data_type_keyword(*data_type)
+                )
+            }
+            ExprKind::If {
+                condition,
+                if_block,
+                else_block,
+            } => format!(
+                "if {} {} else {}",
+                self.format_expr(condition),
+                self.format_expr(if_block),
+                self.format_expr(else_block)
+            ),
+            ExprKind::Block { statements, result } => {
+                let open = self.open_brace_of(expr.span);
+                self.format_nested_block(statements, Some(result), open)
+            }
+            ExprKind::FunctionCall { func_name, args } => {
+                format!("{}({})", func_name, self.format_args(args))
+            }
+            ExprKind::ForeignFunctionCall {
+                module_name,
+                func_name,
+                args,
+            } => format!("{}:{}({})", module_name, func_name, self.format_args(args)),
+            ExprKind::ForeignConstant { module_name, name } => {
+                format!("{}:{}", module_name, name)
+            }
+            ExprKind::BuiltinFunctionCall { function, args } => {
+                format!("{}({})", function.as_str(), self.format_args(args))
+            }
+            ExprKind::MethodCall {
+                callee,
+                method_name,
+                args,
+            } => {
+                // only atoms can be called, methods can not be chained without parentheses
+                let parens = expr_precedence(callee) < ATOM_PRECEDENCE;
+                format!(
+                    "{}.{}({})",
+                    self.parenthesize(callee, parens),
+                    method_name,
+                    self.format_args(args)
+                )
+            }
+        }
+    }
+
+    /// Block of statements inside an expression, the value of block expressions is the `result`.
+    /// `open` is index of the opening brace token. Blocks with only the result and no comments
+    /// fit on one line, `{ 1 }`.
+    fn format_nested_block(
+        &mut self,
+        statements: &[Statement],
+        result: Option<&Expr>,
+        open: usize,
+    ) -> String {
+        let close = self.tokens[self.matching_brace(open)].span.lo;
+        let mut lines = vec![];
+        let mut commented = false;
+
+        for (index, statement) in statements.iter().enumerate() {
+            commented |= self.nested_comments(statement.span.lo, &mut lines);
+            let mut line = self.format_nested_statement(statement);
+            let next = statements
+                .get(index + 1)
+                .map(|s| s.span.lo)
+                .or(result.map(|r| r.span.lo))
+                .unwrap_or(close);
+            commented |= self.nested_trailing_comment(&mut line, statement.span.lo, next);
+            lines.push(line);
+        }
+
+        if let Some(result) = result {
+            commented |= self.nested_comments(result.span.lo, &mut lines);
+            let mut line = match result.kind {
+                // written as an if statement, the parser turns it into the value again
+                ExprKind::If { .. } => self.format_expr(result),
+                _ => self.format_expr_statement(result),
+            };
+            commented |= self.nested_trailing_comment(&mut line, result.span.lo, close);
+            lines.push(line);
+        }
+
+        commented |= self.nested_comments(close, &mut lines);
+
+        match lines.as_slice() {
+            [] => "{\n}".to_string(),
+            [line] if !commented && statements.is_empty() && !line.contains('\n') => {
+                format!("{{ {} }}", line)
+            }
+            _ => {
+                let body = lines.join("\n").replace('\n', &format!("\n{}", INDENT));
+                format!("{{\n{}{}\n}}", INDENT, body)
+            }
+        }
+    }
+
+    /// Statement of a block expression.
+    fn format_nested_statement(&mut self, statement: &Statement) -> String {
+        match &statement.kind {
+            StatementKind::VarDeclare {
+                data_type,
+                variable,
+                value,
+            } => self.format_declaration(*data_type, variable, value),
+            StatementKind::VarAssign { variable, value } => {
+                format!("{} = {}", variable, self.format_expr(value))
+            }
+            StatementKind::Expr(expr) => self.format_expr_statement(expr),
+            StatementKind::Return(expr) => self.format_return(expr),
+            StatementKind::While { condition, block } => {
+                let open = self.open_brace_after(condition.span.hi);
+                format!(
+                    "while {} {}",
+                    self.format_expr(condition),
+                    self.format_nested_block(block, None, open)
+                )
+            }
+            StatementKind::If {
+                condition,
+                if_block,
+                else_block,
+            } => {
+                let open = self.open_brace_after(condition.span.hi);
+                let text = format!(
+                    "if {} {}",
+                    self.format_expr(condition),
+                    self.format_nested_block(if_block, None, open)
+                );
+                let Some(else_block) = else_block else {
+                    return text;
+                };
+
+                // `else if` is parsed as else block with single if statement,
+                // the tokens tell which one was written
+                let else_open = self.matching_brace(open) + 2;
+                if self.tokens[else_open].kind == TokenKind::If {
+                    format!(
+                        "{} else {}",
+                        text,
+                        self.format_nested_statement(&else_block[0])
+                    )
+                } else {
+                    let else_text = self.format_nested_block(else_block, None, else_open);
+                    format!("{} else {}", text, else_text)
+                }
+            }
+        }
+    }
+
+    /// Expression written as a statement, expressions starting with `if` are parenthesized,
+    /// otherwise they would be parsed as if statements.
+    fn format_expr_statement(&mut self, expr: &Expr) -> String {
+        if starts_with_if(expr) {
+            format!("({})", self.format_expr(expr))
+        } else {
+            self.format_expr(expr)
+        }
+    }
+
+    fn format_return(&mut self, expr: &Expr) -> String {
+        match expr.kind {
+            ExprKind::Literal(Value::Void) => "return".to_string(),
+            _ => format!("return {}", self.format_expr(expr)),
+        }
+    }
+
+    /// Variable or global declaration, `let name = value` or `Type name = value`.
+    fn format_declaration(
+        &mut self,
+        data_type: Option<DataType>,
+        name: &str,
+        value: &Expr,
+    ) -> String {
+        declaration_head(data_type, name) + &self.format_expr(value)
+    }
+
+    fn format_args(&mut self, args: &[Expr]) -> String {
+        args.iter()
+            .map(|arg| self.format_expr(arg))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn parenthesize(&mut self, expr: &Expr, parens: bool) -> String {
+        if parens {
+            format!("({})", self.format_expr(expr))
+        } else {
+            self.format_expr(expr)
+        }
+    }
+}
+
+fn starts_with_if(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::If { .. } => true,
+        ExprKind::BinaryOp(_, lhs, _) => starts_with_if(lhs),
+        ExprKind::Cast { expr, .. } | ExprKind::MethodCall { callee: expr, .. } => {
+            starts_with_if(expr)
+        }
+        _ => false,
+    }
+}
+
+/// Start of a variable or global declaration up to its value, `let name = ` or `Type name = `.
+fn declaration_head(data_type: Option<DataType>, name: &str) -> String {
+    let declaration = match data_type {
+        Some(data_type) => data_type_keyword(data_type),
+        None => "let",
+    };
+
+    format!("{} {} = ", declaration, name)
+}
+
+pub(crate) fn format_literal(value: &Value) -> String {
+    match value {
+        Value::String(string) => {
+            let mut literal = String::from('\'');
+            for ch in string.chars() {
+                match ch {
+                    '\\' => literal.push_str("\\\\"),
+                    '\'' => literal.push_str("\\'"),
+                    '\n' => literal.push_str("\\n"),
+                    '\t' => literal.push_str("\\t"),
+                    ch => literal.push(ch),
+                }
+            }
+            literal.push('\'');
+            literal
+        }
+        Value::Int64(int) => format!("{}i64", int),
+        Value::Byte(byte) => format!("{}u8", byte),
+        Value::Float(float) => {
+            let literal = float.to_string();
+            if literal.contains('.') {
+                literal
+            } else {
+                literal + ".0"
+            }
+        }
+        _ => value.to_string(),
+    }
+}
+
+const LOGICAL_PRECEDENCE: u8 = 1;
+const BIT_OR_PRECEDENCE: u8 = 2;
+const BIT_XOR_PRECEDENCE: u8 = 3;
+const BIT_AND_PRECEDENCE: u8 = 4;
+const COMPARISON_PRECEDENCE: u8 = 5;
+const SHIFT_PRECEDENCE: u8 = 6;
+const ADDITIVE_PRECEDENCE: u8 = 7;
+const MULTIPLICATIVE_PRECEDENCE: u8 = 8;
+const CAST_PRECEDENCE: u8 = 9;
+const UNARY_PRECEDENCE: u8 = 10;
+const METHOD_CALL_PRECEDENCE: u8 = 11;
+const ATOM_PRECEDENCE: u8 = 12;
+
+fn binary_op_precedence(op: BinaryOp) -> u8 {
+    match op {
+        BinaryOp::And | BinaryOp::Or => LOGICAL_PRECEDENCE,
+        BinaryOp::Equal
+        | BinaryOp::NotEqual
+        | BinaryOp::Less
+        | BinaryOp::LessEqual
+        | BinaryOp::Greater
+        | BinaryOp::GreaterEqual => COMPARISON_PRECEDENCE,
+        BinaryOp::BitOr => BIT_OR_PRECEDENCE,
+        BinaryOp::BitXor => BIT_XOR_PRECEDENCE,
+        BinaryOp::BitAnd => BIT_AND_PRECEDENCE,
+        BinaryOp::ShiftLeft | BinaryOp::ShiftRight => SHIFT_PRECEDENCE,
+        BinaryOp::Add | BinaryOp::Sub => ADDITIVE_PRECEDENCE,
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::Modulo => MULTIPLICATIVE_PRECEDENCE,
+    }
+}
+
+fn expr_precedence(expr: &Expr) -> u8 {
+    match &expr.kind {
+        ExprKind::BinaryOp(op, _, _) => binary_op_precedence(*op),
+        ExprKind::Cast { .. } => CAST_PRECEDENCE,
+        ExprKind::UnaryOp(_, _) => UNARY_PRECEDENCE,
+        ExprKind::MethodCall { .. } => METHOD_CALL_PRECEDENCE,
+        _ => ATOM_PRECEDENCE,
+    }
+}
+
+pub(crate) fn binary_op_symbol(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Modulo => "%",
+        BinaryOp::BitAnd => "&",
+        BinaryOp::BitOr => "|",
+        BinaryOp::BitXor => "^",
+        BinaryOp::ShiftLeft => "<<",
+        BinaryOp::ShiftRight => ">>",
+        BinaryOp::Equal => "==",
+        BinaryOp::NotEqual => "!=",
+        BinaryOp::Less => "<",
+        BinaryOp::LessEqual => "<=",
+        BinaryOp::Greater => ">",
+        BinaryOp::GreaterEqual => ">=",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+    }
+}
+
+pub(crate) fn data_type_keyword(data_type: DataType) -> &'static str {
+    match data_type {
+        DataType::Void => "Void",
+        DataType::Int => "Int",
+        DataType::Int64 => "Int64",
+        DataType::Byte => "Byte",
+        DataType::Float => "Float",
+        DataType::Bool => "Bool",
+        DataType::String => "String",
+    }
+}
+
+/// Ast with all spans set to zero, so asts of differently formatted code can be compared.
+fn erase_spans(ast: Ast) -> Ast {
+    let (function_map, mut signitures, mut bodies, mut imports, mut constants, mut globals) =
+        ast.deconstruct();
+
+    for import in imports.iter_mut() {
+        import.span = NO_SPAN;
+        for (_, span) in import.functions.iter_mut() {
+            *span = NO_SPAN;
+        }
+    }
+
+    for signiture in signitures.iter_mut() {
+        signiture.span = NO_SPAN;
+        for param in signiture.params.iter_mut() {
+            param.span = NO_SPAN;
+        }
+    }
+
+    for constant in constants.iter_mut() {
+        constant.span = NO_SPAN;
+        erase_expr_spans(&mut constant.value);
+    }
+
+    for global in globals.iter_mut() {
+        global.span = NO_SPAN;
+        erase_expr_spans(&mut global.value);
+    }
+
+    bodies.iter_mut().flatten().for_each(erase_statement_spans);
+
+    Ast::new(
+        function_map,
+        signitures,
+        bodies,
+        imports,
+        constants,
+        globals,
+    )
+}
+
+const NO_SPAN: Span = Span {
+    file: 0,
+    lo: 0,
+    hi: 0,
+};
+
+fn erase_statement_spans(statement: &mut Statement) {
+    statement.span = NO_SPAN;
+
+    match &mut statement.kind {
+        StatementKind::VarAssign { value, .. } | StatementKind::VarDeclare { value, .. } => {
+            erase_expr_spans(value)
+        }
+        StatementKind::If {
+            condition,
+            if_block,
+            else_block,
+        } => {
+            erase_expr_spans(condition);
+            if_block.iter_mut().for_each(erase_statement_spans);
+            else_block
+                .iter_mut()
+                .flatten()
+                .for_each(erase_statement_spans);
+        }
+        StatementKind::While { condition, block } => {
+            erase_expr_spans(condition);
+            block.iter_mut().for_each(erase_statement_spans);
+        }
+        StatementKind::Expr(expr) | StatementKind::Return(expr) => erase_expr_spans(expr),
+    }
+}
+
+fn erase_expr_spans(expr: &mut Expr) {
+    expr.span = NO_SPAN;
+
+    match &mut expr.kind {
+        ExprKind::Literal(_) | ExprKind::Var(_) | ExprKind::ForeignConstant { .. } => (),
+        ExprKind::BinaryOp(_, lhs, rhs) => {
+            erase_expr_spans(lhs);
+            erase_expr_spans(rhs);
+        }
+        ExprKind::UnaryOp(_, operand) | ExprKind::Cast { expr: operand, .. } => {
+            erase_expr_spans(operand)
+        }
+        ExprKind::FunctionCall { args, .. }
+        | ExprKind::ForeignFunctionCall { args, .. }
+        | ExprKind::BuiltinFunctionCall { args, .. } => args.iter_mut().for_each(erase_expr_spans),
+        ExprKind::MethodCall { callee, args, .. } => {
+            erase_expr_spans(callee);
+            args.iter_mut().for_each(erase_expr_spans);
+        }
+        ExprKind::If {
+            condition,
+            if_block,
+            else_block,
+        } => {
+            erase_expr_spans(condition);
+            erase_expr_spans(if_block);
+            erase_expr_spans(else_block);
+        }
+        ExprKind::Block { statements, result } => {
+            statements.iter_mut().for_each(erase_statement_spans);
+            erase_expr_spans(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn format(source: &str) -> String {
+        let formatted = format_source(source, 0).unwrap();
+        assert_eq!(format_source(&formatted, 0).unwrap(), formatted);
+        formatted
+    }
+
+    #[test]
+    fn format_examples() {
+        for entry in std::fs::read_dir("examples").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|e| e == "mv") {
+                let source = std::fs::read_to_string(&path).unwrap();
+                format(&source);
+            }
+        }
+    }
+
+    #[test]
+    fn format_statements() {
+        let source = "
+import math
+Int   add(Int a,Int b){
+return a+b;}
+Void main() {
+  let x=add(1 , 2) ; Float y = 1.0
+  if (x>2){print('big');}else if x==2 {
+print('two')
+  } else { x = -x; }
+  while x<10&&!false { x=x+1; }
+  String s = 'it\\'s'
+  assert_eq(s.length() , math:len(s))
+  return
+}";
+        let expected = "import math
+
+Int add(Int a, Int b) {
+    return a + b
+}
+
+Void main() {
+    let x = add(1, 2)
+    Float y = 1.0
+    if x > 2 {
+        print('big')
+    } else if x == 2 {
+        print('two')
+    } else {
+        x = -x
+    }
+    while x < 10 && !false {
+        x = x + 1
+    }
+    String s = 'it\\'s'
+    assert_eq(s.length(), math:len(s))
+    return
+}
+";
+        assert_eq!(format(source), expected);
+    }
+
+    #[test]
+    fn format_imports() {
+        let source = "import   geometry.vector   as vec\nimport math:{ add,mul }\n";
+        let expected = "import geometry.vector as vec\nimport math:{add, mul}\n";
+        assert_eq!(format(source), expected);
+
+        let source = "pub  import math\npub   Int add(Int a) {\nreturn a\n}";
+        let expected = "pub import math\n\npub Int add(Int a) {\n    return a\n}\n";
+        assert_eq!(format(source), expected);
+    }
+
+    #[test]
+    fn format_constants_and_globals() {
+        let source = "pub   const Int MAX=10\nconst Float HALF = 1.0/2.0\nlet   count=0\nInt total = MAX*2\nVoid main() {\nprint(math:PI)\n}";
+        let expected = "pub const Int MAX = 10
+const Float HALF = 1.0 / 2.0
+let count = 0
+Int total = MAX * 2
+
+Void main() {
+    print(math:PI)
+}
+";
+        assert_eq!(format(source), expected);
+    }
+
+    #[test]
+    fn format_keeps_comments_and_blank_lines() {
+        let source = "#!/usr/bin/env -S mluva run
+# module comment
+import math # trailing import
+
+
+# main function
+Void main() { # opening
+    let x = 1 # trailing
+
+
+    # before if
+    if x == 1 {
+        # inside if
+    } # after if
+    # end of main
+}
+# end of file
+";
+        let expected = "#!/usr/bin/env -S mluva run
+# module comment
+import math # trailing import
+
+# main function
+Void main() { # opening
+    let x = 1 # trailing
+
+    # before if
+    if x == 1 {
+        # inside if
+    } # after if
+    # end of main
+}
+# end of file
+";
+        assert_eq!(format(source), expected);
+    }
+
+    #[test]
+    fn format_keeps_required_parentheses() {
+        let source = "Void main() {
+    let a = (1 + 2) * (3 - (4 - 5))
+    let b = 1 - 2 - 3
+    let c = (1 == 2) == false
+    let d = -(1 + 2)
+    let e = (1 + 2).to_string()
+    let f = true && (false || true)
+    let g = (1 + 2) as Float * -(1 as Float) + -1 as Float
+    let h = (1.5 as Int).to_string() as String
+    let i = (1 | 2) & ~3 ^ 4 << (1 & 1) + 1 == 0
+    let k = (6 & 4) != 0 | 1 < 2
+    let j = 1 << 2 >> 3 | 4 & 5 ^ 6
+}
+";
+        let formatted = format(source);
+        assert_eq!(formatted, source);
+    }
+
+    #[test]
+    fn format_if_and_block_expressions() {
+        let source = "let limit = {
+  let a=2
+  a*3 }
+Void main() {
+    let x = if   limit>3 {1}else if limit>2{ 2 } else {3}
+    let y = {x}+1
+    (if x > 1 { 'a' } else { 'b' }).length()
+    let z = {
+        if y > 1 { y = 1
+        }
+        while y < 3 { print(y) }
+        if x == 1 { x } else { y }
+    }
+}";
+        let expected = "let limit = {
+    let a = 2
+    a * 3
+}
+
+Void main() {
+    let x = if limit > 3 { 1 } else if limit > 2 { 2 } else { 3 }
+    let y = { x } + 1
+    (if x > 1 { 'a' } else { 'b' }.length())
+    let z = {
+        if y > 1 {
+            y = 1
+        }
+        while y < 3 {
+            print(y)
+        }
+        if x == 1 { x } else { y }
+    }
+}
+";
+        assert_eq!(format(source), expected);
+    }
+
+    #[test]
+    fn format_keeps_comments_in_expressions() {
+        let source = "Void main() {
+    let x = if true { # first
+        # one
+        1 # trailing one
+    } else {
+        2
+        # after two
+    }
+    let y = {
+      # before
+      let a = 1 # trailing a
+      while a < 3 {
+        # inside while
+        a = a + 1
+      }
+      a
+    } # after block
+    print(x + y)
+}
+";
+        let expected = "Void main() {
+    let x = if true {
+        # first
+        # one
+        1 # trailing one
+    } else {
+        2
+        # after two
+    }
+    let y = {
+        # before
+        let a = 1 # trailing a
+        while a < 3 {
+            # inside while
+            a = a + 1
+        }
+        a
+    } # after block
+    print(x + y)
+}
+";
+        assert_eq!(format(source), expected);
+    }
+
+    #[test]
+    fn format_rejects_invalid_code() {
+        assert!(format_source("Void main() {\n let x = \n}", 0).is_err());
+    }
+}
@@ -3,9 +3,32 @@ use crate::{
     instruction::Instruction,
 };
 
+/// Functions are private by default, only public (`pub`) functions can be called from other modules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visibility {
+    #[default]
+    Private,
+    Public,
+}
+
+impl Visibility {
+    pub fn is_public(self) -> bool {
+        self == Visibility::Public
+    }
+
+    /// Modifier written before the return type, including the separating space.
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Visibility::Private => "",
+            Visibility::Public => "pub ",
+        }
+    }
+}
+
 /// Signiture of an in-language function without name
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionSigniture {
+    pub visibility: Visibility,
     pub return_type: DataType,
     pub params: Vec<Parameter>,
 }
@@ -13,11 +36,17 @@ pub struct FunctionSigniture {
 impl FunctionSigniture {
     pub fn new(return_type: DataType, params: Vec<Parameter>) -> Self {
         Self {
+            visibility: Visibility::Private,
             return_type,
             params,
         }
     }
 
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
     pub fn check_argument_types(
         &self,
         args: &[(DataType, Span)],
@@ -49,6 +78,7 @@ impl FunctionSigniture {
 impl From<SpannedFunctionSigniture> for FunctionSigniture {
     fn from(spanned: SpannedFunctionSigniture) -> Self {
         Self {
+            visibility: spanned.visibility,
             return_type: spanned.return_type,
             params: spanned
                 .params
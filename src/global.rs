@@ -0,0 +1,33 @@
+use crate::{data_type::DataType, function::Visibility, value::Value};
+
+/// Compiled module constant. Uses of the constant are replaced by its value,
+/// the value is kept for dependents and for listings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constant {
+    pub name: String,
+    pub visibility: Visibility,
+    pub value: Value,
+}
+
+impl Constant {
+    pub fn new(name: String, visibility: Visibility, value: Value) -> Self {
+        Self {
+            name,
+            visibility,
+            value,
+        }
+    }
+}
+
+/// Module global, read and written by `load_global` and `store_global` by its slot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Global {
+    pub name: String,
+    pub data_type: DataType,
+}
+
+impl Global {
+    pub fn new(name: String, data_type: DataType) -> Self {
+        Self { name, data_type }
+    }
+}
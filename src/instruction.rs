@@ -1,4 +1,4 @@
-use crate::value::Value;
+use crate::{data_type::DataType, value::Value};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Instruction {
@@ -8,6 +8,13 @@ pub enum Instruction {
     Load {
         slot: u32,
     },
+    /// Stores the value on top of the stack to the global of the current module
+    StoreGlobal {
+        slot: u32,
+    },
+    LoadGlobal {
+        slot: u32,
+    },
     Push(Value),
     Pop,
     Add,
@@ -25,6 +32,15 @@ pub enum Instruction {
     Or,
     Not,
     Negate,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    /// Shifts by an `Int` amount, amounts of at least the bit width shift out all bits
+    ShiftLeft,
+    ShiftRight,
+    /// Converts the value on top of the stack, see `Value::cast`
+    Cast(DataType),
 
     /// Jumps to a specific index in instruction list
     Jump(u32),
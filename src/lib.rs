@@ -1,11 +1,23 @@
+pub mod assembler;
 pub mod ast;
 pub mod bytecode;
 pub mod compiler;
-mod data_type;
+pub mod data_type;
 mod diagnostics;
+pub mod disassembler;
 pub mod errors;
+pub mod formatter;
 pub mod function;
+pub mod global;
 mod instruction;
+pub mod method;
 pub mod module;
+pub mod program;
 pub mod runtime;
+pub mod session;
+pub mod stdlib;
 pub mod value;
+pub mod verifier;
+
+/// Version of the compiler, cached bytecode of other versions is compiled again.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
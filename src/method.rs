@@ -0,0 +1,614 @@
+use crate::{
+    data_type::DataType,
+    diagnostics::Span,
+    errors::{CompileError, RuntimeError},
+    value::{integer_op, Value},
+};
+
+/// Receives the instance and the arguments, their types are already checked.
+type Implementation = fn(&Value, &[Value]) -> Result<Value, RuntimeError>;
+
+/// Method of a builtin type, e.g. `'text'.length()` or `x.sqrt()`. The type checker, the verifier and the
+/// runtime all look methods up in the same table, so signatures and implementations can not
+/// drift apart.
+#[derive(Debug)]
+pub struct Method {
+    pub receiver: DataType,
+    pub name: &'static str,
+    pub params: &'static [DataType],
+    pub return_type: DataType,
+    implementation: Implementation,
+}
+
+const fn method(
+    receiver: DataType,
+    name: &'static str,
+    params: &'static [DataType],
+    return_type: DataType,
+    implementation: Implementation,
+) -> Method {
+    Method {
+        receiver,
+        name,
+        params,
+        return_type,
+        implementation,
+    }
+}
+
+use DataType::{Bool, Byte, Float, Int, Int64, String};
+
+const METHODS: &[Method] = &[
+    method(String, "length", &[], Int, |s, _| {
+        Ok(Value::Int(string(s)?.chars().count() as i32))
+    }),
+    method(String, "contains", &[String], Bool, |s, args| {
+        Ok(Value::Bool(string(s)?.contains(string(&args[0])?)))
+    }),
+    method(String, "starts_with", &[String], Bool, |s, args| {
+        Ok(Value::Bool(string(s)?.starts_with(string(&args[0])?)))
+    }),
+    method(String, "ends_with", &[String], Bool, |s, args| {
+        Ok(Value::Bool(string(s)?.ends_with(string(&args[0])?)))
+    }),
+    method(String, "index_of", &[String], Int, |s, args| {
+        let s = string(s)?;
+        let index = s
+            .find(string(&args[0])?)
+            .map_or(-1, |byte| s[..byte].chars().count() as i32);
+        Ok(Value::Int(index))
+    }),
+    method(String, "substring", &[Int, Int], String, |s, args| {
+        let s = string(s)?;
+        let (start, end) = (int(&args[0])?, int(&args[1])?);
+        let substring = char_range(s, start, end).ok_or_else(|| {
+            RuntimeError::Other(format!(
+                "Substring {}..{} out of range for string of length {}",
+                start,
+                end,
+                s.chars().count()
+            ))
+        })?;
+        Ok(Value::String(substring.to_string()))
+    }),
+    method(String, "replace", &[String, String], String, |s, args| {
+        let from = string(&args[0])?;
+        if from.is_empty() {
+            return Err(RuntimeError::Other(
+                "Cannot replace an empty string".to_string(),
+            ));
+        }
+        Ok(Value::String(string(s)?.replace(from, string(&args[1])?)))
+    }),
+    method(String, "trim", &[], String, |s, _| {
+        Ok(Value::String(string(s)?.trim().to_string()))
+    }),
+    method(String, "to_upper", &[], String, |s, _| {
+        Ok(Value::String(string(s)?.to_uppercase()))
+    }),
+    method(String, "to_lower", &[], String, |s, _| {
+        Ok(Value::String(string(s)?.to_lowercase()))
+    }),
+    method(String, "split_count", &[String], Int, |s, args| {
+        let separator = separator(&args[0])?;
+        Ok(Value::Int(string(s)?.split(separator).count() as i32))
+    }),
+    method(String, "split_nth", &[String, Int], String, |s, args| {
+        let s = string(s)?;
+        let (separator, index) = (separator(&args[0])?, int(&args[1])?);
+        let field = usize::try_from(index)
+            .ok()
+            .and_then(|index| s.split(separator).nth(index))
+            .ok_or_else(|| {
+                RuntimeError::Other(format!(
+                    "Field {} out of range, string has {} fields",
+                    index,
+                    s.split(separator).count()
+                ))
+            })?;
+        Ok(Value::String(field.to_string()))
+    }),
+    method(String, "repeat", &[Int], String, |s, args| {
+        s.mul(args[0].clone())
+    }),
+    method(String, "char_at", &[Int], String, |s, args| {
+        let s = string(s)?;
+        let index = int(&args[0])?;
+        let char = char_range(s, index, index.saturating_add(1)).ok_or_else(|| {
+            RuntimeError::Other(format!(
+                "Index {} out of range for string of length {}",
+                index,
+                s.chars().count()
+            ))
+        })?;
+        Ok(Value::String(char.to_string()))
+    }),
+    // `parse_int` and `parse_float` trap on invalid input, scripts can't recover from that.
+    // The checked way to parse is `parse_int_or`/`parse_float_or` or testing `is_int`/`is_float` first.
+    method(String, "parse_int", &[], Int, |s, _| {
+        let s = string(s)?;
+        s.parse()
+            .map(Value::Int)
+            .map_err(|_| RuntimeError::Other(format!("Cannot parse '{}' as int", s)))
+    }),
+    method(String, "parse_float", &[], Float, |s, _| {
+        let s = string(s)?;
+        s.parse()
+            .map(Value::Float)
+            .map_err(|_| RuntimeError::Other(format!("Cannot parse '{}' as float", s)))
+    }),
+    method(String, "parse_int_or", &[Int], Int, |s, args| {
+        Ok(string(s)?
+            .parse()
+            .map_or_else(|_| args[0].clone(), Value::Int))
+    }),
+    method(String, "parse_float_or", &[Float], Float, |s, args| {
+        Ok(string(s)?
+            .parse()
+            .map_or_else(|_| args[0].clone(), Value::Float))
+    }),
+    method(String, "is_int", &[], Bool, |s, _| {
+        Ok(Value::Bool(string(s)?.parse::<i32>().is_ok()))
+    }),
+    method(String, "is_float", &[], Bool, |s, _| {
+        Ok(Value::Bool(string(s)?.parse::<f64>().is_ok()))
+    }),
+    method(Int, "abs", &[], Int, |i, _| {
+        int(i)?.checked_abs().map(Value::Int).ok_or(overflow("abs"))
+    }),
+    method(Int, "pow", &[Int], Int, |i, args| {
+        let exponent = exponent(&args[0])?;
+        int(i)?
+            .checked_pow(exponent)
+            .map(Value::Int)
+            .ok_or(overflow("pow"))
+    }),
+    method(Int, "min", &[Int], Int, |i, args| {
+        Ok(Value::Int(int(i)?.min(int(&args[0])?)))
+    }),
+    method(Int, "max", &[Int], Int, |i, args| {
+        Ok(Value::Int(int(i)?.max(int(&args[0])?)))
+    }),
+    method(Int, "clamp", &[Int, Int], Int, |i, args| {
+        let (min, max) = (int(&args[0])?, int(&args[1])?);
+        if min > max {
+            return Err(invalid_clamp_range(&args[0], &args[1]));
+        }
+        Ok(Value::Int(int(i)?.clamp(min, max)))
+    }),
+    method(Int, "to_float", &[], Float, |i, _| i.cast(Float)),
+    method(Int, "to_string", &[], String, |i, _| i.cast(String)),
+    method(Int, "wrapping_add", &[Int], Int, wrapping_add),
+    method(Int, "wrapping_sub", &[Int], Int, wrapping_sub),
+    method(Int, "wrapping_mul", &[Int], Int, wrapping_mul),
+    method(Int, "wrapping_neg", &[], Int, |i, _| {
+        Ok(Value::Int(int(i)?.wrapping_neg()))
+    }),
+    method(Int64, "abs", &[], Int64, |i, _| {
+        int64(i)?
+            .checked_abs()
+            .map(Value::Int64)
+            .ok_or(overflow("abs"))
+    }),
+    method(Int64, "pow", &[Int], Int64, |i, args| {
+        let exponent = exponent(&args[0])?;
+        int64(i)?
+            .checked_pow(exponent)
+            .map(Value::Int64)
+            .ok_or(overflow("pow"))
+    }),
+    method(Int64, "min", &[Int64], Int64, integer_min),
+    method(Int64, "max", &[Int64], Int64, integer_max),
+    method(Int64, "clamp", &[Int64, Int64], Int64, |i, args| {
+        let (min, max) = (int64(&args[0])?, int64(&args[1])?);
+        if min > max {
+            return Err(invalid_clamp_range(&args[0], &args[1]));
+        }
+        Ok(Value::Int64(int64(i)?.clamp(min, max)))
+    }),
+    method(Int64, "to_float", &[], Float, |i, _| i.cast(Float)),
+    method(Int64, "to_string", &[], String, |i, _| i.cast(String)),
+    method(Int64, "wrapping_add", &[Int64], Int64, wrapping_add),
+    method(Int64, "wrapping_sub", &[Int64], Int64, wrapping_sub),
+    method(Int64, "wrapping_mul", &[Int64], Int64, wrapping_mul),
+    method(Int64, "wrapping_neg", &[], Int64, |i, _| {
+        Ok(Value::Int64(int64(i)?.wrapping_neg()))
+    }),
+    method(Byte, "min", &[Byte], Byte, integer_min),
+    method(Byte, "max", &[Byte], Byte, integer_max),
+    method(Byte, "to_int", &[], Int, |b, _| b.cast(Int)),
+    method(Byte, "to_string", &[], String, |b, _| b.cast(String)),
+    method(Byte, "wrapping_add", &[Byte], Byte, wrapping_add),
+    method(Byte, "wrapping_sub", &[Byte], Byte, wrapping_sub),
+    method(Byte, "wrapping_mul", &[Byte], Byte, wrapping_mul),
+    method(Float, "abs", &[], Float, |x, _| map_float(x, f64::abs)),
+    method(Float, "pow", &[Float], Float, |x, args| {
+        Ok(Value::Float(float(x)?.powf(float(&args[0])?)))
+    }),
+    method(Float, "sqrt", &[], Float, |x, _| map_float(x, f64::sqrt)),
+    method(Float, "floor", &[], Float, |x, _| map_float(x, f64::floor)),
+    method(Float, "ceil", &[], Float, |x, _| map_float(x, f64::ceil)),
+    method(Float, "round", &[], Float, |x, _| map_float(x, f64::round)),
+    method(Float, "min", &[Float], Float, |x, args| {
+        Ok(Value::Float(float(x)?.min(float(&args[0])?)))
+    }),
+    method(Float, "max", &[Float], Float, |x, args| {
+        Ok(Value::Float(float(x)?.max(float(&args[0])?)))
+    }),
+    method(Float, "clamp", &[Float, Float], Float, |x, args| {
+        let (min, max) = (float(&args[0])?, float(&args[1])?);
+        if min.is_nan() || max.is_nan() || min > max {
+            return Err(invalid_clamp_range(&args[0], &args[1]));
+        }
+        Ok(Value::Float(float(x)?.clamp(min, max)))
+    }),
+    method(Float, "to_int", &[], Int, |x, _| x.cast(Int)),
+    method(Float, "to_string", &[], String, |x, _| x.cast(String)),
+    method(Float, "sin", &[], Float, |x, _| map_float(x, f64::sin)),
+    method(Float, "cos", &[], Float, |x, _| map_float(x, f64::cos)),
+    method(Float, "tan", &[], Float, |x, _| map_float(x, f64::tan)),
+    method(Float, "atan", &[], Float, |x, _| map_float(x, f64::atan)),
+    method(Float, "atan2", &[Float], Float, |y, args| {
+        Ok(Value::Float(float(y)?.atan2(float(&args[0])?)))
+    }),
+    method(Float, "log", &[], Float, |x, _| map_float(x, f64::ln)),
+    method(Float, "log10", &[], Float, |x, _| map_float(x, f64::log10)),
+    method(Float, "exp", &[], Float, |x, _| map_float(x, f64::exp)),
+];
+
+impl Method {
+    pub fn find(receiver: DataType, name: &str) -> Option<&'static Method> {
+        METHODS
+            .iter()
+            .find(|method| method.receiver == receiver && method.name == name)
+    }
+
+    /// Methods of the type in the order of the table.
+    pub fn of_type(receiver: DataType) -> impl Iterator<Item = &'static Method> {
+        METHODS
+            .iter()
+            .filter(move |method| method.receiver == receiver)
+    }
+
+    /// Checks types of the arguments and returns the type of the result.
+    pub fn check_argument_types(
+        &self,
+        args: &[(DataType, Span)],
+        call_span: Span,
+    ) -> Result<DataType, CompileError> {
+        if args.len() != self.params.len() {
+            return Err(CompileError::wrong_number_of_arguments_at(
+                self.params.len(),
+                args.len(),
+                call_span,
+            ));
+        }
+
+        for (param, (arg, arg_span)) in self.params.iter().zip(args) {
+            if param != arg {
+                return Err(CompileError::wrong_type_at(*param, *arg, *arg_span));
+            }
+        }
+
+        Ok(self.return_type)
+    }
+
+    pub fn call(&self, instance: &Value, args: &[Value]) -> Result<Value, RuntimeError> {
+        let types_match = instance.get_type() == self.receiver
+            && args.len() == self.params.len()
+            && args
+                .iter()
+                .zip(self.params)
+                .all(|(a, p)| a.get_type() == *p);
+        if !types_match {
+            return Err(RuntimeError::TypeError);
+        }
+
+        (self.implementation)(instance, args)
+    }
+}
+
+fn string(value: &Value) -> Result<&str, RuntimeError> {
+    match value {
+        Value::String(s) => Ok(s),
+        _ => Err(RuntimeError::TypeError),
+    }
+}
+
+fn int(value: &Value) -> Result<i32, RuntimeError> {
+    match value {
+        Value::Int(i) => Ok(*i),
+        _ => Err(RuntimeError::TypeError),
+    }
+}
+
+fn int64(value: &Value) -> Result<i64, RuntimeError> {
+    match value {
+        Value::Int64(i) => Ok(*i),
+        _ => Err(RuntimeError::TypeError),
+    }
+}
+
+fn float(value: &Value) -> Result<f64, RuntimeError> {
+    match value {
+        Value::Float(x) => Ok(*x),
+        _ => Err(RuntimeError::TypeError),
+    }
+}
+
+fn map_float(value: &Value, f: fn(f64) -> f64) -> Result<Value, RuntimeError> {
+    Ok(Value::Float(f(float(value)?)))
+}
+
+fn overflow(operation: &'static str) -> RuntimeError {
+    RuntimeError::IntegerOverflow { operation }
+}
+
+/// Exponent of an integer `pow`, negative exponents have no integer result.
+fn exponent(value: &Value) -> Result<u32, RuntimeError> {
+    let exponent = int(value)?;
+    u32::try_from(exponent).map_err(|_| {
+        RuntimeError::Other(format!(
+            "Negative exponent {} of integer pow, use Float pow instead",
+            exponent
+        ))
+    })
+}
+
+fn integer_min(lhs: &Value, args: &[Value]) -> Result<Value, RuntimeError> {
+    integer_op(
+        lhs,
+        &args[0],
+        "min",
+        |a, b| Some(a.min(b)),
+        |a, b| Some(a.min(b)),
+        |a, b| Some(a.min(b)),
+    )
+}
+
+fn integer_max(lhs: &Value, args: &[Value]) -> Result<Value, RuntimeError> {
+    integer_op(
+        lhs,
+        &args[0],
+        "max",
+        |a, b| Some(a.max(b)),
+        |a, b| Some(a.max(b)),
+        |a, b| Some(a.max(b)),
+    )
+}
+
+fn wrapping_add(lhs: &Value, args: &[Value]) -> Result<Value, RuntimeError> {
+    integer_op(
+        lhs,
+        &args[0],
+        "wrapping_add",
+        |a, b| Some(a.wrapping_add(b)),
+        |a, b| Some(a.wrapping_add(b)),
+        |a, b| Some(a.wrapping_add(b)),
+    )
+}
+
+fn wrapping_sub(lhs: &Value, args: &[Value]) -> Result<Value, RuntimeError> {
+    integer_op(
+        lhs,
+        &args[0],
+        "wrapping_sub",
+        |a, b| Some(a.wrapping_sub(b)),
+        |a, b| Some(a.wrapping_sub(b)),
+        |a, b| Some(a.wrapping_sub(b)),
+    )
+}
+
+fn wrapping_mul(lhs: &Value, args: &[Value]) -> Result<Value, RuntimeError> {
+    integer_op(
+        lhs,
+        &args[0],
+        "wrapping_mul",
+        |a, b| Some(a.wrapping_mul(b)),
+        |a, b| Some(a.wrapping_mul(b)),
+        |a, b| Some(a.wrapping_mul(b)),
+    )
+}
+
+fn invalid_clamp_range(min: &Value, max: &Value) -> RuntimeError {
+    RuntimeError::Other(format!(
+        "Invalid clamp range, min {} is above max {}",
+        min, max
+    ))
+}
+
+fn separator(value: &Value) -> Result<&str, RuntimeError> {
+    let separator = string(value)?;
+    if separator.is_empty() {
+        return Err(RuntimeError::Other(
+            "Separator can not be empty".to_string(),
+        ));
+    }
+
+    Ok(separator)
+}
+
+/// Characters of the string from `start` up to `end`, `None` when the range is out of bounds.
+fn char_range(s: &str, start: i32, end: i32) -> Option<&str> {
+    let start = usize::try_from(start).ok()?;
+    let end = usize::try_from(end).ok()?;
+    if end < start {
+        return None;
+    }
+
+    let mut offsets = s
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(s.len()));
+    let start_offset = offsets.nth(start)?;
+    let end_offset = match end - start {
+        0 => start_offset,
+        length => offsets.nth(length - 1)?,
+    };
+
+    Some(&s[start_offset..end_offset])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn call(instance: &str, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        let instance = Value::String(instance.to_string());
+        Method::find(DataType::String, name)
+            .unwrap()
+            .call(&instance, args)
+    }
+
+    #[test]
+    fn string_methods() {
+        let s = |s: &str| Value::String(s.to_string());
+
+        assert_eq!(call("čaj", "length", &[]).unwrap(), Value::Int(3));
+        assert_eq!(call("a,b,c", "index_of", &[s("b")]).unwrap(), Value::Int(2));
+        assert_eq!(call("abc", "index_of", &[s("x")]).unwrap(), Value::Int(-1));
+        assert_eq!(
+            call("čajník", "substring", &[Value::Int(1), Value::Int(4)]).unwrap(),
+            s("ajn")
+        );
+        assert_eq!(
+            call("abc", "substring", &[Value::Int(3), Value::Int(3)]).unwrap(),
+            s("")
+        );
+        assert!(call("abc", "substring", &[Value::Int(2), Value::Int(4)]).is_err());
+        assert!(call("abc", "substring", &[Value::Int(2), Value::Int(1)]).is_err());
+        assert_eq!(call("abc", "char_at", &[Value::Int(2)]).unwrap(), s("c"));
+        assert!(call("abc", "char_at", &[Value::Int(-1)]).is_err());
+
+        assert_eq!(
+            call("a;b;;c", "split_count", &[s(";")]).unwrap(),
+            Value::Int(4)
+        );
+        assert_eq!(
+            call("a;b;;c", "split_nth", &[s(";"), Value::Int(3)]).unwrap(),
+            s("c")
+        );
+        assert!(call("a;b", "split_nth", &[s(";"), Value::Int(2)]).is_err());
+        assert!(call("a;b", "split_count", &[s("")]).is_err());
+
+        assert_eq!(call("-42", "parse_int", &[]).unwrap(), Value::Int(-42));
+        assert!(call("4 2", "parse_int", &[]).is_err());
+        assert_eq!(call("2.5", "parse_float", &[]).unwrap(), Value::Float(2.5));
+        assert_eq!(call("x", "is_float", &[]).unwrap(), Value::Bool(false));
+        assert_eq!(
+            call("12", "parse_int_or", &[Value::Int(-1)]).unwrap(),
+            Value::Int(12)
+        );
+        assert_eq!(
+            call("1.5", "parse_int_or", &[Value::Int(-1)]).unwrap(),
+            Value::Int(-1)
+        );
+        assert_eq!(
+            call("x", "parse_float_or", &[Value::Float(0.5)]).unwrap(),
+            Value::Float(0.5)
+        );
+
+        assert!(matches!(
+            call("abc", "contains", &[Value::Int(1)]),
+            Err(RuntimeError::TypeError)
+        ));
+    }
+
+    #[test]
+    fn numeric_methods() {
+        let call = |instance: Value, name: &str, args: &[Value]| {
+            Method::find(instance.get_type(), name)
+                .unwrap()
+                .call(&instance, args)
+        };
+
+        assert_eq!(call(Value::Int(-3), "abs", &[]).unwrap(), Value::Int(3));
+        assert!(call(Value::Int(i32::MIN), "abs", &[]).is_err());
+        assert_eq!(
+            call(Value::Int(2), "pow", &[Value::Int(10)]).unwrap(),
+            Value::Int(1024)
+        );
+        assert!(call(Value::Int(2), "pow", &[Value::Int(-1)]).is_err());
+        assert!(matches!(
+            call(Value::Int(2), "pow", &[Value::Int(31)]),
+            Err(RuntimeError::IntegerOverflow { operation: "pow" })
+        ));
+        assert_eq!(
+            call(Value::Int(i32::MAX), "wrapping_add", &[Value::Int(1)]).unwrap(),
+            Value::Int(i32::MIN)
+        );
+        assert_eq!(
+            call(Value::Int(i32::MIN), "wrapping_neg", &[]).unwrap(),
+            Value::Int(i32::MIN)
+        );
+        assert_eq!(
+            call(Value::Byte(0), "wrapping_sub", &[Value::Byte(1)]).unwrap(),
+            Value::Byte(255)
+        );
+        assert_eq!(
+            call(Value::Int64(2), "pow", &[Value::Int(40)]).unwrap(),
+            Value::Int64(1 << 40)
+        );
+        assert_eq!(
+            call(Value::Int64(3), "max", &[Value::Int64(-5)]).unwrap(),
+            Value::Int64(3)
+        );
+        assert_eq!(
+            call(Value::Int(7), "clamp", &[Value::Int(0), Value::Int(5)]).unwrap(),
+            Value::Int(5)
+        );
+        assert!(call(Value::Int(7), "clamp", &[Value::Int(5), Value::Int(0)]).is_err());
+        assert_eq!(
+            call(Value::Int(7), "to_string", &[]).unwrap(),
+            Value::String("7".to_string())
+        );
+
+        assert_eq!(
+            call(Value::Float(2.5), "round", &[]).unwrap(),
+            Value::Float(3.0)
+        );
+        assert_eq!(
+            call(Value::Float(-2.5), "floor", &[]).unwrap(),
+            Value::Float(-3.0)
+        );
+        assert_eq!(
+            call(Value::Float(-2.9), "to_int", &[]).unwrap(),
+            Value::Int(-2)
+        );
+        assert!(call(Value::Float(f64::NAN), "to_int", &[]).is_err());
+        assert!(call(Value::Float(3e9), "to_int", &[]).is_err());
+        assert!(call(
+            Value::Float(1.0),
+            "clamp",
+            &[Value::Float(f64::NAN), Value::Float(2.0)]
+        )
+        .is_err());
+        assert_eq!(
+            call(Value::Float(0.0), "atan2", &[Value::Float(1.0)]).unwrap(),
+            Value::Float(0.0)
+        );
+    }
+
+    #[test]
+    fn check_argument_types() {
+        let span = Span::new(0, 0, 1);
+        let replace = Method::find(DataType::String, "replace").unwrap();
+
+        let args = [(DataType::String, span), (DataType::String, span)];
+        assert_eq!(
+            replace.check_argument_types(&args, span).unwrap(),
+            DataType::String
+        );
+
+        let args = [
+            (DataType::String, span),
+            (DataType::Int, Span::new(0, 2, 3)),
+        ];
+        let error = replace.check_argument_types(&args, span).unwrap_err();
+        assert_eq!(error.message, "wrong type: expected string, found int");
+        assert_eq!(error.span.map(|s| (s.lo, s.hi)), Some((2, 3)));
+
+        assert!(replace.check_argument_types(&args[..1], span).is_err());
+        assert!(Method::find(DataType::Int, "length").is_none());
+    }
+}
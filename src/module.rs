@@ -2,22 +2,31 @@ use std::collections::HashMap;
 
 use crate::{
     bytecode::{read_fn_map_bytecode, write_fn_map_bytecode, BytecodeHeader, BytecodeSerializable},
-    compiler::{tokenize, Compiler, Parser, TypeChecker},
+    compiler::{module_interfaces, tokenize, Compiler, Parser, TypeChecker},
     diagnostics::FileId,
     errors::{CompileError, RuntimeError},
     function::{FunctionSigniture, FunctionSource},
+    global::{Constant, Global},
     runtime::Runtime,
     value::Value,
+    verifier::verify,
 };
 
+#[derive(Debug, Clone)]
 pub struct Module {
     main_slot: Option<u32>,
     function_map: HashMap<String, u32>,
     function_signitures: Vec<FunctionSigniture>,
     function_sources: Vec<FunctionSource>,
+    constants: Vec<Constant>,
+    globals: Vec<Global>,
+    /// Initializer of globals, runs once before the module is first used
+    init_source: Option<FunctionSource>,
 }
 
 impl Module {
+    pub const TEST_PREFIX: &'static str = "test_";
+
     pub fn new(
         main_slot: Option<u32>,
         function_map: HashMap<String, u32>,
@@ -29,22 +38,61 @@ impl Module {
             function_map,
             function_signitures,
             function_sources,
+            constants: vec![],
+            globals: vec![],
+            init_source: None,
         }
     }
 
     pub fn empty() -> Self {
-        Self {
-            main_slot: None,
-            function_map: HashMap::new(),
-            function_signitures: vec![],
-            function_sources: vec![],
-        }
+        Self::new(None, HashMap::new(), vec![], vec![])
+    }
+
+    pub fn with_constants(mut self, constants: Vec<Constant>) -> Self {
+        self.constants = constants;
+        self
+    }
+
+    /// Sets globals of the module, `init_source` stores their initial values.
+    pub fn with_globals(
+        mut self,
+        globals: Vec<Global>,
+        init_source: Option<FunctionSource>,
+    ) -> Self {
+        self.globals = globals;
+        self.init_source = init_source;
+        self
+    }
+
+    pub(crate) fn add_constant(&mut self, constant: Constant) {
+        self.constants.push(constant);
     }
 
     pub fn is_executable(&self) -> bool {
         self.main_slot.is_some()
     }
 
+    /// Names of test functions in the order of definition.
+    /// Test function has name starting with `test_` and takes no parameters.
+    pub fn get_test_names(&self) -> Vec<&str> {
+        let mut tests: Vec<_> = self
+            .function_map
+            .iter()
+            .filter(|(name, slot)| {
+                name.starts_with(Self::TEST_PREFIX)
+                    && self.function_signitures[**slot as usize].params.is_empty()
+            })
+            .map(|(name, slot)| (*slot, name.as_str()))
+            .collect();
+
+        tests.sort();
+        tests.into_iter().map(|(_, name)| name).collect()
+    }
+
+    pub fn get_main_slot(&self) -> Option<u32> {
+        self.main_slot
+    }
+
     pub fn get_main_source(&self) -> Option<&FunctionSource> {
         let slot = self.main_slot?;
         self.function_sources.get(slot as usize)
@@ -58,15 +106,59 @@ impl Module {
         self.function_sources.get(slot as usize)
     }
 
+    pub fn get_function_signiture_by_slot(&self, slot: u32) -> Option<&FunctionSigniture> {
+        self.function_signitures.get(slot as usize)
+    }
+
+    pub fn get_function_map(&self) -> &HashMap<String, u32> {
+        &self.function_map
+    }
+
     pub fn get_function_signiture(&self, name: &str) -> Option<&FunctionSigniture> {
         let slot = self.function_map.get(name)?;
         self.function_signitures.get(*slot as usize)
     }
 
+    /// Names of all functions with their signitures, in slot order.
+    /// Names pointing to a missing slot are skipped, the verifier reports them.
+    pub fn get_functions(&self) -> Vec<(&str, &FunctionSigniture)> {
+        let mut functions: Vec<_> = self
+            .function_map
+            .iter()
+            .map(|(name, slot)| (*slot, name.as_str()))
+            .collect();
+
+        functions.sort();
+        functions
+            .into_iter()
+            .filter_map(|(slot, name)| {
+                let signiture = self.function_signitures.get(slot as usize)?;
+                Some((name, signiture))
+            })
+            .collect()
+    }
+
     pub fn get_slot(&self, name: &str) -> Option<u32> {
         self.function_map.get(name).copied()
     }
 
+    pub fn get_constants(&self) -> &[Constant] {
+        &self.constants
+    }
+
+    pub fn get_constant(&self, name: &str) -> Option<&Constant> {
+        self.constants.iter().find(|constant| constant.name == name)
+    }
+
+    /// Globals in slot order.
+    pub fn get_globals(&self) -> &[Global] {
+        &self.globals
+    }
+
+    pub fn get_init_source(&self) -> Option<&FunctionSource> {
+        self.init_source.as_ref()
+    }
+
     pub fn from_string(input: &str, file_id: FileId) -> Result<Self, CompileError> {
         let dependencies = HashMap::new();
 
@@ -89,43 +181,115 @@ impl Module {
         Ok(module)
     }
 
+    /// Interfaces of modules that can import each other, keyed like `modules`: slots and
+    /// signitures of functions and values of constants, without function bodies. Every module
+    /// of the group can then be compiled with `from_ast_and_dependencies` against the interfaces.
+    /// `dependencies` are compiled modules outside of the group.
+    pub fn interfaces(
+        modules: &HashMap<String, crate::ast::Ast>,
+        dependencies: &HashMap<String, Module>,
+    ) -> Result<HashMap<String, Module>, CompileError> {
+        module_interfaces(modules, dependencies)
+    }
+
     // TODO: rename this to something more meaningful
     pub fn from_bytecode_bytes(bytes: &[u8]) -> Result<Self, String> {
         Self::from_bytecode(bytes, &mut 0)
     }
 
+    /// Reads module from bytecode and verifies it, see `verifier::verify`.
+    /// Bytecode that was not produced by the compiler in this process must be loaded this way.
+    pub fn from_verified_bytecode(
+        bytes: &[u8],
+        dependencies: &HashMap<String, Module>,
+    ) -> Result<Self, String> {
+        let module = Self::from_bytecode_bytes(bytes)?;
+        verify(&module, dependencies)?;
+        Ok(module)
+    }
+
     pub fn to_bytecode(&self) -> Vec<u8> {
         let mut buffer = Vec::new();
         self.write_bytecode(&mut buffer);
         buffer
     }
 
+    /// Names, slots and signitures of all functions and public constants, the part of the module
+    /// its dependents are compiled against. Changes of function bodies do not change the interface,
+    /// values of public constants do since they are inlined.
+    pub fn interface_bytecode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let mut slots: Vec<_> = self
+            .function_map
+            .iter()
+            .map(|(name, slot)| (*slot, name))
+            .collect();
+        slots.sort();
+        for (slot, name) in slots {
+            name.write_bytecode(&mut buffer);
+            slot.write_bytecode(&mut buffer);
+            self.function_signitures[slot as usize].write_bytecode(&mut buffer);
+        }
+
+        for constant in &self.constants {
+            if constant.visibility.is_public() {
+                constant.write_bytecode(&mut buffer);
+            }
+        }
+
+        buffer
+    }
+
     pub fn execute_without_dependencies(&self) -> Result<Value, RuntimeError> {
         Runtime::new(self, &HashMap::new()).execute()
     }
 }
 
+// Compiled modules are shared between threads (see `Program`), keep them `Send + Sync`.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Module>();
+};
+
 impl BytecodeSerializable for Module {
     fn from_bytecode(bytes: &[u8], cursor: &mut usize) -> Result<Self, String> {
         let header = BytecodeHeader::from_bytecode(bytes, cursor)?;
 
         let function_map = read_fn_map_bytecode(bytes, cursor, header.function_count as usize)?;
 
-        let mut function_signitures = Vec::with_capacity(header.function_count as usize);
+        // counts come from untrusted bytecode, vectors are not preallocated with them
+        let mut function_signitures = Vec::new();
         for _ in 0..header.function_count {
             function_signitures.push(FunctionSigniture::from_bytecode(bytes, cursor)?);
         }
 
-        let mut function_sources = Vec::with_capacity(header.function_count as usize);
+        let mut function_sources = Vec::new();
         for _ in 0..header.function_count {
             function_sources.push(FunctionSource::from_bytecode(bytes, cursor)?);
         }
 
+        let constant_count = usize::from_bytecode(bytes, cursor)?;
+        let mut constants = Vec::new();
+        for _ in 0..constant_count {
+            constants.push(Constant::from_bytecode(bytes, cursor)?);
+        }
+
+        let global_count = usize::from_bytecode(bytes, cursor)?;
+        let mut globals = Vec::new();
+        for _ in 0..global_count {
+            globals.push(Global::from_bytecode(bytes, cursor)?);
+        }
+
+        let init_source = Option::<FunctionSource>::from_bytecode(bytes, cursor)?;
+
         Ok(Self {
             main_slot: header.main_slot,
             function_map,
             function_signitures,
             function_sources,
+            constants,
+            globals,
+            init_source,
         })
     }
 
@@ -142,5 +306,137 @@ impl BytecodeSerializable for Module {
         for src in &self.function_sources {
             src.write_bytecode(buffer);
         }
+
+        self.constants.len().write_bytecode(buffer);
+        for constant in &self.constants {
+            constant.write_bytecode(buffer);
+        }
+
+        self.globals.len().write_bytecode(buffer);
+        for global in &self.globals {
+            global.write_bytecode(buffer);
+        }
+
+        self.init_source.write_bytecode(buffer);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_names_in_definition_order() {
+        let source = "
+Void test_b() {
+}
+
+Void helper() {
+}
+
+Void test_with_param(Int x) {
+}
+
+Void test_a() {
+    assert_eq(1 + 1, 2)
+}
+";
+        let module = Module::from_string(source, 0).unwrap();
+        assert_eq!(module.get_test_names(), vec!["test_b", "test_a"]);
+    }
+
+    #[test]
+    fn interface_ignores_function_bodies() {
+        let interface = |source| Module::from_string(source, 0).unwrap().interface_bytecode();
+
+        let original = interface("Int add(Int a, Int b) {\n return a + b\n}");
+        let body_changed = interface("Int add(Int a, Int b) {\n return b + a + 0\n}");
+        let signiture_changed = interface("Int add(Int a, Float b) {\n return a\n}");
+        let slots_changed =
+            interface("Void helper() {\n}\nInt add(Int a, Int b) {\n return a + b\n}");
+
+        assert_eq!(original, body_changed);
+        assert_ne!(original, signiture_changed);
+        assert_ne!(original, slots_changed);
+    }
+
+    #[test]
+    fn modules_importing_each_other() {
+        let sources = [
+            ("a", "import b\npub const Int X = b:Y + 1\npub Int f(Int n) {\n if n == 0 {\n return X\n }\n return b:g(n - 1)\n}"),
+            ("b", "import a\npub const Int Y = 1\npub Int g(Int n) {\n return a:f(n) * 2\n}\nInt main() {\n return g(2)\n}"),
+        ];
+        let asts: HashMap<String, crate::ast::Ast> = sources
+            .iter()
+            .map(|(name, source)| {
+                (
+                    name.to_string(),
+                    crate::ast::Ast::from_string(source, 0).unwrap(),
+                )
+            })
+            .collect();
+
+        let interfaces = Module::interfaces(&asts, &HashMap::new()).unwrap();
+        assert_eq!(
+            interfaces["a"].get_constant("X").unwrap().value,
+            Value::Int(2)
+        );
+
+        let modules: HashMap<String, Module> = asts
+            .into_iter()
+            .map(|(name, ast)| {
+                let module = Module::from_ast_and_dependencies(ast, &interfaces).unwrap();
+                assert_eq!(
+                    module.interface_bytecode(),
+                    interfaces[&name].interface_bytecode()
+                );
+                (name, module)
+            })
+            .collect();
+
+        let result = Runtime::new(&modules["b"], &modules).execute();
+        assert_eq!(result.unwrap(), Value::Int(16));
+
+        let group_interfaces = |a: &str, b: &str| {
+            let asts = HashMap::from([
+                ("a".to_string(), crate::ast::Ast::from_string(a, 0).unwrap()),
+                ("b".to_string(), crate::ast::Ast::from_string(b, 0).unwrap()),
+            ]);
+            Module::interfaces(&asts, &HashMap::new())
+        };
+
+        // constants wait only for the constants they use
+        let chained = group_interfaces(
+            "import b\npub const Int X = b:Y\npub const Int Z = 1",
+            "import a\npub const Int Y = a:Z\nconst Int W = a:X",
+        );
+        assert_eq!(
+            chained.unwrap()["b"].get_constant("W").unwrap().value,
+            Value::Int(1)
+        );
+
+        let error = group_interfaces(
+            "import b\npub const Int X = b:Y\n",
+            "import a\npub const Int Y = a:X + 1",
+        )
+        .unwrap_err();
+        assert_eq!(error.message, "value of constant a:X depends on itself");
+
+        let error = group_interfaces("pub import b\n", "pub import a\n").unwrap_err();
+        assert_eq!(error.message, "cyclic re-export: a -> b -> a");
+    }
+
+    #[test]
+    fn interface_contains_public_constants() {
+        let interface = |source| Module::from_string(source, 0).unwrap().interface_bytecode();
+
+        let original = interface("pub const Int MAX = 10\nconst Int MIN = 0\nlet count = 0\n");
+        let private_changed =
+            interface("pub const Int MAX = 10\nconst Int MIN = 1\nlet count = 5\n");
+        let public_changed =
+            interface("pub const Int MAX = 20\nconst Int MIN = 0\nlet count = 0\n");
+
+        assert_eq!(original, private_changed);
+        assert_ne!(original, public_changed);
     }
 }
@@ -0,0 +1,105 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{errors::RuntimeError, module::Module, runtime::Runtime, value::Value};
+
+/// Compiled root module bundled together with all of its dependencies.
+/// Cloning is cheap, so one program can be shared and executed on many threads at once,
+/// each execution gets its own `Runtime` with separate stack.
+#[derive(Debug, Clone)]
+pub struct Program {
+    root: Arc<Module>,
+    dependencies: Arc<HashMap<String, Module>>,
+}
+
+impl Program {
+    pub fn new(root: Module, dependencies: HashMap<String, Module>) -> Self {
+        Self {
+            root: Arc::new(root),
+            dependencies: Arc::new(dependencies),
+        }
+    }
+
+    pub fn without_dependencies(root: Module) -> Self {
+        Self::new(root, HashMap::new())
+    }
+
+    pub fn root(&self) -> &Module {
+        &self.root
+    }
+
+    pub fn dependencies(&self) -> &HashMap<String, Module> {
+        &self.dependencies
+    }
+
+    pub fn execute(&self) -> Result<Value, RuntimeError> {
+        Runtime::new(&self.root, &self.dependencies).execute()
+    }
+
+    pub fn call(&self, function_name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        Runtime::new(&self.root, &self.dependencies).call(function_name, args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::Ast;
+
+    const MATH_SOURCE: &str = "
+Int double(Int x) {
+    return x * 2
+}
+
+pub Int quadruple(Int x) {
+    return double(double(x))
+}
+";
+
+    const MAIN_SOURCE: &str = "
+import math
+
+Int main() {
+    let i = 0
+    let sum = 0
+    while i < 1000 {
+        sum = sum + math:quadruple(i) % 7
+        i = i + 1
+    }
+    return sum
+}
+";
+
+    fn compile_program() -> Program {
+        let math = Module::from_string(MATH_SOURCE, 0).unwrap();
+        let mut dependencies = HashMap::new();
+        dependencies.insert("math".to_string(), math);
+
+        let ast = Ast::from_string(MAIN_SOURCE, 1).unwrap();
+        let root = Module::from_ast_and_dependencies(ast, &dependencies).unwrap();
+
+        Program::new(root, dependencies)
+    }
+
+    #[test]
+    fn program_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Program>();
+    }
+
+    #[test]
+    fn execute_program_in_parallel() {
+        let program = compile_program();
+        let expected = program.execute().unwrap();
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let program = program.clone();
+                std::thread::spawn(move || program.execute().unwrap())
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    }
+}
@@ -6,43 +6,145 @@ use crate::instruction::Instruction;
 use crate::module::Module;
 use crate::value::Value;
 
+/// Values of module globals keyed by the module name. Every execution has its own globals,
+/// a module is initialized when it is used for the first time.
+type Globals = HashMap<String, Vec<Value>>;
+
+/// Key of the executed module in `Globals` when it is not one of the dependencies,
+/// dependencies never have an empty name.
+const ROOT_MODULE: &str = "";
+
 pub struct Runtime<'a> {
     module: &'a Module,
+    /// key of the module in `globals`, shared with calls from modules importing it
+    module_name: &'a str,
     dependencies: &'a HashMap<String, Module>,
     stack: Vec<Value>,
+    globals: Globals,
+    args: &'a [String],
 }
 
 impl<'a> Runtime<'a> {
+    /// When `module` is one of the `dependencies`, e.g. a module of cyclic imports,
+    /// calls from other modules into it use the same globals.
     pub fn new(module: &'a Module, dependencies: &'a HashMap<String, Module>) -> Self {
+        let module_name = dependencies
+            .iter()
+            .find(|(_, dependency)| std::ptr::eq(*dependency, module))
+            .map_or(ROOT_MODULE, |(name, _)| name.as_str());
+
         Self {
             module,
+            module_name,
             dependencies,
             stack: vec![],
+            globals: HashMap::new(),
+            args: &[],
         }
     }
 
+    /// Sets command line arguments, available through `arg_count()` and `arg(i)` builtins.
+    pub fn with_args(mut self, args: &'a [String]) -> Self {
+        self.args = args;
+        self
+    }
+
     pub fn execute(mut self) -> Result<Value, RuntimeError> {
         let main_function = self.module.get_main_source().ok_or(RuntimeError::Other(
             "Module is not executable (missing main function)".to_string(),
         ))?;
 
-        let functions = self.module.get_sources();
-        let val = InternalFunctionRuntime::new(
+        let val = self.function_runtime(main_function).run()?;
+
+        Ok(val)
+    }
+
+    fn function_runtime(&mut self, source: &'a FunctionSource) -> InternalFunctionRuntime<'_> {
+        InternalFunctionRuntime::new(
             self.dependencies,
-            functions,
+            self.args,
+            &mut self.globals,
             &mut self.stack,
-            main_function,
+            self.module_name,
+            self.module,
+            source,
         )
-        .interpret()?;
+    }
 
-        Ok(val)
+    /// Executes function source with given local slots, used by interactive session
+    /// where variables keep their values between executions.
+    pub(crate) fn execute_with_slots(
+        mut self,
+        source: &FunctionSource,
+        slots: &mut Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let mut function_runtime = InternalFunctionRuntime::new(
+            self.dependencies,
+            self.args,
+            &mut self.globals,
+            &mut self.stack,
+            self.module_name,
+            self.module,
+            source,
+        );
+
+        slots.resize(source.slot_count, Value::Void);
+        function_runtime.slots = std::mem::take(slots);
+        let result = function_runtime.run();
+        *slots = function_runtime.slots;
+
+        result
+    }
+
+    /// Calls function of the module by name with given arguments.
+    /// Arguments are checked against the function signiture before the call.
+    pub fn call(mut self, function_name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let (Some(signiture), Some(slot)) = (
+            self.module.get_function_signiture(function_name),
+            self.module.get_slot(function_name),
+        ) else {
+            return Err(RuntimeError::Other(format!(
+                "Function '{}' not found",
+                function_name
+            )));
+        };
+
+        if signiture.params.len() != args.len() {
+            return Err(RuntimeError::Other(format!(
+                "Function '{}' expects {} arguments, found {}",
+                function_name,
+                signiture.params.len(),
+                args.len()
+            )));
+        }
+
+        for (param, arg) in signiture.params.iter().zip(&args) {
+            if param.data_type != arg.get_type() {
+                return Err(RuntimeError::Other(format!(
+                    "Argument '{}' of function '{}' expects type {}, found {}",
+                    param.name,
+                    function_name,
+                    param.data_type,
+                    arg.get_type()
+                )));
+            }
+        }
+
+        let source = self.module.get_function_source_by_slot(slot).unwrap();
+        let mut function_runtime = self.function_runtime(source);
+        function_runtime.stack.extend(args);
+        function_runtime.run()
     }
 }
 
 struct InternalFunctionRuntime<'a> {
     dependencies: &'a HashMap<String, Module>,
-    functions: &'a [FunctionSource],
+    args: &'a [String],
+    globals: &'a mut Globals,
     stack: &'a mut Vec<Value>,
+    /// name of the module the function belongs to, its key in `globals`
+    module_name: &'a str,
+    module: &'a Module,
     index: usize,
     slots: Vec<Value>,
     source: &'a FunctionSource,
@@ -51,25 +153,82 @@ struct InternalFunctionRuntime<'a> {
 impl<'a> InternalFunctionRuntime<'a> {
     pub fn new(
         dependencies: &'a HashMap<String, Module>,
-        functions: &'a [FunctionSource],
+        args: &'a [String],
+        globals: &'a mut Globals,
         stack: &'a mut Vec<Value>,
+        module_name: &'a str,
+        module: &'a Module,
         source: &'a FunctionSource,
     ) -> Self {
         Self {
             dependencies,
-            functions,
+            args,
+            globals,
             stack,
+            module_name,
+            module,
             source,
             index: 0,
             slots: vec![Value::Void; source.slot_count],
         }
     }
 
+    /// Function of the same module called from this one.
+    fn nested(&mut self, source: &'a FunctionSource) -> InternalFunctionRuntime<'_> {
+        InternalFunctionRuntime::new(
+            self.dependencies,
+            self.args,
+            self.globals,
+            self.stack,
+            self.module_name,
+            self.module,
+            source,
+        )
+    }
+
+    /// Initializes globals of the module if it is used for the first time, then interprets the function.
+    fn run(&mut self) -> Result<Value, RuntimeError> {
+        self.initialize_globals()?;
+        self.interpret()
+    }
+
+    /// Globals get default values of their types and the module is marked as initialized
+    /// before its initializer runs, so the initializer can call functions using the globals.
+    fn initialize_globals(&mut self) -> Result<(), RuntimeError> {
+        if self.globals.contains_key(self.module_name) {
+            return Ok(());
+        }
+
+        let values = self
+            .module
+            .get_globals()
+            .iter()
+            .map(|global| Value::default_of(global.data_type))
+            .collect();
+        self.globals.insert(self.module_name.to_string(), values);
+
+        if let Some(source) = self.module.get_init_source() {
+            self.nested(source).interpret()?;
+        }
+
+        Ok(())
+    }
+
+    fn global_slot(&mut self, slot: u32) -> Result<&mut Value, RuntimeError> {
+        self.globals
+            .get_mut(self.module_name)
+            .and_then(|globals| globals.get_mut(slot as usize))
+            .ok_or(RuntimeError::Other(format!(
+                "Global slot {} not found in module '{}'",
+                slot, self.module_name
+            )))
+    }
+
     fn pop(&mut self) -> Result<Value, RuntimeError> {
         self.stack.pop().ok_or(RuntimeError::ValueStackUnderflow)
     }
 
-    pub fn interpret(mut self) -> Result<Value, RuntimeError> {
+    pub fn interpret(&mut self) -> Result<Value, RuntimeError> {
         while self.index < self.source.body.len() {
             let instruction = &self.source.body[self.index];
             match *instruction {
@@ -89,15 +248,19 @@ impl<'a> InternalFunctionRuntime<'a> {
                     self.stack.push(self.slots[slot as usize].clone());
                 }
 
+                Instruction::StoreGlobal { slot } => {
+                    let value = self.pop()?;
+                    *self.global_slot(slot)? = value;
+                }
+
+                Instruction::LoadGlobal { slot } => {
+                    let value = self.global_slot(slot)?.clone();
+                    self.stack.push(value);
+                }
+
                 Instruction::Call { call_slot } => {
-                    let source = &self.functions[call_slot as usize];
-                    let result = InternalFunctionRuntime::new(
-                        self.dependencies,
-                        self.functions,
-                        &mut self.stack,
-                        source,
-                    )
-                    .interpret()?;
+                    let source = &self.module.get_sources()[call_slot as usize];
+                    let result = self.nested(source).interpret()?;
                     self.stack.push(result);
                 }
 
@@ -120,13 +283,17 @@ impl<'a> InternalFunctionRuntime<'a> {
                         )),
                     )?;
 
+                    // foreign function calls its own module functions and uses its globals
                     let result = InternalFunctionRuntime::new(
                         self.dependencies,
-                        self.functions,
-                        &mut self.stack,
+                        self.args,
+                        self.globals,
+                        self.stack,
+                        module_name,
+                        module,
                         source,
                     )
-                    .interpret()?;
+                    .run()?;
                     self.stack.push(result);
                 }
 
@@ -135,7 +302,7 @@ impl<'a> InternalFunctionRuntime<'a> {
                     arg_count,
                 } => {
                     let args = self.stack.split_off(self.stack.len() - arg_count as usize);
-                    let result = function.execute(args)?;
+                    let result = function.execute(args, self.args)?;
                     self.stack.push(result);
                 }
 
@@ -183,6 +350,16 @@ impl<'a> InternalFunctionRuntime<'a> {
 
                 Instruction::Not => self.apply_un_op(Value::not)?,
                 Instruction::Negate => self.apply_un_op(Value::negate)?,
+                Instruction::BitAnd => self.apply_bin_op(Value::bit_and)?,
+                Instruction::BitOr => self.apply_bin_op(Value::bit_or)?,
+                Instruction::BitXor => self.apply_bin_op(Value::bit_xor)?,
+                Instruction::BitNot => self.apply_un_op(Value::bit_not)?,
+                Instruction::ShiftLeft => self.apply_bin_op(Value::shift_left)?,
+                Instruction::ShiftRight => self.apply_bin_op(Value::shift_right)?,
+                Instruction::Cast(data_type) => {
+                    let value = self.pop()?;
+                    self.stack.push(value.cast(data_type)?);
+                }
             }
 
             self.index += 1;
@@ -220,3 +397,328 @@ impl<'a> InternalFunctionRuntime<'a> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::errors::CompileErrorKind;
+
+    #[test]
+    fn program_arguments() {
+        let source = "
+Int main() {
+    if arg_count() != 2 {
+        return 1
+    }
+    if arg(1) != 'b' {
+        return 2
+    }
+    return 42
+}
+";
+        let module = Module::from_string(source, 0).unwrap();
+        let dependencies = HashMap::new();
+        let args = vec!["a".to_string(), "b".to_string()];
+
+        let result = Runtime::new(&module, &dependencies)
+            .with_args(&args)
+            .execute();
+        assert_eq!(result.unwrap(), Value::Int(42));
+
+        let result = Runtime::new(&module, &dependencies).execute();
+        assert_eq!(result.unwrap(), Value::Int(1));
+    }
+
+    #[test]
+    fn casts() {
+        let run = |expr: &str| {
+            let source = format!("String value() {{\n return ({}) as String\n}}", expr);
+            let module = Module::from_string(&source, 0).unwrap();
+            Runtime::new(&module, &HashMap::new()).call("value", vec![])
+        };
+
+        assert_eq!(
+            run("7 as Float / 2.0").unwrap(),
+            Value::String("3.5".to_string())
+        );
+        assert_eq!(run("-2.9 as Int").unwrap(), Value::String("-2".to_string()));
+        assert_eq!(
+            run("2.5 as Int as Float").unwrap(),
+            Value::String("2".to_string())
+        );
+        assert_eq!(run("true").unwrap(), Value::String("true".to_string()));
+        assert!(run("3000000000.0 as Int").is_err());
+        assert!(matches!(
+            run("(-1.0).sqrt() as Int"),
+            Err(RuntimeError::Other(message)) if message == "Cannot cast NaN to int"
+        ));
+        assert!(matches!(
+            run("(10.0).pow(400.0) as Int64"),
+            Err(RuntimeError::IntegerOverflow { operation: "cast" })
+        ));
+        assert!(matches!(
+            run("-(10.0).pow(400.0) as Byte"),
+            Err(RuntimeError::IntegerOverflow { operation: "cast" })
+        ));
+        assert_eq!(
+            run("300 as Int64 * 10000000000i64").unwrap(),
+            Value::String("3000000000000".to_string())
+        );
+        assert!(run("300 as Byte").is_err());
+    }
+
+    #[test]
+    fn string_repetition_limit() {
+        let run = |expr: &str| {
+            let source = format!("String value() {{\n return {}\n}}", expr);
+            let module = Module::from_string(&source, 0).unwrap();
+            Runtime::new(&module, &HashMap::new()).call("value", vec![])
+        };
+
+        assert_eq!(
+            run("'ab' * 3").unwrap(),
+            Value::String("ababab".to_string())
+        );
+        assert_eq!(run("'ab' * 0").unwrap(), Value::String(String::new()));
+        assert!(matches!(
+            run("'ab' * -1"),
+            Err(RuntimeError::Other(message)) if message == "Cannot repeat a string -1 times"
+        ));
+        assert!(matches!(
+            run("'ab' * 2147483647"),
+            Err(RuntimeError::Other(message)) if message.ends_with("longer than 16777216 bytes")
+        ));
+        assert!(run("'ab'.repeat(2147483647)").is_err());
+        assert_eq!(
+            run("'a' * 16777216").unwrap(),
+            Value::String("a".repeat(crate::value::MAX_REPEATED_STRING_LENGTH))
+        );
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        let run = |data_type: &str, expr: &str| {
+            let source = format!("{} value() {{\n return {}\n}}", data_type, expr);
+            let module = Module::from_string(&source, 0).unwrap();
+            Runtime::new(&module, &HashMap::new()).call("value", vec![])
+        };
+
+        assert_eq!(run("Int", "12 & 10 | 1 ^ 3").unwrap(), Value::Int(10));
+        assert_eq!(run("Bool", "(6 & 4) != 0").unwrap(), Value::Bool(true));
+        assert_eq!(run("Int", "~0").unwrap(), Value::Int(-1));
+        assert_eq!(run("Byte", "~15u8").unwrap(), Value::Byte(240));
+        assert_eq!(run("Int", "1 << 2 + 1").unwrap(), Value::Int(8));
+        assert_eq!(run("Int64", "1i64 << 40").unwrap(), Value::Int64(1 << 40));
+        assert_eq!(run("Int", "-16 >> 2").unwrap(), Value::Int(-4));
+        assert_eq!(run("Byte", "255u8 << 4").unwrap(), Value::Byte(240));
+
+        assert_eq!(run("Int", "1 << 32").unwrap(), Value::Int(0));
+        assert_eq!(run("Int", "-5 >> 40").unwrap(), Value::Int(-1));
+        assert_eq!(run("Byte", "128u8 >> 8").unwrap(), Value::Byte(0));
+        assert!(run("Int", "1 << -1").is_err());
+    }
+
+    #[test]
+    fn if_and_block_expressions() {
+        let source = "
+Int sign(Int a) {
+    return if a < 0 { -1 } else if a == 0 { 0 } else { 1 }
+}
+
+Int sum_to(Int n) {
+    let total = {
+        let i = 0
+        let sum = 0
+        while i < n {
+            i = i + 1
+            sum = sum + i
+        }
+        sum
+    }
+    return total * { 2 } / 2
+}
+";
+        let module = Module::from_string(source, 0).unwrap();
+        let call = |name: &str, arg: i32| {
+            Runtime::new(&module, &HashMap::new())
+                .call(name, vec![Value::Int(arg)])
+                .unwrap()
+        };
+
+        assert_eq!(call("sign", -7), Value::Int(-1));
+        assert_eq!(call("sign", 0), Value::Int(0));
+        assert_eq!(call("sign", 3), Value::Int(1));
+        assert_eq!(call("sum_to", 4), Value::Int(10));
+    }
+
+    #[test]
+    fn checked_integer_arithmetic() {
+        let run = |data_type: &str, expr: &str| {
+            let source = format!("{} value() {{\n return {}\n}}", data_type, expr);
+            let module = Module::from_string(&source, 0).unwrap();
+            Runtime::new(&module, &HashMap::new()).call("value", vec![])
+        };
+        let overflow = |result: Result<Value, RuntimeError>| match result {
+            Err(RuntimeError::IntegerOverflow { operation }) => operation,
+            other => panic!("expected overflow, got {:?}", other),
+        };
+
+        assert_eq!(overflow(run("Int", "2147483647 + 1")), "addition");
+        assert_eq!(overflow(run("Int", "-2147483647 - 2")), "subtraction");
+        assert_eq!(overflow(run("Int", "65536 * 65536")), "multiplication");
+        assert_eq!(overflow(run("Int", "-(-2147483647 - 1)")), "negation");
+        assert_eq!(overflow(run("Int", "(-2147483647 - 1) / -1")), "division");
+        assert_eq!(overflow(run("Byte", "200u8 + 100u8")), "addition");
+        assert_eq!(overflow(run("Byte", "0u8 - 1u8")), "subtraction");
+        assert_eq!(
+            overflow(run("Int64", "9223372036854775807i64 + 1i64")),
+            "addition"
+        );
+
+        assert_eq!(
+            run("Int64", "2147483647 as Int64 + 1i64").unwrap(),
+            Value::Int64(2_147_483_648)
+        );
+        assert_eq!(
+            run("Byte", "200u8.wrapping_add(100u8)").unwrap(),
+            Value::Byte(44)
+        );
+        assert!(matches!(
+            run("Byte", "1u8 % 0u8"),
+            Err(RuntimeError::DivisionByZero)
+        ));
+    }
+
+    #[test]
+    fn modules_with_same_name_in_different_folders() {
+        let utils = Module::from_string("pub Int id() {\n return 1\n}", 0).unwrap();
+        let text = Module::from_string("pub Int id() {\n return 2\n}", 0).unwrap();
+        let dependencies = HashMap::from([
+            ("utils.strings".to_string(), utils),
+            ("text.strings".to_string(), text),
+        ]);
+
+        let compile = |source| {
+            let ast = crate::ast::Ast::from_string(source, 0).unwrap();
+            Module::from_ast_and_dependencies(ast, &dependencies)
+        };
+
+        let module =
+            compile("import utils.strings\nInt main() {\n return strings:id()\n}").unwrap();
+        let result = Runtime::new(&module, &dependencies).execute();
+        assert_eq!(result.unwrap(), Value::Int(1));
+
+        let source = "import utils.strings
+import text.strings
+Int main() {
+    return utils.strings:id() * 10 + text.strings:id()
+}";
+        let module = compile(source).unwrap();
+        let result = Runtime::new(&module, &dependencies).execute();
+        assert_eq!(result.unwrap(), Value::Int(12));
+
+        let source = source.replace("utils.strings:id()", "strings:id()");
+        let error = compile(&source).unwrap_err();
+        assert!(matches!(error.kind, CompileErrorKind::AmbiguousModule(_)));
+    }
+
+    #[test]
+    fn globals_are_initialized_per_module() {
+        let source = "let calls = 0\npub const Int STEP = 5\npub Int bump() {\n calls = calls + STEP\n return calls\n}";
+        let counter = Module::from_string(source, 0).unwrap();
+        let dependencies = HashMap::from([("counter".to_string(), counter)]);
+
+        let source = "import counter
+Int total = double(counter:STEP)
+Int double(Int x) {
+    return x * 2
+}
+Int main() {
+    counter:bump()
+    total = total + counter:bump()
+    return total
+}";
+        let ast = crate::ast::Ast::from_string(source, 0).unwrap();
+        let module = Module::from_ast_and_dependencies(ast, &dependencies).unwrap();
+        assert!(crate::verifier::verify(&module, &dependencies).is_ok());
+
+        let result = Runtime::new(&module, &dependencies).execute();
+        assert_eq!(result.unwrap(), Value::Int(20));
+
+        let result = Runtime::new(&module, &dependencies).execute();
+        assert_eq!(result.unwrap(), Value::Int(20), "globals are reset per run");
+    }
+
+    #[test]
+    fn reexported_functions() {
+        let vector = Module::from_string("pub Int len() {\n return 5\n}", 0).unwrap();
+        let math = Module::from_string(
+            "pub Int add(Int a, Int b) {\n return a + b\n}\npub Void check(Int a) {\n assert(a > 0)\n}",
+            0,
+        )
+        .unwrap();
+        let mut dependencies = HashMap::from([
+            ("geometry.vector".to_string(), vector),
+            ("math".to_string(), math),
+        ]);
+
+        let facade = "pub import geometry.vector\npub import math:{add, check}\n";
+        let ast = crate::ast::Ast::from_string(facade, 0).unwrap();
+        let facade = Module::from_ast_and_dependencies(ast, &dependencies).unwrap();
+        assert!(crate::verifier::verify(&facade, &dependencies).is_ok());
+        dependencies.insert("lib".to_string(), facade);
+
+        let source = "import lib
+Int main() {
+    lib:check(1)
+    return lib:add(lib:len(), 1)
+}";
+        let ast = crate::ast::Ast::from_string(source, 0).unwrap();
+        let module = Module::from_ast_and_dependencies(ast, &dependencies).unwrap();
+        let result = Runtime::new(&module, &dependencies).execute();
+        assert_eq!(result.unwrap(), Value::Int(6));
+    }
+
+    #[test]
+    fn aliased_and_selectively_imported_functions() {
+        let vector = Module::from_string("pub Int len() {\n return 5\n}", 0).unwrap();
+        let math = Module::from_string(
+            "pub Int add(Int a, Int b) {\n return a + b\n}\npub Int mul(Int a, Int b) {\n return a * b\n}",
+            0,
+        )
+        .unwrap();
+        let dependencies = HashMap::from([
+            ("geometry.vector".to_string(), vector),
+            ("math".to_string(), math),
+        ]);
+
+        let source = "import geometry.vector as vec
+import math:{add, mul}
+Int main() {
+    return add(mul(vec:len(), 2), math:add(1, 1))
+}";
+        let ast = crate::ast::Ast::from_string(source, 0).unwrap();
+        let module = Module::from_ast_and_dependencies(ast, &dependencies).unwrap();
+        let result = Runtime::new(&module, &dependencies).execute();
+        assert_eq!(result.unwrap(), Value::Int(12));
+    }
+
+    #[test]
+    fn assert_eq_reports_values() {
+        let source = "
+Void test_strings() {
+    assert_eq(format('a', 1), 'a2')
+}
+";
+        let module = Module::from_string(source, 0).unwrap();
+        let dependencies = HashMap::new();
+
+        let error = Runtime::new(&module, &dependencies)
+            .call("test_strings", vec![])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Assertion failed: expected 'a2', actual 'a1'"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    ast::{Ast, Import, Statement},
+    compiler::{
+        compile_session_statements, data_type_scope::DataTypeScope, tokenize, Compiler, Parser,
+        TypeChecker,
+    },
+    data_type::DataType,
+    diagnostics::FileId,
+    errors::{CompileError, RuntimeError},
+    module::Module,
+    runtime::Runtime,
+    value::Value,
+};
+
+/// Parsed input of the session.
+#[derive(Debug, Clone)]
+pub struct SessionInput {
+    items: Ast,
+    statements: Vec<Statement>,
+}
+
+impl SessionInput {
+    pub fn parse(input: &str, file_id: FileId) -> Result<Self, CompileError> {
+        let tokens = tokenize(input, file_id)?;
+        let (items, statements) = Parser::new(&tokens, file_id).parse_session_input()?;
+
+        Ok(Self { items, statements })
+    }
+
+    pub fn get_imports(&self) -> &Vec<Import> {
+        self.items.get_imports()
+    }
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    Compile(CompileError),
+    Runtime(RuntimeError),
+}
+
+impl From<CompileError> for SessionError {
+    fn from(e: CompileError) -> Self {
+        Self::Compile(e)
+    }
+}
+
+impl From<RuntimeError> for SessionError {
+    fn from(e: RuntimeError) -> Self {
+        Self::Runtime(e)
+    }
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Compile(e) => write!(f, "{}", e),
+            Self::Runtime(e) => write!(f, "Runtime error: {}", e),
+        }
+    }
+}
+
+/// Interactive session that keeps declared variables and functions between inputs.
+/// Every input is type checked against the variables declared so far,
+/// so the errors are reported before anything is executed.
+pub struct Session {
+    ast: Ast,
+    module: Module,
+    dependencies: HashMap<String, Module>,
+    scope: DataTypeScope,
+    locals: HashMap<String, usize>,
+    slots: Vec<Value>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Session {
+    pub fn new() -> Self {
+        let mut scope = DataTypeScope::new();
+        scope.enter();
+
+        Self {
+            ast: Ast::empty(),
+            module: Module::empty(),
+            dependencies: HashMap::new(),
+            scope,
+            locals: HashMap::new(),
+            slots: vec![],
+        }
+    }
+
+    pub fn has_dependency(&self, name: &str) -> bool {
+        self.dependencies.contains_key(name)
+    }
+
+    pub fn add_dependency(&mut self, name: String, module: Module) {
+        self.dependencies.insert(name, module);
+    }
+
+    /// Names and types of declared variables, sorted by name.
+    pub fn variables(&self) -> Vec<(String, DataType)> {
+        let mut variables: Vec<_> = self
+            .locals
+            .keys()
+            .filter_map(|name| Some((name.clone(), *self.scope.get(name)?)))
+            .collect();
+
+        variables.sort_by(|a, b| a.0.cmp(&b.0));
+        variables
+    }
+
+    /// Adds functions of the input to the session and executes its statements.
+    /// Returns value and type of the last statement if it is an expression.
+    /// Imports of the input must be already added as dependencies.
+    pub fn eval(&mut self, input: SessionInput) -> Result<Option<(Value, DataType)>, SessionError> {
+        let ast = self.ast_with_items(input.items)?;
+
+        let mut checker = TypeChecker::with_scope(&ast, &self.dependencies, self.scope.clone());
+        let result_type = checker.check_session_statements(&input.statements)?;
+        let scope = checker.into_scope();
+
+        // compile the functions only when they changed
+        if ast.function_count() != self.ast.function_count() {
+            self.module = Compiler::new(ast.clone(), &self.dependencies).compile()?;
+        }
+
+        let mut locals = self.locals.clone();
+        let source =
+            compile_session_statements(&ast, &self.dependencies, &input.statements, &mut locals)?;
+
+        // functions are already compiled, keep them even if the execution fails
+        self.ast = ast;
+
+        let value = Runtime::new(&self.module, &self.dependencies)
+            .execute_with_slots(&source, &mut self.slots)?;
+
+        self.scope = scope;
+        self.locals = locals;
+
+        Ok(result_type.map(|data_type| (value, data_type)))
+    }
+
+    /// Returns type of the expression without executing it.
+    pub fn type_of(&self, input: &str, file_id: FileId) -> Result<DataType, CompileError> {
+        let input = SessionInput::parse(input, file_id)?;
+        let ast = self.ast_with_items(input.items)?;
+
+        let mut checker = TypeChecker::with_scope(&ast, &self.dependencies, self.scope.clone());
+        match checker.check_session_statements(&input.statements)? {
+            Some(data_type) if input.statements.len() == 1 => Ok(data_type),
+            _ => Err(CompileError::new(
+                crate::errors::CompileErrorKind::Other,
+                "expected single expression",
+            )),
+        }
+    }
+
+    /// Returns session functions, imports and constants extended by items of the input.
+    /// All items are type checked.
+    fn ast_with_items(&self, items: Ast) -> Result<Ast, CompileError> {
+        let mut ast = self.ast.clone();
+        let (function_map, signitures, bodies, imports, constants, _) = items.deconstruct();
+
+        let mut functions: Vec<_> = function_map.into_iter().collect();
+        functions.sort_by_key(|(_, slot)| *slot);
+
+        for (name, slot) in functions {
+            let signiture = signitures[slot as usize].clone();
+            if ast.get_function_slot(&name).is_some() {
+                return Err(CompileError::function_already_defined_at(
+                    name,
+                    signiture.span,
+                ));
+            }
+
+            ast.add_function(name, signiture, bodies[slot as usize].clone());
+        }
+
+        let previous_imports = ast.get_imports().len();
+        for import in imports {
+            let repeated = ast
+                .get_imports()
+                .iter()
+                .any(|existing| existing.to_string() == import.to_string());
+            if !repeated {
+                ast.add_import(import);
+            }
+        }
+
+        let previous_constants = ast.get_constants().len();
+        for constant in constants {
+            if ast.get_constant(&constant.name).is_some() || self.scope.contains(&constant.name) {
+                return Err(CompileError::variable_redeclaration_at(
+                    constant.name,
+                    constant.span,
+                ));
+            }
+
+            ast.add_constant(constant);
+        }
+
+        if ast.function_count() != self.ast.function_count()
+            || ast.get_imports().len() != previous_imports
+            || ast.get_constants().len() != previous_constants
+        {
+            TypeChecker::new(&ast, &self.dependencies).check()?;
+        }
+
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn eval(session: &mut Session, input: &str) -> Option<(Value, DataType)> {
+        let input = SessionInput::parse(input, 0).unwrap();
+        session.eval(input).unwrap()
+    }
+
+    #[test]
+    fn variables_persist_between_inputs() {
+        let mut session = Session::new();
+
+        assert_eq!(eval(&mut session, "let x = 40"), None);
+        assert_eq!(eval(&mut session, "Int y = 2"), None);
+        assert_eq!(
+            eval(&mut session, "x + y"),
+            Some((Value::Int(42), DataType::Int))
+        );
+
+        eval(&mut session, "x = x * 2");
+        assert_eq!(
+            eval(&mut session, "x"),
+            Some((Value::Int(80), DataType::Int))
+        );
+    }
+
+    #[test]
+    fn functions_persist_between_inputs() {
+        let mut session = Session::new();
+
+        eval(&mut session, "Int sub(Int a, Int b) {\n return a - b\n}");
+        eval(&mut session, "let a = sub(10, 4)");
+        eval(&mut session, "String name() {\n return 'mluva'\n}");
+
+        assert_eq!(
+            eval(&mut session, "format(name(), a)"),
+            Some((Value::String("mluva6".to_string()), DataType::String))
+        );
+    }
+
+    #[test]
+    fn constants_persist_between_inputs() {
+        let mut session = Session::new();
+
+        eval(&mut session, "const Int MAX = 10");
+        eval(&mut session, "Int twice() {\n return MAX * 2\n}");
+        assert_eq!(
+            eval(&mut session, "twice() + MAX"),
+            Some((Value::Int(30), DataType::Int))
+        );
+
+        let input = SessionInput::parse("const Int MAX = 20", 0).unwrap();
+        assert!(matches!(session.eval(input), Err(SessionError::Compile(_))));
+        let input = SessionInput::parse("MAX = 20", 0).unwrap();
+        assert!(matches!(session.eval(input), Err(SessionError::Compile(_))));
+    }
+
+    #[test]
+    fn errors_keep_session_usable() {
+        let mut session = Session::new();
+        eval(&mut session, "let x = 1");
+
+        let input = SessionInput::parse("x = 'text'", 0).unwrap();
+        assert!(matches!(session.eval(input), Err(SessionError::Compile(_))));
+
+        let input = SessionInput::parse("let y = x / 0", 0).unwrap();
+        assert!(matches!(session.eval(input), Err(SessionError::Runtime(_))));
+        assert!(session.type_of("y", 0).is_err());
+
+        let input = SessionInput::parse("Int f() {\n return true\n}", 0).unwrap();
+        assert!(matches!(session.eval(input), Err(SessionError::Compile(_))));
+
+        assert_eq!(
+            eval(&mut session, "x + 1"),
+            Some((Value::Int(2), DataType::Int))
+        );
+    }
+
+    #[test]
+    fn type_of_expression() {
+        let mut session = Session::new();
+        eval(&mut session, "let x = 1.5");
+
+        assert_eq!(session.type_of("x * 2.0", 0).unwrap(), DataType::Float);
+        assert_eq!(session.type_of("x > 1.0", 0).unwrap(), DataType::Bool);
+        assert!(session.type_of("x + 1", 0).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use crate::module::Module;
+
+/// Modules shipped with the interpreter as `(name, source)`. They are compiled like any other
+/// module, their functions call native methods of the builtin types.
+const SOURCES: &[(&str, &str)] = &[("math", include_str!("stdlib/math.mv"))];
+
+/// Names of the standard modules, e.g. `math`.
+pub fn standard_module_names() -> impl Iterator<Item = &'static str> {
+    SOURCES.iter().map(|(name, _)| *name)
+}
+
+/// Compiles the standard module `name`, `None` if there is no such module.
+/// Frontends resolve an import to a standard module only when the project
+/// has no module with the same name.
+pub fn standard_module(name: &str) -> Option<Module> {
+    let (_, source) = SOURCES
+        .iter()
+        .find(|(module_name, _)| *module_name == name)?;
+    let module = Module::from_string(source, 0)
+        .unwrap_or_else(|e| panic!("standard module '{}' does not compile: {}", name, e));
+
+    Some(module)
+}
+
+/// All standard modules keyed by their names.
+pub fn standard_modules() -> HashMap<String, Module> {
+    standard_module_names()
+        .map(|name| (name.to_string(), standard_module(name).unwrap()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ast::Ast, runtime::Runtime, value::Value};
+
+    #[test]
+    fn math_module() {
+        let dependencies = standard_modules();
+        let source = "import math\n\
+            Float value() {\n\
+                return math:sqrt(16.0) + math:pow(2.0, 3.0) + math:cos(0.0) + math:PI\n\
+            }";
+        let ast = Ast::from_string(source, 1).unwrap();
+        let module = Module::from_ast_and_dependencies(ast, &dependencies).unwrap();
+
+        let result = Runtime::new(&module, &dependencies)
+            .call("value", vec![])
+            .unwrap();
+        assert_eq!(result, Value::Float(13.0 + std::f64::consts::PI));
+    }
+
+    #[test]
+    fn math_module_calls_are_type_checked() {
+        let dependencies = standard_modules();
+        let source = "import math\nVoid main() {\n let x = math:sin(1)\n}";
+        let ast = Ast::from_string(source, 1).unwrap();
+
+        let error = Module::from_ast_and_dependencies(ast, &dependencies).unwrap_err();
+        assert_eq!(error.message, "wrong type: expected float, found int");
+        assert!(standard_module("strings").is_none());
+    }
+}
@@ -1,12 +1,18 @@
-use std::fmt::Display;
+use std::{cmp::Ordering, fmt::Display};
 
 use super::data_type::DataType;
-use crate::errors::RuntimeError;
+use crate::{errors::RuntimeError, method::Method};
+
+/// Longest string in bytes `String * Int` can produce, longer results are a runtime error
+/// instead of an allocation that can abort the process.
+pub const MAX_REPEATED_STRING_LENGTH: usize = 16 * 1024 * 1024;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Void,
     Int(i32),
+    Int64(i64),
+    Byte(u8),
     Float(f64),
     Bool(bool),
     String(String),
@@ -17,12 +23,27 @@ impl Value {
         match self {
             Self::Void => DataType::Void,
             Self::Int(_) => DataType::Int,
+            Self::Int64(_) => DataType::Int64,
+            Self::Byte(_) => DataType::Byte,
             Self::Float(_) => DataType::Float,
             Self::Bool(_) => DataType::Bool,
             Self::String(_) => DataType::String,
         }
     }
 
+    /// Value of a global before its initializer runs.
+    pub fn default_of(data_type: DataType) -> Self {
+        match data_type {
+            DataType::Void => Self::Void,
+            DataType::Int => Self::Int(0),
+            DataType::Int64 => Self::Int64(0),
+            DataType::Byte => Self::Byte(0),
+            DataType::Float => Self::Float(0.0),
+            DataType::Bool => Self::Bool(false),
+            DataType::String => Self::String(String::new()),
+        }
+    }
+
     pub fn is_false(&self) -> Result<bool, RuntimeError> {
         match self {
             Self::Bool(b) => Ok(!b),
@@ -30,16 +51,71 @@ impl Value {
         }
     }
 
-    pub fn method_call(&self, method_name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
-        match (self, method_name, args.as_slice()) {
-            (Value::String(s), "length", &[]) => Ok(Value::Int(s.len() as i32)),
+    /// Converts the value to `data_type`, `DataType::can_cast_to` tells which casts are valid.
+    /// Floats are truncated towards zero when cast to an integer type. `NaN` and values out of
+    /// the range of the integer type are errors.
+    pub fn cast(&self, data_type: DataType) -> Result<Value, RuntimeError> {
+        if self.get_type() == data_type {
+            return Ok(self.clone());
+        }
+
+        match (self, data_type) {
+            (Self::Float(f), _) if data_type.is_integer() => {
+                if f.is_nan() {
+                    return Err(RuntimeError::Other(format!(
+                        "Cannot cast NaN to {}",
+                        data_type
+                    )));
+                }
+
+                // `as` saturates, floats out of the range of i128 are out of every integer range
+                Self::integer_of(data_type, f.trunc() as i128)
+            }
+            (_, DataType::Float) => match self.as_integer() {
+                Some(i) => Ok(Self::Float(i as f64)),
+                None => Err(RuntimeError::TypeError),
+            },
+            (_, DataType::String) if self.get_type().can_cast_to(DataType::String) => {
+                Ok(Self::String(self.to_string()))
+            }
+            _ => match self.as_integer() {
+                Some(i) if data_type.is_integer() => Self::integer_of(data_type, i),
+                _ => Err(RuntimeError::TypeError),
+            },
+        }
+    }
+
+    /// Value of an integer type, wide enough for all of them.
+    fn as_integer(&self) -> Option<i128> {
+        match self {
+            Self::Int(i) => Some(*i as i128),
+            Self::Int64(i) => Some(*i as i128),
+            Self::Byte(i) => Some(*i as i128),
+            _ => None,
+        }
+    }
+
+    /// Integer of `data_type` with the value, `IntegerOverflow` when it is out of the range.
+    fn integer_of(data_type: DataType, value: i128) -> Result<Value, RuntimeError> {
+        let overflow = |_| RuntimeError::IntegerOverflow { operation: "cast" };
+        match data_type {
+            DataType::Int => i32::try_from(value).map(Self::Int).map_err(overflow),
+            DataType::Int64 => i64::try_from(value).map(Self::Int64).map_err(overflow),
+            DataType::Byte => u8::try_from(value).map(Self::Byte).map_err(overflow),
+            _ => Err(RuntimeError::TypeError),
+        }
+    }
 
-            _ => Err(RuntimeError::Other(format!(
+    pub fn method_call(&self, method_name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let Some(method) = Method::find(self.get_type(), method_name) else {
+            return Err(RuntimeError::Other(format!(
                 "Method '{}' not found for type '{}'",
                 method_name,
                 self.get_type()
-            ))),
-        }
+            )));
+        };
+
+        method.call(self, &args)
     }
 }
 
@@ -55,94 +131,138 @@ impl Value {
     }
 
     pub fn less(&self, rhs: Self) -> Result<Value, RuntimeError> {
-        match (self, rhs) {
-            (Self::Int(a), Self::Int(b)) => Ok(Value::Bool(*a < b)),
-            (Self::Float(a), Self::Float(b)) => Ok(Value::Bool(*a < b)),
-            _ => Err(RuntimeError::TypeError),
-        }
+        let ordering = self.compare(&rhs)?;
+        Ok(Value::Bool(ordering == Some(Ordering::Less)))
     }
 
     pub fn less_equal(&self, rhs: Self) -> Result<Value, RuntimeError> {
-        match (self, rhs) {
-            (Self::Int(a), Self::Int(b)) => Ok(Value::Bool(*a <= b)),
-            (Self::Float(a), Self::Float(b)) => Ok(Value::Bool(*a <= b)),
-            _ => Err(RuntimeError::TypeError),
-        }
+        let ordering = self.compare(&rhs)?;
+        Ok(Value::Bool(matches!(
+            ordering,
+            Some(Ordering::Less | Ordering::Equal)
+        )))
     }
 
     pub fn greater(&self, rhs: Self) -> Result<Value, RuntimeError> {
-        match (self, rhs) {
-            (Self::Int(a), Self::Int(b)) => Ok(Value::Bool(*a > b)),
-            (Self::Float(a), Self::Float(b)) => Ok(Value::Bool(*a > b)),
-            _ => Err(RuntimeError::TypeError),
-        }
+        let ordering = self.compare(&rhs)?;
+        Ok(Value::Bool(ordering == Some(Ordering::Greater)))
     }
 
     pub fn greater_equal(&self, rhs: Self) -> Result<Value, RuntimeError> {
+        let ordering = self.compare(&rhs)?;
+        Ok(Value::Bool(matches!(
+            ordering,
+            Some(Ordering::Greater | Ordering::Equal)
+        )))
+    }
+
+    /// Ordering of two numbers or strings of the same type, `None` when a float is `NaN`.
+    fn compare(&self, rhs: &Self) -> Result<Option<Ordering>, RuntimeError> {
         match (self, rhs) {
-            (Self::Int(a), Self::Int(b)) => Ok(Value::Bool(*a >= b)),
-            (Self::Float(a), Self::Float(b)) => Ok(Value::Bool(*a >= b)),
+            (Self::Int(a), Self::Int(b)) => Ok(a.partial_cmp(b)),
+            (Self::Int64(a), Self::Int64(b)) => Ok(a.partial_cmp(b)),
+            (Self::Byte(a), Self::Byte(b)) => Ok(a.partial_cmp(b)),
+            (Self::Float(a), Self::Float(b)) => Ok(a.partial_cmp(b)),
+            (Self::String(a), Self::String(b)) => Ok(a.partial_cmp(b)),
             _ => Err(RuntimeError::TypeError),
         }
     }
 
     pub fn add(&self, rhs: Self) -> Result<Value, RuntimeError> {
         match (self, rhs) {
-            (Self::Int(a), Self::Int(b)) => Ok(Value::Int(a + b)),
             (Self::Float(a), Self::Float(b)) => Ok(Value::Float(a + b)),
-            _ => return Err(RuntimeError::TypeError),
+            (Self::String(a), Self::String(b)) => Ok(Value::String(a.to_owned() + &b)),
+            (lhs, rhs) => integer_op(
+                lhs,
+                &rhs,
+                "addition",
+                i32::checked_add,
+                i64::checked_add,
+                u8::checked_add,
+            ),
         }
     }
 
     pub fn mul(&self, rhs: Self) -> Result<Value, RuntimeError> {
         match (self, rhs) {
-            (Self::Int(a), Self::Int(b)) => Ok(Value::Int(a * b)),
             (Self::Float(a), Self::Float(b)) => Ok(Value::Float(a * b)),
-            _ => return Err(RuntimeError::TypeError),
+            (Self::String(a), Self::Int(count)) => {
+                let count = usize::try_from(count).map_err(|_| {
+                    RuntimeError::Other(format!("Cannot repeat a string {} times", count))
+                })?;
+                match a.len().checked_mul(count) {
+                    Some(length) if length <= MAX_REPEATED_STRING_LENGTH => {
+                        Ok(Value::String(a.repeat(count)))
+                    }
+                    _ => Err(RuntimeError::Other(format!(
+                        "Cannot repeat a string {} times, the result is longer than {} bytes",
+                        count, MAX_REPEATED_STRING_LENGTH
+                    ))),
+                }
+            }
+            (lhs, rhs) => integer_op(
+                lhs,
+                &rhs,
+                "multiplication",
+                i32::checked_mul,
+                i64::checked_mul,
+                u8::checked_mul,
+            ),
         }
     }
 
     pub fn sub(&self, rhs: Self) -> Result<Value, RuntimeError> {
         match (self, rhs) {
-            (Self::Int(a), Self::Int(b)) => Ok(Value::Int(a - b)),
             (Self::Float(a), Self::Float(b)) => Ok(Value::Float(a - b)),
-            _ => return Err(RuntimeError::TypeError),
+            (lhs, rhs) => integer_op(
+                lhs,
+                &rhs,
+                "subtraction",
+                i32::checked_sub,
+                i64::checked_sub,
+                u8::checked_sub,
+            ),
         }
     }
 
     pub fn div(&self, rhs: Self) -> Result<Value, RuntimeError> {
         match (self, rhs) {
-            (Self::Int(a), Self::Int(b)) => {
-                if b == 0 {
-                    return Err(RuntimeError::DivisionByZero);
-                }
-                Ok(Value::Int(a / b))
-            }
             (Self::Float(a), Self::Float(b)) => {
                 if b == 0.0 {
                     return Err(RuntimeError::DivisionByZero);
                 }
                 Ok(Value::Float(a / b))
             }
-            _ => return Err(RuntimeError::TypeError),
+            (_, rhs) if rhs.as_integer() == Some(0) => Err(RuntimeError::DivisionByZero),
+            // `i32::MIN / -1` overflows
+            (lhs, rhs) => integer_op(
+                lhs,
+                &rhs,
+                "division",
+                i32::checked_div,
+                i64::checked_div,
+                u8::checked_div,
+            ),
         }
     }
 
     pub fn modulo(&self, rhs: Self) -> Result<Value, RuntimeError> {
         match (self, rhs) {
-            (Self::Int(a), Self::Int(b)) => {
-                if b == 0 {
-                    return Err(RuntimeError::DivisionByZero);
-                }
-                Ok(Value::Int(a % b))
-            }
             (Self::Float(a), Self::Float(b)) => {
                 if b == 0.0 {
                     return Err(RuntimeError::DivisionByZero);
                 }
                 Ok(Value::Float(a % b))
             }
-            _ => return Err(RuntimeError::Unknown),
+            (_, rhs) if rhs.as_integer() == Some(0) => Err(RuntimeError::DivisionByZero),
+            (lhs, rhs) => integer_op(
+                lhs,
+                &rhs,
+                "remainder",
+                i32::checked_rem,
+                i64::checked_rem,
+                u8::checked_rem,
+            ),
         }
     }
 
@@ -168,12 +288,114 @@ impl Value {
     }
 
     pub fn negate(&self) -> Result<Value, RuntimeError> {
+        let overflow = RuntimeError::IntegerOverflow {
+            operation: "negation",
+        };
         match self {
-            Self::Int(a) => Ok(Value::Int(-a)),
+            Self::Int(a) => a.checked_neg().map(Value::Int).ok_or(overflow),
+            Self::Int64(a) => a.checked_neg().map(Value::Int64).ok_or(overflow),
             Self::Float(a) => Ok(Value::Float(-a)),
             _ => return Err(RuntimeError::TypeError),
         }
     }
+
+    pub fn bit_and(&self, rhs: Self) -> Result<Value, RuntimeError> {
+        integer_op(
+            self,
+            &rhs,
+            "bitwise and",
+            |a, b| Some(a & b),
+            |a, b| Some(a & b),
+            |a, b| Some(a & b),
+        )
+    }
+
+    pub fn bit_or(&self, rhs: Self) -> Result<Value, RuntimeError> {
+        integer_op(
+            self,
+            &rhs,
+            "bitwise or",
+            |a, b| Some(a | b),
+            |a, b| Some(a | b),
+            |a, b| Some(a | b),
+        )
+    }
+
+    pub fn bit_xor(&self, rhs: Self) -> Result<Value, RuntimeError> {
+        integer_op(
+            self,
+            &rhs,
+            "bitwise xor",
+            |a, b| Some(a ^ b),
+            |a, b| Some(a ^ b),
+            |a, b| Some(a ^ b),
+        )
+    }
+
+    pub fn bit_not(&self) -> Result<Value, RuntimeError> {
+        match self {
+            Self::Int(a) => Ok(Value::Int(!a)),
+            Self::Int64(a) => Ok(Value::Int64(!a)),
+            Self::Byte(a) => Ok(Value::Byte(!a)),
+            _ => Err(RuntimeError::TypeError),
+        }
+    }
+
+    /// Shifting by the bit width of the type or more shifts out all bits and gives `0`.
+    pub fn shift_left(&self, rhs: Self) -> Result<Value, RuntimeError> {
+        let amount = shift_amount(&rhs)?;
+        match self {
+            Self::Int(a) => Ok(Value::Int(a.checked_shl(amount).unwrap_or(0))),
+            Self::Int64(a) => Ok(Value::Int64(a.checked_shl(amount).unwrap_or(0))),
+            Self::Byte(a) => Ok(Value::Byte(a.checked_shl(amount).unwrap_or(0))),
+            _ => Err(RuntimeError::TypeError),
+        }
+    }
+
+    /// Arithmetic shift, the sign bit of `Int` and `Int64` is copied. Shifting by the bit width
+    /// of the type or more gives `0`, or `-1` for negative numbers.
+    pub fn shift_right(&self, rhs: Self) -> Result<Value, RuntimeError> {
+        let amount = shift_amount(&rhs)?;
+        let sign = |negative: bool| if negative { -1 } else { 0 };
+        match self {
+            Self::Int(a) => Ok(Value::Int(a.checked_shr(amount).unwrap_or(sign(*a < 0)))),
+            Self::Int64(a) => Ok(Value::Int64(
+                a.checked_shr(amount).unwrap_or(sign(*a < 0) as i64),
+            )),
+            Self::Byte(a) => Ok(Value::Byte(a.checked_shr(amount).unwrap_or(0))),
+            _ => Err(RuntimeError::TypeError),
+        }
+    }
+}
+
+/// Amount of a shift, it is an `Int` for all integer types and can not be negative.
+fn shift_amount(amount: &Value) -> Result<u32, RuntimeError> {
+    let Value::Int(amount) = amount else {
+        return Err(RuntimeError::TypeError);
+    };
+
+    u32::try_from(*amount)
+        .map_err(|_| RuntimeError::Other(format!("Negative shift amount {}", amount)))
+}
+
+/// Applies the operation to two integers of the same type, `None` from the operation is
+/// reported as an overflow of `operation`, e.g. `addition`.
+pub(crate) fn integer_op(
+    lhs: &Value,
+    rhs: &Value,
+    operation: &'static str,
+    int: fn(i32, i32) -> Option<i32>,
+    int64: fn(i64, i64) -> Option<i64>,
+    byte: fn(u8, u8) -> Option<u8>,
+) -> Result<Value, RuntimeError> {
+    let result = match (lhs, rhs) {
+        (Value::Int(a), Value::Int(b)) => int(*a, *b).map(Value::Int),
+        (Value::Int64(a), Value::Int64(b)) => int64(*a, *b).map(Value::Int64),
+        (Value::Byte(a), Value::Byte(b)) => byte(*a, *b).map(Value::Byte),
+        _ => return Err(RuntimeError::TypeError),
+    };
+
+    result.ok_or(RuntimeError::IntegerOverflow { operation })
 }
 
 mod froms {
@@ -185,6 +407,18 @@ mod froms {
         }
     }
 
+    impl From<i64> for Value {
+        fn from(value: i64) -> Self {
+            Self::Int64(value)
+        }
+    }
+
+    impl From<u8> for Value {
+        fn from(value: u8) -> Self {
+            Self::Byte(value)
+        }
+    }
+
     impl From<f64> for Value {
         fn from(value: f64) -> Self {
             Self::Float(value)
@@ -209,6 +443,8 @@ impl Display for Value {
         match self {
             Self::Void => write!(f, "void"),
             Self::Int(num) => write!(f, "{}", num),
+            Self::Int64(num) => write!(f, "{}", num),
+            Self::Byte(num) => write!(f, "{}", num),
             Self::Float(num) => write!(f, "{}", num),
             Self::Bool(bool) => write!(f, "{}", bool),
             Self::String(string) => write!(f, "{}", string),
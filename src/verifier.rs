@@ -0,0 +1,742 @@
+use std::collections::HashMap;
+
+use crate::{
+    data_type::DataType,
+    diagnostics::Span,
+    function::{FunctionSigniture, FunctionSource},
+    global::Global,
+    instruction::Instruction,
+    module::Module,
+};
+
+/// Checks that the module can be executed without corrupting the runtime.
+///
+/// Every function is interpreted abstractly, with types instead of values:
+/// jump targets, slots and called functions must exist, the stack must have the same
+/// types whenever two paths meet, every instruction gets operands of the right types
+/// and every path ends with `Return` of the declared type. Globals are stored only
+/// with values of their types, the initializer of globals is verified as `Void` function.
+/// Foreign calls are checked against the signitures in `dependencies`.
+pub fn verify(module: &Module, dependencies: &HashMap<String, Module>) -> Result<(), String> {
+    let function_count = module.get_sources().len();
+    let mut names = vec![None; function_count];
+    for (name, slot) in module.get_function_map() {
+        match names.get_mut(*slot as usize) {
+            Some(used @ None) => *used = Some(name.as_str()),
+            Some(Some(_)) => {
+                return Err(format!("function '{}': slot {} is used twice", name, slot))
+            }
+            None => return Err(format!("function '{}': slot {} out of range", name, slot)),
+        }
+    }
+
+    let mut functions = Vec::with_capacity(function_count);
+    for (slot, name) in names.into_iter().enumerate() {
+        let name = name.ok_or_else(|| format!("slot {} has no function", slot))?;
+        let signiture = module
+            .get_function_signiture_by_slot(slot as u32)
+            .ok_or_else(|| format!("function '{}': slot {} has no signiture", name, slot))?;
+        functions.push((name, signiture));
+    }
+
+    if let Some(slot) = module.get_main_slot() {
+        if slot as usize >= function_count {
+            return Err(format!("main slot {} out of range", slot));
+        }
+    }
+
+    for (&(name, signiture), source) in functions.iter().zip(module.get_sources()) {
+        let verifier = FunctionVerifier {
+            functions: &functions,
+            globals: module.get_globals(),
+            dependencies,
+            signiture,
+            source,
+        };
+
+        verifier
+            .verify()
+            .map_err(|e| format!("function '{}', {}", name, e))?;
+    }
+
+    if let Some(source) = module.get_init_source() {
+        let verifier = FunctionVerifier {
+            functions: &functions,
+            globals: module.get_globals(),
+            dependencies,
+            signiture: &FunctionSigniture::new(DataType::Void, vec![]),
+            source,
+        };
+
+        verifier
+            .verify()
+            .map_err(|e| format!("initializer of globals, {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Types on the stack and in the slots before an instruction is executed.
+/// A slot is `None` when it may not be set or may hold different types on different paths.
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    stack: Vec<DataType>,
+    slots: Vec<Option<DataType>>,
+}
+
+impl State {
+    fn pop(&mut self) -> Result<DataType, String> {
+        self.stack
+            .pop()
+            .ok_or_else(|| "stack underflow".to_string())
+    }
+
+    /// Pops `count` values, the first pushed is the first in the result.
+    fn pop_many(&mut self, count: u32) -> Result<Vec<DataType>, String> {
+        let count = count as usize;
+        if self.stack.len() < count {
+            return Err("stack underflow".to_string());
+        }
+
+        Ok(self.stack.split_off(self.stack.len() - count))
+    }
+}
+
+struct FunctionVerifier<'a> {
+    functions: &'a [(&'a str, &'a FunctionSigniture)],
+    globals: &'a [Global],
+    dependencies: &'a HashMap<String, Module>,
+    signiture: &'a FunctionSigniture,
+    source: &'a FunctionSource,
+}
+
+impl FunctionVerifier<'_> {
+    fn verify(&self) -> Result<(), String> {
+        let body = &self.source.body;
+        for (index, instruction) in body.iter().enumerate() {
+            self.check_operands(instruction)
+                .map_err(|e| format!("instruction {}: {}", index, e))?;
+        }
+
+        if self.signiture.params.len() > self.source.slot_count {
+            return Err(format!(
+                "{} parameters do not fit into {} slots",
+                self.signiture.params.len(),
+                self.source.slot_count
+            ));
+        }
+
+        // a slot can be loaded only after a `Store`, so a function can not use more slots
+        // than it has instructions, corrupt slot counts are rejected before allocating the slots
+        if self.source.slot_count > body.len() {
+            return Err(format!(
+                "{} slots for {} instructions",
+                self.source.slot_count,
+                body.len()
+            ));
+        }
+
+        let initial = State {
+            stack: self.signiture.params.iter().map(|p| p.data_type).collect(),
+            slots: vec![None; self.source.slot_count],
+        };
+
+        let mut states: Vec<Option<State>> = vec![None; body.len()];
+        let mut worklist = vec![];
+        merge(&mut states, &mut worklist, 0, initial)?;
+
+        while let Some(index) = worklist.pop() {
+            let mut state = states[index].clone().unwrap();
+            let successors = self
+                .step(&body[index], index, &mut state)
+                .map_err(|e| format!("instruction {}: {}", index, e))?;
+
+            for successor in successors {
+                merge(&mut states, &mut worklist, successor, state.clone())
+                    .map_err(|e| format!("instruction {}: {}", index, e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks operands that do not depend on the control flow.
+    fn check_operands(&self, instruction: &Instruction) -> Result<(), String> {
+        match instruction {
+            Instruction::Store { slot } | Instruction::Load { slot }
+                if *slot as usize >= self.source.slot_count =>
+            {
+                Err(format!(
+                    "slot {} out of range, function has {} slots",
+                    slot, self.source.slot_count
+                ))
+            }
+            Instruction::StoreGlobal { slot } | Instruction::LoadGlobal { slot }
+                if *slot as usize >= self.globals.len() =>
+            {
+                Err(format!(
+                    "global slot {} out of range, module has {} globals",
+                    slot,
+                    self.globals.len()
+                ))
+            }
+            // the compiler may emit unreachable jumps right behind the last instruction
+            Instruction::Jump(target) | Instruction::JumpIfFalse(target)
+                if *target as usize > self.source.body.len() =>
+            {
+                Err(format!("jump target {} out of range", target))
+            }
+            Instruction::Call { call_slot } if *call_slot as usize >= self.functions.len() => {
+                Err(format!("called slot {} does not exist", call_slot))
+            }
+            Instruction::ForeignCall {
+                module_name,
+                call_slot,
+            } => self.foreign_signiture(module_name, *call_slot).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    fn foreign_signiture(
+        &self,
+        module_name: &str,
+        call_slot: u32,
+    ) -> Result<&FunctionSigniture, String> {
+        let module = self
+            .dependencies
+            .get(module_name)
+            .ok_or_else(|| format!("module '{}' is not loaded", module_name))?;
+
+        let signiture = module
+            .get_function_signiture_by_slot(call_slot)
+            .ok_or_else(|| {
+                format!(
+                    "called slot {} does not exist in module '{}'",
+                    call_slot, module_name
+                )
+            })?;
+
+        if !signiture.visibility.is_public() {
+            return Err(format!(
+                "called slot {} of module '{}' is a private function",
+                call_slot, module_name
+            ));
+        }
+
+        Ok(signiture)
+    }
+
+    /// Applies the instruction to the state and returns indices of the next instructions.
+    fn step(
+        &self,
+        instruction: &Instruction,
+        index: usize,
+        state: &mut State,
+    ) -> Result<Vec<usize>, String> {
+        match instruction {
+            Instruction::Store { slot } => {
+                let data_type = state.pop()?;
+                state.slots[*slot as usize] = Some(data_type);
+            }
+            Instruction::Load { slot } => {
+                let data_type = state.slots[*slot as usize]
+                    .ok_or_else(|| format!("slot {} may be loaded before it is stored", slot))?;
+                state.stack.push(data_type);
+            }
+            Instruction::StoreGlobal { slot } => {
+                expect(self.globals[*slot as usize].data_type, state.pop()?)?;
+            }
+            Instruction::LoadGlobal { slot } => {
+                state.stack.push(self.globals[*slot as usize].data_type);
+            }
+            Instruction::Push(value) => state.stack.push(value.get_type()),
+            Instruction::Pop => {
+                state.pop()?;
+            }
+            Instruction::Add
+            | Instruction::Sub
+            | Instruction::Mul
+            | Instruction::Div
+            | Instruction::Modulo => {
+                let right = state.pop()?;
+                let left = state.pop()?;
+                let data_type = arithmetic_result(instruction, left, right)?;
+                state.stack.push(data_type);
+            }
+            Instruction::Less
+            | Instruction::LessEqual
+            | Instruction::Greater
+            | Instruction::GreaterEqual => {
+                let right = state.pop()?;
+                let left = state.pop()?;
+                match (left, right) {
+                    (left, right) if left == right && left.is_numeric() => (),
+                    (DataType::String, DataType::String) => (),
+                    _ => {
+                        return Err(format!(
+                            "expected two numbers of the same type or two Strings, found {} and {}",
+                            left, right
+                        ))
+                    }
+                }
+                state.stack.push(DataType::Bool);
+            }
+            Instruction::Equal | Instruction::NotEqual => {
+                state.pop_many(2)?;
+                state.stack.push(DataType::Bool);
+            }
+            Instruction::And | Instruction::Or => {
+                expect(DataType::Bool, state.pop()?)?;
+                expect(DataType::Bool, state.pop()?)?;
+                state.stack.push(DataType::Bool);
+            }
+            Instruction::Not => {
+                expect(DataType::Bool, state.pop()?)?;
+                state.stack.push(DataType::Bool);
+            }
+            Instruction::Negate => {
+                let data_type = state.pop()?;
+                if !matches!(data_type, DataType::Int | DataType::Int64 | DataType::Float) {
+                    return Err(format!("expected Int, Int64 or Float, found {}", data_type));
+                }
+                state.stack.push(data_type);
+            }
+            Instruction::BitAnd | Instruction::BitOr | Instruction::BitXor => {
+                let right = state.pop()?;
+                let left = state.pop()?;
+                if left != right || !left.is_integer() {
+                    return Err(format!(
+                        "expected two integers of the same type, found {} and {}",
+                        left, right
+                    ));
+                }
+                state.stack.push(left);
+            }
+            Instruction::ShiftLeft | Instruction::ShiftRight => {
+                expect(DataType::Int, state.pop()?)?;
+                let data_type = state.pop()?;
+                if !data_type.is_integer() {
+                    return Err(format!("expected Int, Int64 or Byte, found {}", data_type));
+                }
+                state.stack.push(data_type);
+            }
+            Instruction::BitNot => {
+                let data_type = state.pop()?;
+                if !data_type.is_integer() {
+                    return Err(format!("expected Int, Int64 or Byte, found {}", data_type));
+                }
+                state.stack.push(data_type);
+            }
+            Instruction::Cast(data_type) => {
+                let operand = state.pop()?;
+                if !operand.can_cast_to(*data_type) {
+                    return Err(format!("cannot cast {} to {}", operand, data_type));
+                }
+                state.stack.push(*data_type);
+            }
+            Instruction::Jump(target) => return Ok(vec![*target as usize]),
+            Instruction::JumpIfFalse(target) => {
+                expect(DataType::Bool, state.pop()?)?;
+                return Ok(vec![index + 1, *target as usize]);
+            }
+            Instruction::Call { call_slot } => {
+                let (_, signiture) = self.functions[*call_slot as usize];
+                call(signiture, state)?;
+            }
+            Instruction::ForeignCall {
+                module_name,
+                call_slot,
+            } => {
+                let signiture = self.foreign_signiture(module_name, *call_slot)?;
+                call(signiture, state)?;
+            }
+            Instruction::Return => {
+                expect(self.signiture.return_type, state.pop()?)?;
+                return Ok(vec![]);
+            }
+            Instruction::BuiltinFunctionCall {
+                function,
+                arg_count,
+            } => {
+                let args = without_spans(state.pop_many(*arg_count)?);
+                let data_type = function
+                    .check_argument_types(&args, no_span())
+                    .map_err(|e| e.message)?;
+                state.stack.push(data_type);
+            }
+            Instruction::MethodCall {
+                method_name,
+                arg_count,
+            } => {
+                // the callee is pushed after the arguments
+                let callee = state.pop()?;
+                let args = without_spans(state.pop_many(*arg_count)?);
+                let data_type = callee
+                    .check_method_call(method_name, no_span(), &args)
+                    .map_err(|e| e.message)?;
+                state.stack.push(data_type);
+            }
+        }
+
+        Ok(vec![index + 1])
+    }
+}
+
+/// Stores the state before the instruction at `index`, or merges it with the stored one.
+/// The instruction is queued again when the merge changed its state.
+fn merge(
+    states: &mut [Option<State>],
+    worklist: &mut Vec<usize>,
+    index: usize,
+    state: State,
+) -> Result<(), String> {
+    let existing = match states.get_mut(index) {
+        Some(Some(existing)) => existing,
+        Some(empty) => {
+            *empty = Some(state);
+            worklist.push(index);
+            return Ok(());
+        }
+        None => return Err("execution reaches the end of the function without return".to_string()),
+    };
+
+    if existing.stack != state.stack {
+        return Err(format!(
+            "stack at instruction {} differs between paths, {:?} and {:?}",
+            index, existing.stack, state.stack
+        ));
+    }
+
+    let mut changed = false;
+    for (slot, incoming) in existing.slots.iter_mut().zip(state.slots) {
+        if slot.is_some() && *slot != incoming {
+            *slot = None;
+            changed = true;
+        }
+    }
+
+    if changed {
+        worklist.push(index);
+    }
+
+    Ok(())
+}
+
+fn call(signiture: &FunctionSigniture, state: &mut State) -> Result<(), String> {
+    let args = without_spans(state.pop_many(signiture.params.len() as u32)?);
+    signiture
+        .check_argument_types(&args, no_span())
+        .map_err(|e| e.message)?;
+    state.stack.push(signiture.return_type);
+    Ok(())
+}
+
+/// Pops two operands of the same numeric type and returns the type.
+/// Numbers of the same type, `add` also concatenates Strings and `mul` repeats a String.
+fn arithmetic_result(
+    instruction: &Instruction,
+    left: DataType,
+    right: DataType,
+) -> Result<DataType, String> {
+    match (instruction, left, right) {
+        (_, left, right) if left == right && left.is_numeric() => Ok(left),
+        (Instruction::Add, DataType::String, DataType::String) => Ok(DataType::String),
+        (Instruction::Mul, DataType::String, DataType::Int) => Ok(DataType::String),
+        _ => Err(format!(
+            "expected two numbers of the same type, found {} and {}",
+            left, right
+        )),
+    }
+}
+
+fn expect(expected: DataType, found: DataType) -> Result<(), String> {
+    if expected != found {
+        return Err(format!("expected {}, found {}", expected, found));
+    }
+
+    Ok(())
+}
+
+fn without_spans(types: Vec<DataType>) -> Vec<(DataType, Span)> {
+    types.into_iter().map(|t| (t, no_span())).collect()
+}
+
+fn no_span() -> Span {
+    Span::new(0, 0, 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        assembler::assemble,
+        bytecode::{write_fn_map_bytecode, BytecodeHeader, BytecodeSerializable},
+        function::{Parameter, Visibility},
+        value::Value,
+    };
+
+    fn verify_listing(source: &str) -> Result<(), String> {
+        let module = assemble(source, 0, &HashMap::new()).unwrap();
+        verify(&module, &HashMap::new())
+    }
+
+    fn single_function(slot_count: usize, body: Vec<Instruction>) -> Module {
+        let signiture = FunctionSigniture::new(DataType::Int, vec![]);
+        Module::new(
+            Some(0),
+            HashMap::from([("main".to_string(), 0)]),
+            vec![signiture],
+            vec![FunctionSource::new(slot_count, body)],
+        )
+    }
+
+    #[test]
+    fn compiled_examples_verify() {
+        for entry in std::fs::read_dir("examples").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|e| e != "mv") {
+                continue;
+            }
+
+            let source = std::fs::read_to_string(&path).unwrap();
+            let module = Module::from_string(&source, 0).unwrap();
+            let result = verify(&module, &HashMap::new());
+            assert!(result.is_ok(), "{}: {:?}", path.display(), result);
+        }
+    }
+
+    #[test]
+    fn foreign_calls_are_checked_against_dependencies() {
+        let math = Module::from_string("pub Int add(Int a, Int b) {\n return a + b\n}", 0).unwrap();
+        let dependencies = HashMap::from([("math".to_string(), math)]);
+        let source = "function Int main()\n push 1\n push 2\n foreign_call math:add\n return\n";
+        let module = assemble(source, 0, &dependencies).unwrap();
+
+        assert!(verify(&module, &dependencies).is_ok());
+        let error = verify(&module, &HashMap::new()).unwrap_err();
+        assert!(error.contains("module 'math' is not loaded"), "{}", error);
+
+        let other = Module::from_string("pub Int add(Int a, Float b) {\n return a\n}", 0).unwrap();
+        let dependencies = HashMap::from([("math".to_string(), other)]);
+        let error = verify(&module, &dependencies).unwrap_err();
+        assert!(error.contains("expected float, found int"), "{}", error);
+
+        let private = Module::from_string("Int add(Int a, Int b) {\n return a + b\n}", 0).unwrap();
+        let dependencies = HashMap::from([("math".to_string(), private)]);
+        let error = verify(&module, &dependencies).unwrap_err();
+        assert!(error.contains("is a private function"), "{}", error);
+    }
+
+    #[test]
+    fn corrupted_operands_are_rejected() {
+        let module = single_function(1, vec![Instruction::Jump(7)]);
+        let error = verify(&module, &HashMap::new()).unwrap_err();
+        assert_eq!(
+            error,
+            "function 'main', instruction 0: jump target 7 out of range"
+        );
+
+        let module = single_function(1, vec![Instruction::Load { slot: 1 }, Instruction::Return]);
+        let error = verify(&module, &HashMap::new()).unwrap_err();
+        assert!(error.contains("slot 1 out of range"), "{}", error);
+
+        let module = single_function(0, vec![Instruction::Call { call_slot: 3 }]);
+        let error = verify(&module, &HashMap::new()).unwrap_err();
+        assert!(error.contains("called slot 3 does not exist"), "{}", error);
+
+        let signiture = FunctionSigniture::new(DataType::Int, vec![]);
+        let module = Module::new(
+            Some(2),
+            HashMap::from([("main".to_string(), 0)]),
+            vec![signiture],
+            vec![FunctionSource::new(0, vec![Instruction::Return])],
+        );
+        let error = verify(&module, &HashMap::new()).unwrap_err();
+        assert_eq!(error, "main slot 2 out of range");
+
+        let module = single_function(
+            1000,
+            vec![Instruction::Push(Value::Int(1)), Instruction::Return],
+        );
+        let error = verify(&module, &HashMap::new()).unwrap_err();
+        assert_eq!(error, "function 'main', 1000 slots for 2 instructions");
+    }
+
+    #[test]
+    fn corrupted_function_table_is_rejected() {
+        let signiture = FunctionSigniture::new(DataType::Int, vec![]);
+        let source = FunctionSource::new(
+            0,
+            vec![Instruction::Push(Value::Int(1)), Instruction::Return],
+        );
+
+        // slot 1 is skipped, the function in slot 2 must not be paired with it
+        let module = Module::new(
+            Some(0),
+            HashMap::from([("main".to_string(), 0), ("other".to_string(), 2)]),
+            vec![signiture.clone(); 3],
+            vec![source.clone(); 3],
+        );
+        let error = verify(&module, &HashMap::new()).unwrap_err();
+        assert_eq!(error, "slot 1 has no function");
+
+        let module = Module::new(
+            Some(0),
+            HashMap::from([("main".to_string(), 0), ("other".to_string(), 0)]),
+            vec![signiture.clone(); 2],
+            vec![source.clone(); 2],
+        );
+        let error = verify(&module, &HashMap::new()).unwrap_err();
+        assert!(error.contains("slot 0 is used twice"), "{}", error);
+
+        let mut bytes = vec![];
+        BytecodeHeader::new(Some(0), 2).write_bytecode(&mut bytes);
+        for _ in 0..2 {
+            "main".to_string().write_bytecode(&mut bytes);
+            0u32.write_bytecode(&mut bytes);
+        }
+        let error = Module::from_verified_bytecode(&bytes, &HashMap::new()).unwrap_err();
+        assert_eq!(error, "Function 'main' is defined twice");
+    }
+
+    #[test]
+    fn huge_counts_are_rejected() {
+        let mut bytes = vec![];
+        BytecodeHeader::new(Some(0), u32::MAX).write_bytecode(&mut bytes);
+        assert!(Module::from_verified_bytecode(&bytes, &HashMap::new()).is_err());
+
+        let mut bytes = vec![];
+        BytecodeHeader::new(Some(0), 1).write_bytecode(&mut bytes);
+        write_fn_map_bytecode(&HashMap::from([("main".to_string(), 0)]), &mut bytes);
+        let signiture_start = bytes.len();
+        Visibility::Private.write_bytecode(&mut bytes);
+        DataType::Int.write_bytecode(&mut bytes);
+        u32::MAX.write_bytecode(&mut bytes);
+        assert!(Module::from_verified_bytecode(&bytes, &HashMap::new()).is_err());
+
+        bytes.truncate(signiture_start);
+        FunctionSigniture::new(DataType::Int, vec![]).write_bytecode(&mut bytes);
+        0usize.write_bytecode(&mut bytes);
+        u32::MAX.write_bytecode(&mut bytes);
+        assert!(Module::from_verified_bytecode(&bytes, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn globals_are_checked() {
+        let listing = "global Int count\nfunction Int main()\n load_global 0\n return\n";
+        assert!(verify_listing(listing).is_ok());
+
+        let error = verify_listing("function Int main()\n load_global 0\n return\n").unwrap_err();
+        assert!(
+            error.contains("global slot 0 out of range, module has 0 globals"),
+            "{}",
+            error
+        );
+
+        let listing =
+            "global Int count\ninit\n push 'zero'\n store_global 0\n push void\n return\n";
+        let error = verify_listing(listing).unwrap_err();
+        assert!(error.starts_with("initializer of globals, "), "{}", error);
+        assert!(error.contains("expected int, found string"), "{}", error);
+    }
+
+    #[test]
+    fn control_flow_is_checked() {
+        let error = verify_listing("function Int main()\n push 1\n pop\n").unwrap_err();
+        assert!(
+            error.contains("end of the function without return"),
+            "{}",
+            error
+        );
+
+        let listing = "
+function Int main()
+    push true
+    jump_if_false other
+    push 1
+other:
+    push 2
+    return
+";
+        let error = verify_listing(listing).unwrap_err();
+        assert!(error.contains("differs between paths"), "{}", error);
+
+        let listing = "
+function Int main()
+    push true
+    jump_if_false skip
+    push 1
+    store 0
+skip:
+    load 0
+    return
+";
+        let error = verify_listing(listing).unwrap_err();
+        assert!(error.contains("slot 0 may be loaded before"), "{}", error);
+
+        let error = verify_listing("function Int main()\n return\n").unwrap_err();
+        assert!(error.contains("stack underflow"), "{}", error);
+    }
+
+    #[test]
+    fn instruction_types_are_checked() {
+        let cases = [
+            (
+                "push 1\n push 1.0\n add\n return",
+                "two numbers of the same type",
+            ),
+            ("push 'a'\n return", "expected int, found string"),
+            ("push 1\n not\n push 1\n return", "expected bool, found int"),
+            (
+                "push 'a'\n builtin arg 1\n pop\n push 1\n return",
+                "expected int",
+            ),
+            ("push 1\n method length 0\n return", "method"),
+            ("push 2\n push 'a'\n mul\n return", "found int and string"),
+            ("push 'a'\n push 1\n less\n return", "two Strings"),
+            (
+                "push true\n cast Float\n pop\n push 1\n return",
+                "cannot cast bool to float",
+            ),
+            (
+                "push 1.5\n cast Float\n return",
+                "expected int, found float",
+            ),
+            (
+                "push 1\n push 1u8\n bit_and\n return",
+                "two integers of the same type",
+            ),
+            (
+                "push 1i64\n push 1i64\n shift_left\n pop\n push 1\n return",
+                "expected int, found int64",
+            ),
+        ];
+
+        for (body, expected) in cases {
+            let listing = format!("function Int main()\n {}\n", body);
+            let error = verify_listing(&listing).unwrap_err();
+            assert!(error.contains(expected), "{}: {}", body, error);
+        }
+
+        let signiture = FunctionSigniture::new(
+            DataType::Int,
+            vec![Parameter::new("a".into(), DataType::Int)],
+        );
+        let module = Module::new(
+            Some(0),
+            HashMap::from([("main".to_string(), 0)]),
+            vec![signiture],
+            vec![FunctionSource::new(
+                1,
+                vec![
+                    Instruction::Store { slot: 0 },
+                    Instruction::Load { slot: 0 },
+                    Instruction::Push(Value::Int(1)),
+                    Instruction::Add,
+                    Instruction::Return,
+                ],
+            )],
+        );
+        assert!(verify(&module, &HashMap::new()).is_ok());
+    }
+}